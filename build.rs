@@ -1,5 +1,8 @@
 // build.rs
-// 该脚本后续会用于自动编译 proto 文件，目前先占位
-fn main() {
-    // TODO: 添加 proto 编译逻辑
-}
\ No newline at end of file
+// 编译 proto/ 下的 gRPC 服务定义，protoc 由 protobuf-src 从源码构建，不依赖系统安装
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protobuf_src::protoc());
+    println!("cargo:rerun-if-changed=proto");
+    tonic_build::configure().compile_protos(&["proto/pool_events.proto"], &["proto"])?;
+    Ok(())
+}
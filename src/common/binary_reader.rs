@@ -32,6 +32,13 @@ pub fn read_u128(data: &[u8], offset: &mut usize) -> u128 {
     u128::from_le_bytes(bytes.try_into().unwrap())
 }
 
+// 从字节流读取 i128 类型，并推进 offset
+pub fn read_i128(data: &[u8], offset: &mut usize) -> i128 {
+    let bytes = &data[*offset..*offset+16];
+    *offset += 16;
+    i128::from_le_bytes(bytes.try_into().unwrap())
+}
+
 // 从字节流读取 u32 类型，并推进 offset
 pub fn read_u32(data: &[u8], offset: &mut usize) -> u32 {
     let bytes = &data[*offset..*offset+4];
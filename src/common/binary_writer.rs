@@ -0,0 +1,61 @@
+//! binary_reader 的反向操作：按小端序写入预先分配好的缓冲区，并推进 offset。
+//! 编码账户时先分配完整的账户大小 (全部为 0)，跳过的 padding 保持为 0。
+
+use solana_program::pubkey::Pubkey;
+
+// 向字节流写入 u8 类型，并推进 offset
+pub fn write_u8(data: &mut [u8], offset: &mut usize, value: u8) {
+    data[*offset] = value;
+    *offset += 1;
+}
+
+// 向字节流写入 u16 类型，并推进 offset
+pub fn write_u16(data: &mut [u8], offset: &mut usize, value: u16) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 u32 类型，并推进 offset
+pub fn write_u32(data: &mut [u8], offset: &mut usize, value: u32) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 i32 类型，并推进 offset
+pub fn write_i32(data: &mut [u8], offset: &mut usize, value: i32) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 u64 类型，并推进 offset
+pub fn write_u64(data: &mut [u8], offset: &mut usize, value: u64) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 i64 类型，并推进 offset
+pub fn write_i64(data: &mut [u8], offset: &mut usize, value: i64) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 u128 类型，并推进 offset
+pub fn write_u128(data: &mut [u8], offset: &mut usize, value: u128) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 i128 类型，并推进 offset
+pub fn write_i128(data: &mut [u8], offset: &mut usize, value: i128) {
+    write_bytes(data, offset, &value.to_le_bytes());
+}
+
+// 向字节流写入 bool 类型 (0 或 1)，并推进 offset
+pub fn write_bool(data: &mut [u8], offset: &mut usize, value: bool) {
+    write_u8(data, offset, value as u8);
+}
+
+// 向字节流写入 Pubkey 类型（32字节），并推进 offset
+pub fn write_pubkey(data: &mut [u8], offset: &mut usize, value: &Pubkey) {
+    write_bytes(data, offset, value.as_ref());
+}
+
+// 向字节流原样写入字节数组 (padding、seed 等)，并推进 offset
+pub fn write_bytes(data: &mut [u8], offset: &mut usize, value: &[u8]) {
+    data[*offset..*offset + value.len()].copy_from_slice(value);
+    *offset += value.len();
+}
@@ -1,2 +1,6 @@
 pub mod binary_reader;
-pub mod utils;
\ No newline at end of file
+pub mod binary_writer;
+pub mod utils;
+pub mod view;
+pub mod serde_helpers;
+pub mod snapshot;
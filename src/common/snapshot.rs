@@ -0,0 +1,251 @@
+//! Account snapshots in the standard Solana JSON formats, loaded into a `Pubkey → Account` map.
+//!
+//! Accepted shapes:
+//! - `solana account <pubkey> --output json` (`{"pubkey": ..., "account": {...}}`)
+//! - a list of those, which is also the `getProgramAccounts` result
+//! - `getAccountInfo` / `getMultipleAccounts` responses, whose values carry no pubkey, so
+//!   the requested keys are passed alongside ([`Snapshot::from_account_info`],
+//!   [`Snapshot::from_multiple_accounts`])
+//!
+//! Full JSON-RPC envelopes, bare `result`s and `{"context", "value"}` wrappers all work.
+//! Account data may be base58, base64, base64+zstd or jsonParsed. jsonParsed is turned back
+//! into bytes for SPL Token accounts and mints, the only parsed data the decoders consume.
+
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail, Context};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Deserialize;
+use serde_json::Value;
+use solana_account_decoder::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::account::Account;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshot {
+    /// Context slot of the RPC response, if the input had one
+    pub slot: Option<u64>,
+    pub accounts: BTreeMap<Pubkey, Account>,
+}
+
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+impl Snapshot {
+    /// Loads a file holding any of the keyed shapes
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::from_json(&text).with_context(|| format!("loading {}", path.display()))
+    }
+
+    /// Parses a CLI dump, a list of them or a `getProgramAccounts` response
+    pub fn from_json(text: &str) -> anyhow::Result<Self> {
+        let (slot, value) = unwrap_response(serde_json::from_str(text)?)?;
+        let keyed: Vec<KeyedAccount> = match value {
+            Value::Array(_) => serde_json::from_value(value)?,
+            Value::Object(ref fields) if fields.contains_key("pubkey") => vec![serde_json::from_value(value)?],
+            _ => bail!("accounts without pubkeys, load them with from_account_info or from_multiple_accounts"),
+        };
+        let mut snapshot = Self {
+            slot,
+            ..Default::default()
+        };
+        for KeyedAccount { pubkey, account } in keyed {
+            let pubkey = Pubkey::from_str(&pubkey)?;
+            let account = decode_account(&account).with_context(|| format!("account {}", pubkey))?;
+            snapshot.accounts.insert(pubkey, account);
+        }
+        Ok(snapshot)
+    }
+
+    /// Parses a `getAccountInfo` response for `pubkey`, a `null` value gives an empty snapshot
+    pub fn from_account_info(pubkey: Pubkey, text: &str) -> anyhow::Result<Self> {
+        Self::from_multiple_accounts(&[pubkey], text)
+    }
+
+    /// Parses a `getMultipleAccounts` response, values pair up with `pubkeys` by position and
+    /// `null`s (missing accounts) are left out
+    pub fn from_multiple_accounts(pubkeys: &[Pubkey], text: &str) -> anyhow::Result<Self> {
+        let (slot, value) = unwrap_response(serde_json::from_str(text)?)?;
+        let values = match value {
+            Value::Array(values) => values,
+            single => vec![single],
+        };
+        if values.len() != pubkeys.len() {
+            bail!("{} accounts for {} pubkeys", values.len(), pubkeys.len());
+        }
+        let mut snapshot = Self {
+            slot,
+            ..Default::default()
+        };
+        for (pubkey, value) in pubkeys.iter().zip(values) {
+            if value.is_null() {
+                continue;
+            }
+            let account: UiAccount = serde_json::from_value(value)?;
+            let account = decode_account(&account).with_context(|| format!("account {}", pubkey))?;
+            snapshot.accounts.insert(*pubkey, account);
+        }
+        Ok(snapshot)
+    }
+
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Accounts of this snapshot owned by `program`
+    pub fn owned_by<'a>(&'a self, program: &'a Pubkey) -> impl Iterator<Item = (&'a Pubkey, &'a Account)> + 'a {
+        self.accounts.iter().filter(move |(_, account)| account.owner == *program)
+    }
+
+    /// Adds `other`'s accounts, the newer context slot wins on conflicts
+    pub fn merge(&mut self, other: Snapshot) {
+        if other.slot >= self.slot {
+            self.accounts.extend(other.accounts);
+            self.slot = other.slot;
+        } else {
+            for (pubkey, account) in other.accounts {
+                self.accounts.entry(pubkey).or_insert(account);
+            }
+        }
+    }
+}
+
+/// Strips the JSON-RPC envelope and the `{"context", "value"}` wrapper
+fn unwrap_response(mut value: Value) -> anyhow::Result<(Option<u64>, Value)> {
+    if let Some(error) = value.get("error") {
+        bail!("RPC error: {}", error);
+    }
+    if value.get("jsonrpc").is_some() {
+        value = value.get_mut("result").map(Value::take).ok_or_else(|| anyhow!("response has no result"))?;
+    }
+    match value {
+        Value::Object(mut fields) if fields.contains_key("context") && fields.contains_key("value") => {
+            let slot = fields["context"]["slot"].as_u64();
+            Ok((slot, fields.remove("value").unwrap()))
+        }
+        other => Ok((None, other)),
+    }
+}
+
+/// [`UiAccount`] back to its on-chain form, whatever the data encoding
+pub fn decode_account(account: &UiAccount) -> anyhow::Result<Account> {
+    Ok(Account {
+        lamports: account.lamports,
+        data: decode_data(&account.data)?,
+        owner: Pubkey::from_str(&account.owner)?,
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+    })
+}
+
+fn decode_data(data: &UiAccountData) -> anyhow::Result<Vec<u8>> {
+    match data {
+        UiAccountData::LegacyBinary(blob) => Ok(bs58::decode(blob).into_vec()?),
+        UiAccountData::Binary(blob, UiAccountEncoding::Base58) => Ok(bs58::decode(blob).into_vec()?),
+        UiAccountData::Binary(blob, UiAccountEncoding::Base64) => Ok(BASE64.decode(blob)?),
+        UiAccountData::Binary(blob, UiAccountEncoding::Base64Zstd) => {
+            Ok(zstd::decode_all(BASE64.decode(blob)?.as_slice()).context("invalid zstd data")?)
+        }
+        UiAccountData::Binary(_, encoding) => bail!("{:?} is not a binary encoding", encoding),
+        UiAccountData::Json(parsed) => encode_parsed(&parsed.program, &parsed.parsed),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "info", rename_all = "camelCase")]
+enum ParsedToken {
+    Account(ParsedTokenAccount),
+    Mint(ParsedMint),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParsedTokenAccount {
+    mint: String,
+    owner: String,
+    token_amount: ParsedAmount,
+    #[serde(default)]
+    delegate: Option<String>,
+    state: String,
+    is_native: bool,
+    #[serde(default)]
+    rent_exempt_reserve: Option<ParsedAmount>,
+    #[serde(default)]
+    delegated_amount: Option<ParsedAmount>,
+    #[serde(default)]
+    close_authority: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParsedMint {
+    #[serde(default)]
+    mint_authority: Option<String>,
+    supply: String,
+    decimals: u8,
+    is_initialized: bool,
+    #[serde(default)]
+    freeze_authority: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ParsedAmount {
+    amount: String,
+}
+
+/// Re-packs jsonParsed SPL Token data, other programs' parsed forms are rejected
+fn encode_parsed(program: &str, parsed: &Value) -> anyhow::Result<Vec<u8>> {
+    if program != "spl-token" {
+        bail!("jsonParsed data of {} cannot be turned back into bytes, fetch it as base64", program);
+    }
+    let key = |s: &str| Pubkey::from_str(s).with_context(|| format!("invalid pubkey {}", s));
+    let optional_key = |s: &Option<String>| s.as_deref().map(key).transpose().map(COption::from);
+    let amount = |a: &ParsedAmount| a.amount.parse::<u64>().with_context(|| format!("invalid amount {}", a.amount));
+
+    match serde_json::from_value::<ParsedToken>(parsed.clone())? {
+        ParsedToken::Account(info) => {
+            let state = match info.state.as_str() {
+                "uninitialized" => AccountState::Uninitialized,
+                "initialized" => AccountState::Initialized,
+                "frozen" => AccountState::Frozen,
+                other => bail!("unknown token account state {}", other),
+            };
+            let is_native = match (info.is_native, &info.rent_exempt_reserve) {
+                (true, Some(reserve)) => COption::Some(amount(reserve)?),
+                (true, None) => bail!("native token account without rentExemptReserve"),
+                (false, _) => COption::None,
+            };
+            let account = TokenAccount {
+                mint: key(&info.mint)?,
+                owner: key(&info.owner)?,
+                amount: amount(&info.token_amount)?,
+                delegate: optional_key(&info.delegate)?,
+                state,
+                is_native,
+                delegated_amount: info.delegated_amount.as_ref().map(amount).transpose()?.unwrap_or(0),
+                close_authority: optional_key(&info.close_authority)?,
+            };
+            let mut data = vec![0; TokenAccount::LEN];
+            TokenAccount::pack(account, &mut data)?;
+            Ok(data)
+        }
+        ParsedToken::Mint(info) => {
+            let mint = Mint {
+                mint_authority: optional_key(&info.mint_authority)?,
+                supply: info.supply.parse().with_context(|| format!("invalid supply {}", info.supply))?,
+                decimals: info.decimals,
+                is_initialized: info.is_initialized,
+                freeze_authority: optional_key(&info.freeze_authority)?,
+            };
+            let mut data = vec![0; Mint::LEN];
+            Mint::pack(mint, &mut data)?;
+            Ok(data)
+        }
+    }
+}
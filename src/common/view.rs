@@ -0,0 +1,22 @@
+use bytemuck::Pod;
+
+/// Zero-copy view over account bytes.
+///
+/// Implementors are `#[repr(C, packed)]` Pod structs matching the on-chain layout, so they have
+/// alignment 1 and can be borrowed from any `&[u8]`. `view` checks the length (and, through
+/// bytemuck, alignment) and borrows the leading `SIZE` bytes; trailing bytes such as Token-2022
+/// extensions are ignored. Anchor accounts set `DISCRIMINATOR` and data starting with anything
+/// else is rejected.
+pub trait AccountView: Pod {
+    const SIZE: usize = std::mem::size_of::<Self>();
+    const DISCRIMINATOR: Option<[u8; 8]> = None;
+
+    fn view(data: &[u8]) -> Option<&Self> {
+        if let Some(discriminator) = Self::DISCRIMINATOR {
+            if data.get(..8)? != discriminator {
+                return None;
+            }
+        }
+        bytemuck::try_from_bytes(data.get(..Self::SIZE)?).ok()
+    }
+}
@@ -1,14 +1,27 @@
+use bytemuck::{Pod, Zeroable};
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
-use crate::common::binary_reader::{read_pubkey, read_u64, read_u8, read_u16, read_u32, read_i32, read_i64, read_u128};
+use crate::common::serde_helpers::{i128_string, pubkey_string, u128_array_string, u128_string};
+use crate::common::view::AccountView;
+use crate::common::binary_reader::{read_pubkey, read_u64, read_u8, read_u16, read_u32, read_i32, read_i64, read_u128, read_i128};
+use crate::common::binary_writer::{
+    write_bytes, write_i128, write_i32, write_i64, write_pubkey, write_u128, write_u16, write_u32, write_u64, write_u8,
+};
 
 // 账户数据大小常量
 pub const METEORA_DLMM_POOL_SIZE: usize = 904;
 pub const METEORA_DLMM_ORACLE_SIZE: usize = 3232; // Oracle 账户大小常量
 pub const METEORA_DLMM_BIN_ARRAY_SIZE: usize = 10136; // 添加 BinArray 大小常量
+pub const METEORA_DLMM_BINS_PER_ARRAY: usize = 70;
 
+// Anchor 账户 discriminator: sha256("account:<Name>")[..8]
+pub const METEORA_DLMM_POOL_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+pub const METEORA_DLMM_ORACLE_DISCRIMINATOR: [u8; 8] = [139, 194, 131, 179, 140, 179, 229, 244];
+pub const METEORA_DLMM_BIN_ARRAY_DISCRIMINATOR: [u8; 8] = [92, 142, 92, 220, 5, 148, 70, 181];
 
-#[derive(Debug)]
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct StaticParameters {
     pub base_factor: u16,
     pub filter_period: u16,
@@ -23,7 +36,7 @@ pub struct StaticParameters {
     pub padding: [u8; 5], // 新增
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct VariableParameters {
     pub volatility_accumulator: u32,
     pub volatility_reference: u32,
@@ -31,25 +44,29 @@ pub struct VariableParameters {
     pub last_update_timestamp: i64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ProtocolFee {
     pub amount_x: u64,
     pub amount_y: u64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct RewardInfo {
+    #[serde(with = "pubkey_string")]
     pub mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub vault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub funder: Pubkey,
     pub reward_duration: u64,
     pub reward_duration_end: u64,
-    pub reward_rate: u64,
+    #[serde(with = "u128_string")]
+    pub reward_rate: u128,
     pub last_update_time: u64,
     pub cumulative_seconds_with_empty_liquidity_reward: u64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct MeteoraLayout {
     pub parameters: StaticParameters,
     pub v_parameters: VariableParameters,
@@ -63,12 +80,18 @@ pub struct MeteoraLayout {
     pub base_factor_seed: [u8; 2],
     pub activation_type: u8,
     pub creator_pool_on_off_control: u8,
+    #[serde(with = "pubkey_string")]
     pub token_x_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_y_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub reserve_x: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub reserve_y: Pubkey,
     pub protocol_fee: ProtocolFee,
     pub reward_infos: [RewardInfo; 2],
+    #[serde(with = "pubkey_string")]
+    pub oracle: Pubkey,
     pub bin_array_bitmap: [u64; 16],
     pub last_updated_at: i64,
 }
@@ -171,12 +194,12 @@ impl MeteoraLayout {
             reward_info.funder = read_pubkey(data, &mut offset);
             reward_info.reward_duration = read_u64(data, &mut offset);
             reward_info.reward_duration_end = read_u64(data, &mut offset);
-            reward_info.reward_rate = read_u64(data, &mut offset);
+            reward_info.reward_rate = read_u128(data, &mut offset);
             reward_info.last_update_time = read_u64(data, &mut offset);
             reward_info.cumulative_seconds_with_empty_liquidity_reward = read_u64(data, &mut offset);
         }
 
-        // 确保读取完 RewardInfos 后的 offset 正确
+        let oracle = read_pubkey(data, &mut offset);
 
         // 读取 binArrayBitmap
         let mut bin_array_bitmap = [0u64; 16];
@@ -205,10 +228,78 @@ impl MeteoraLayout {
             reserve_y,
             protocol_fee,
             reward_infos,
+            oracle,
             bin_array_bitmap,
             last_updated_at,
         })
     }
+
+    /// try_from_slice_manual 的逆操作，生成完整的账户数据，结构体里没有的 padding 和尾部字段写 0
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; METEORA_DLMM_POOL_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &METEORA_DLMM_POOL_DISCRIMINATOR);
+
+        let p = &self.parameters;
+        write_u16(&mut data, &mut offset, p.base_factor);
+        write_u16(&mut data, &mut offset, p.filter_period);
+        write_u16(&mut data, &mut offset, p.decay_period);
+        write_u16(&mut data, &mut offset, p.reduction_factor);
+        write_u32(&mut data, &mut offset, p.variable_fee_control);
+        write_u32(&mut data, &mut offset, p.max_volatility_accumulator);
+        write_i32(&mut data, &mut offset, p.min_bin_id);
+        write_i32(&mut data, &mut offset, p.max_bin_id);
+        write_u16(&mut data, &mut offset, p.protocol_share);
+        write_u8(&mut data, &mut offset, p.base_fee_power_factor);
+        write_bytes(&mut data, &mut offset, &p.padding);
+
+        let v = &self.v_parameters;
+        write_u32(&mut data, &mut offset, v.volatility_accumulator);
+        write_u32(&mut data, &mut offset, v.volatility_reference);
+        write_i32(&mut data, &mut offset, v.index_reference);
+        offset += 4; // padding
+        write_i64(&mut data, &mut offset, v.last_update_timestamp);
+        offset += 8; // padding1
+
+        write_bytes(&mut data, &mut offset, &self.bump_seed);
+        write_bytes(&mut data, &mut offset, &self.bin_step_seed);
+        write_u8(&mut data, &mut offset, self.pair_type);
+        write_i32(&mut data, &mut offset, self.active_id);
+        write_u16(&mut data, &mut offset, self.bin_step);
+        write_u8(&mut data, &mut offset, self.status);
+        write_u8(&mut data, &mut offset, self.require_base_factor_seed);
+        write_bytes(&mut data, &mut offset, &self.base_factor_seed);
+        write_u8(&mut data, &mut offset, self.activation_type);
+        write_u8(&mut data, &mut offset, self.creator_pool_on_off_control);
+
+        write_pubkey(&mut data, &mut offset, &self.token_x_mint);
+        write_pubkey(&mut data, &mut offset, &self.token_y_mint);
+        write_pubkey(&mut data, &mut offset, &self.reserve_x);
+        write_pubkey(&mut data, &mut offset, &self.reserve_y);
+        write_u64(&mut data, &mut offset, self.protocol_fee.amount_x);
+        write_u64(&mut data, &mut offset, self.protocol_fee.amount_y);
+        offset += 32; // padding1
+
+        for reward_info in &self.reward_infos {
+            write_pubkey(&mut data, &mut offset, &reward_info.mint);
+            write_pubkey(&mut data, &mut offset, &reward_info.vault);
+            write_pubkey(&mut data, &mut offset, &reward_info.funder);
+            write_u64(&mut data, &mut offset, reward_info.reward_duration);
+            write_u64(&mut data, &mut offset, reward_info.reward_duration_end);
+            write_u128(&mut data, &mut offset, reward_info.reward_rate);
+            write_u64(&mut data, &mut offset, reward_info.last_update_time);
+            write_u64(&mut data, &mut offset, reward_info.cumulative_seconds_with_empty_liquidity_reward);
+        }
+
+        write_pubkey(&mut data, &mut offset, &self.oracle);
+        for item in &self.bin_array_bitmap {
+            write_u64(&mut data, &mut offset, *item);
+        }
+        write_i64(&mut data, &mut offset, self.last_updated_at);
+
+        data
+    }
 }
 
 pub fn print_meteora_layout(account_key: String, data: &MeteoraLayout) {
@@ -268,27 +359,114 @@ pub fn print_meteora_layout(account_key: String, data: &MeteoraLayout) {
             reward.cumulative_seconds_with_empty_liquidity_reward);
     }
     
-    // 删除打印 Oracle 的代码
-    // log::info!("\nOracle: {}", data.oracle);
+    log::info!("\nOracle: {}", data.oracle);
     log::info!("Last Updated At: {}", data.last_updated_at);
     log::info!("======================================================\n");
 }
 
-#[derive(Debug)]
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct StaticParametersView {
+    pub base_factor: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub protocol_share: u16,
+    pub base_fee_power_factor: u8,
+    pub padding: [u8; 5],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct VariableParametersView {
+    pub volatility_accumulator: u32,
+    pub volatility_reference: u32,
+    pub index_reference: i32,
+    pub padding: [u8; 4],
+    pub last_update_timestamp: i64,
+    pub padding1: [u8; 8],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RewardInfoView {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub funder: Pubkey,
+    pub reward_duration: u64,
+    pub reward_duration_end: u64,
+    pub reward_rate: u128,
+    pub last_update_time: u64,
+    pub cumulative_seconds_with_empty_liquidity_reward: u64,
+}
+
+/// LbPair 零拷贝视图，字段顺序与链上账户一致 (包括 oracle 和尾部的激活参数)
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct MeteoraLbPairView {
+    pub discriminator: [u8; 8],
+    pub parameters: StaticParametersView,
+    pub v_parameters: VariableParametersView,
+    pub bump_seed: [u8; 1],
+    pub bin_step_seed: [u8; 2],
+    pub pair_type: u8,
+    pub active_id: i32,
+    pub bin_step: u16,
+    pub status: u8,
+    pub require_base_factor_seed: u8,
+    pub base_factor_seed: [u8; 2],
+    pub activation_type: u8,
+    pub creator_pool_on_off_control: u8,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+    pub protocol_fee_amount_x: u64,
+    pub protocol_fee_amount_y: u64,
+    pub padding1: [u8; 32],
+    pub reward_infos: [RewardInfoView; 2],
+    pub oracle: Pubkey,
+    pub bin_array_bitmap: [u64; 16],
+    pub last_updated_at: i64,
+    pub padding2: [u8; 32],
+    pub pre_activation_swap_address: Pubkey,
+    pub base_key: Pubkey,
+    pub activation_point: u64,
+    pub pre_activation_duration: u64,
+    pub padding3: [u8; 8],
+    pub padding4: u64,
+    pub creator: Pubkey,
+    pub token_mint_x_program_flag: u8,
+    pub token_mint_y_program_flag: u8,
+    pub reserved: [u8; 22],
+}
+
+const _: () = assert!(std::mem::size_of::<MeteoraLbPairView>() == METEORA_DLMM_POOL_SIZE);
+
+impl AccountView for MeteoraLbPairView {
+    const DISCRIMINATOR: Option<[u8; 8]> = Some(METEORA_DLMM_POOL_DISCRIMINATOR);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct OracleLayout {
     pub discriminator: u64,
     pub idx: u64,
     pub active_size: u64,
     pub length: u64,
-    pub bins: Vec<BinData>,
+    pub observations: Vec<Observation>,
 }
 
-#[derive(Debug)]
-pub struct BinData {
-    pub amount_x: u64,
-    pub amount_y: u64,
-    pub price: u64,
-    pub liquidity: u64,
+/// 单个价格观测点，每个 32 字节
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Observation {
+    #[serde(with = "i128_string")]
+    pub cumulative_active_bin_id: i128,
+    pub created_at: i64,
+    pub last_updated_at: i64,
 }
 
 impl OracleLayout {
@@ -298,25 +476,20 @@ impl OracleLayout {
             return None;
         }
 
-        let mut offset = 8; // 跳过 discriminator
+        let mut offset = 0;
 
         let discriminator = read_u64(data, &mut offset);
         let idx = read_u64(data, &mut offset);
         let active_size = read_u64(data, &mut offset);
         let length = read_u64(data, &mut offset);
 
-        let mut bins = Vec::new();
+        // 头部之后是 length 个 Observation，账户按最大容量分配
+        let mut observations = Vec::new();
         while offset + 32 <= data.len() {
-            let amount_x = read_u64(data, &mut offset);
-            let amount_y = read_u64(data, &mut offset);
-            let price = read_u64(data, &mut offset);
-            let liquidity = read_u64(data, &mut offset);
-            
-            bins.push(BinData {
-                amount_x,
-                amount_y,
-                price,
-                liquidity,
+            observations.push(Observation {
+                cumulative_active_bin_id: read_i128(data, &mut offset),
+                created_at: read_i64(data, &mut offset),
+                last_updated_at: read_i64(data, &mut offset),
             });
         }
 
@@ -325,9 +498,30 @@ impl OracleLayout {
             idx,
             active_size,
             length,
-            bins,
+            observations,
         })
     }
+
+    /// try_from_slice_manual 的逆操作，超出账户容量的观测点被忽略，不足的部分写 0。
+    /// 总是写 METEORA_DLMM_ORACLE_DISCRIMINATOR (忽略 discriminator 字段)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; METEORA_DLMM_ORACLE_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &METEORA_DLMM_ORACLE_DISCRIMINATOR);
+        write_u64(&mut data, &mut offset, self.idx);
+        write_u64(&mut data, &mut offset, self.active_size);
+        write_u64(&mut data, &mut offset, self.length);
+
+        let capacity = (METEORA_DLMM_ORACLE_SIZE - offset) / 32;
+        for observation in self.observations.iter().take(capacity) {
+            write_i128(&mut data, &mut offset, observation.cumulative_active_bin_id);
+            write_i64(&mut data, &mut offset, observation.created_at);
+            write_i64(&mut data, &mut offset, observation.last_updated_at);
+        }
+
+        data
+    }
 }
 
 pub fn print_oracle_layout(account_key: String, data: &OracleLayout) {
@@ -339,54 +533,43 @@ pub fn print_oracle_layout(account_key: String, data: &OracleLayout) {
     log::info!("Active Size: {}", data.active_size);
     log::info!("Length: {}", data.length);
     
-    // 打印前 5 个和后 5 个 bin (如果有足够多的 bin)
-    let bin_count = data.bins.len();
-    log::info!("\n总共 {} 个 Bin，显示部分数据:", bin_count);
-    
-    // 打印前 5 个 bin
-    let front_limit = std::cmp::min(5, bin_count);
-    for i in 0..front_limit {
-        let bin = &data.bins[i];
-        log::info!("Bin[{}]: X={}, Y={}, Price={}, Liquidity={}", 
-            i, bin.amount_x, bin.amount_y, bin.price, bin.liquidity);
-    }
-    
-    // 如果 bin 数量超过 10 个，打印省略号
-    if bin_count > 10 {
-        log::info!("...");
-    }
-    
-    // 打印后 5 个 bin
-    if bin_count > 5 {
-        let back_start = std::cmp::max(front_limit, bin_count - 5);
-        for i in back_start..bin_count {
-            let bin = &data.bins[i];
-            log::info!("Bin[{}]: X={}, Y={}, Price={}, Liquidity={}", 
-                i, bin.amount_x, bin.amount_y, bin.price, bin.liquidity);
-        }
+    // 只打印已写入的观测点 (active_size 个)
+    let active = std::cmp::min(data.active_size as usize, data.observations.len());
+    log::info!("\n共 {} 个观测点，已写入 {} 个:", data.observations.len(), active);
+    for (i, observation) in data.observations.iter().enumerate().take(active) {
+        log::info!("Observation[{}]: Cumulative Active Bin ID={}, Created At={}, Last Updated At={}",
+            i, observation.cumulative_active_bin_id, observation.created_at, observation.last_updated_at);
     }
     
     log::info!("==============================================================\n");
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Bin {
     pub amount_x: u64,
     pub amount_y: u64,
-    pub price: u64,
+    #[serde(with = "u128_string")]
+    pub price: u128,
+    #[serde(with = "u128_string")]
     pub liquidity_supply: u128,
-    pub reward_per_token_stored: [u64; 2],
-    pub fee_amount_x_per_token_stored: u64,
-    pub fee_amount_y_per_token_stored: u64,
-    pub amount_x_in: u64,
-    pub amount_y_in: u64,
+    #[serde(with = "u128_array_string")]
+    pub reward_per_token_stored: [u128; 2],
+    #[serde(with = "u128_string")]
+    pub fee_amount_x_per_token_stored: u128,
+    #[serde(with = "u128_string")]
+    pub fee_amount_y_per_token_stored: u128,
+    #[serde(with = "u128_string")]
+    pub amount_x_in: u128,
+    #[serde(with = "u128_string")]
+    pub amount_y_in: u128,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct BinArrayLayout {
     pub index: i64,
     pub version: u8,
     pub padding: [u8; 7],
+    #[serde(with = "pubkey_string")]
     pub lb_pair: Pubkey,
     pub bins: Vec<Bin>,
 }
@@ -409,18 +592,18 @@ impl BinArrayLayout {
 
         let lb_pair = read_pubkey(data, &mut offset);
 
-        let mut bins = Vec::with_capacity(70);
-        for _ in 0..70 {
+        let mut bins = Vec::with_capacity(METEORA_DLMM_BINS_PER_ARRAY);
+        for _ in 0..METEORA_DLMM_BINS_PER_ARRAY {
             let bin = Bin {
                 amount_x: read_u64(data, &mut offset),
                 amount_y: read_u64(data, &mut offset),
-                price: read_u64(data, &mut offset),
+                price: read_u128(data, &mut offset),
                 liquidity_supply: read_u128(data, &mut offset),
-                reward_per_token_stored: [read_u64(data, &mut offset), read_u64(data, &mut offset)],
-                fee_amount_x_per_token_stored: read_u64(data, &mut offset),
-                fee_amount_y_per_token_stored: read_u64(data, &mut offset),
-                amount_x_in: read_u64(data, &mut offset),
-                amount_y_in: read_u64(data, &mut offset),
+                reward_per_token_stored: [read_u128(data, &mut offset), read_u128(data, &mut offset)],
+                fee_amount_x_per_token_stored: read_u128(data, &mut offset),
+                fee_amount_y_per_token_stored: read_u128(data, &mut offset),
+                amount_x_in: read_u128(data, &mut offset),
+                amount_y_in: read_u128(data, &mut offset),
             };
             bins.push(bin);
         }
@@ -433,6 +616,34 @@ impl BinArrayLayout {
             bins,
         })
     }
+
+    /// try_from_slice_manual 的逆操作，只写前 70 个 bin，不足的部分写 0
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; METEORA_DLMM_BIN_ARRAY_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &METEORA_DLMM_BIN_ARRAY_DISCRIMINATOR);
+        write_i64(&mut data, &mut offset, self.index);
+        write_u8(&mut data, &mut offset, self.version);
+        write_bytes(&mut data, &mut offset, &self.padding);
+        write_pubkey(&mut data, &mut offset, &self.lb_pair);
+
+        for bin in self.bins.iter().take(METEORA_DLMM_BINS_PER_ARRAY) {
+            write_u64(&mut data, &mut offset, bin.amount_x);
+            write_u64(&mut data, &mut offset, bin.amount_y);
+            write_u128(&mut data, &mut offset, bin.price);
+            write_u128(&mut data, &mut offset, bin.liquidity_supply);
+            for reward in &bin.reward_per_token_stored {
+                write_u128(&mut data, &mut offset, *reward);
+            }
+            write_u128(&mut data, &mut offset, bin.fee_amount_x_per_token_stored);
+            write_u128(&mut data, &mut offset, bin.fee_amount_y_per_token_stored);
+            write_u128(&mut data, &mut offset, bin.amount_x_in);
+            write_u128(&mut data, &mut offset, bin.amount_y_in);
+        }
+
+        data
+    }
 }
 
 pub fn print_bin_array_layout(account_key: String, data: &BinArrayLayout) {
@@ -1,11 +1,16 @@
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
+use crate::common::serde_helpers::{pubkey_string};
 use crate::common::binary_reader::{read_pubkey, read_u64, read_u8, read_bool};
+use crate::common::binary_writer::{write_bool, write_bytes, write_pubkey, write_u64, write_u8};
 
 // 账户数据大小常量
 pub const METEORA_POOLS_SIZE: usize = 944;
+/// Anchor 账户 discriminator: sha256("account:Pool")[..8]
+pub const METEORA_POOLS_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct PoolFees {
     pub trade_fee_numerator: u64,
     pub trade_fee_denominator: u64,
@@ -13,46 +18,59 @@ pub struct PoolFees {
     pub protocol_trade_fee_denominator: u64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Bootstrapping {
     pub activation_point: u64,
+    #[serde(with = "pubkey_string")]
     pub whitelisted_vault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub pool_creator: Pubkey,
     pub activation_type: u8,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct PartnerInfo {
     pub fee_numerator: u64,
+    #[serde(with = "pubkey_string")]
     pub partner_authority: Pubkey,
     pub pending_fee_a: u64,
     pub pending_fee_b: u64,
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Padding {
     pub padding0: [u8; 6],
     pub padding1: [u64; 21],
     pub padding2: [u64; 21],
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct MeteoraPools {
+    #[serde(with = "pubkey_string")]
     pub lp_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_a_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_b_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub a_vault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub b_vault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub a_vault_lp: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub b_vault_lp: Pubkey,
     pub a_vault_lp_bump: u8,
     pub enabled: bool,
+    #[serde(with = "pubkey_string")]
     pub protocol_token_a_fee: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub protocol_token_b_fee: Pubkey,
     pub fee_last_updated_at: u64,
     pub padding0: [u8; 24],
     pub fees: PoolFees,
-    pub pool_type: u8,  // 0 = permissionless
+    pub pool_type: u8,  // 0 = permissioned, 1 = permissionless
+    #[serde(with = "pubkey_string")]
     pub stake: Pubkey,
     pub total_locked_lp: u64,
     pub bootstrapping: Bootstrapping,
@@ -153,6 +171,55 @@ impl MeteoraPools {
             curve_type,
         })
     }
+
+    /// try_from_slice_manual 的逆操作，生成完整的账户数据，curve_type 之后的部分写 0
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; METEORA_POOLS_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &METEORA_POOLS_DISCRIMINATOR);
+        write_pubkey(&mut data, &mut offset, &self.lp_mint);
+        write_pubkey(&mut data, &mut offset, &self.token_a_mint);
+        write_pubkey(&mut data, &mut offset, &self.token_b_mint);
+        write_pubkey(&mut data, &mut offset, &self.a_vault);
+        write_pubkey(&mut data, &mut offset, &self.b_vault);
+        write_pubkey(&mut data, &mut offset, &self.a_vault_lp);
+        write_pubkey(&mut data, &mut offset, &self.b_vault_lp);
+        write_u8(&mut data, &mut offset, self.a_vault_lp_bump);
+        write_bool(&mut data, &mut offset, self.enabled);
+        write_pubkey(&mut data, &mut offset, &self.protocol_token_a_fee);
+        write_pubkey(&mut data, &mut offset, &self.protocol_token_b_fee);
+        write_u64(&mut data, &mut offset, self.fee_last_updated_at);
+        write_bytes(&mut data, &mut offset, &self.padding0);
+
+        write_u64(&mut data, &mut offset, self.fees.trade_fee_numerator);
+        write_u64(&mut data, &mut offset, self.fees.trade_fee_denominator);
+        write_u64(&mut data, &mut offset, self.fees.protocol_trade_fee_numerator);
+        write_u64(&mut data, &mut offset, self.fees.protocol_trade_fee_denominator);
+
+        write_u8(&mut data, &mut offset, self.pool_type);
+        write_pubkey(&mut data, &mut offset, &self.stake);
+        write_u64(&mut data, &mut offset, self.total_locked_lp);
+
+        write_u64(&mut data, &mut offset, self.bootstrapping.activation_point);
+        write_pubkey(&mut data, &mut offset, &self.bootstrapping.whitelisted_vault);
+        write_pubkey(&mut data, &mut offset, &self.bootstrapping.pool_creator);
+        write_u8(&mut data, &mut offset, self.bootstrapping.activation_type);
+
+        write_u64(&mut data, &mut offset, self.partner_info.fee_numerator);
+        write_pubkey(&mut data, &mut offset, &self.partner_info.partner_authority);
+        write_u64(&mut data, &mut offset, self.partner_info.pending_fee_a);
+        write_u64(&mut data, &mut offset, self.partner_info.pending_fee_b);
+
+        write_bytes(&mut data, &mut offset, &self.padding.padding0);
+        for value in self.padding.padding1.iter().chain(&self.padding.padding2) {
+            write_u64(&mut data, &mut offset, *value);
+        }
+
+        write_u8(&mut data, &mut offset, self.curve_type);
+
+        data
+    }
 }
 
 pub fn print_meteora_pools_layout(account_key: String, data: &MeteoraPools) {
@@ -175,7 +242,12 @@ pub fn print_meteora_pools_layout(account_key: String, data: &MeteoraPools) {
     log::info!("  Trade Fee: {}/{}", data.fees.trade_fee_numerator, data.fees.trade_fee_denominator);
     log::info!("  Protocol Trade Fee: {}/{}", data.fees.protocol_trade_fee_numerator, data.fees.protocol_trade_fee_denominator);
     
-    log::info!("\nPool Type: {}", if data.pool_type == 0 { "Permissionless" } else { "Unknown" });
+    let pool_type = match data.pool_type {
+        0 => "Permissioned",
+        1 => "Permissionless",
+        _ => "Unknown",
+    };
+    log::info!("\nPool Type: {}", pool_type);
     log::info!("Stake: {}", data.stake);
     log::info!("Total Locked LP: {}", data.total_locked_lp);
     
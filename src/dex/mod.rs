@@ -4,4 +4,75 @@ pub mod raydium_clmm;
 pub mod solfi;
 pub mod meteora_dlmm;
 pub mod meteora_pools; // 已存在
+pub mod pda;
+pub mod spl_token;
 
+use serde::{Deserialize, Serialize};
+use solana_program::{hash::hash, pubkey, pubkey::Pubkey};
+
+/// 支持解析的 DEX 程序，序列化时使用 tag() 相同的短标签
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dex {
+    RaydiumAmmV4,
+    RaydiumCpmm,
+    RaydiumClmm,
+    MeteoraDlmm,
+    MeteoraPools,
+    #[serde(rename = "solfi")]
+    SolFi,
+    OpenBook,
+}
+
+impl Dex {
+    pub const ALL: [Dex; 7] = [
+        Dex::RaydiumAmmV4,
+        Dex::RaydiumCpmm,
+        Dex::RaydiumClmm,
+        Dex::MeteoraDlmm,
+        Dex::MeteoraPools,
+        Dex::SolFi,
+        Dex::OpenBook,
+    ];
+
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            Dex::RaydiumAmmV4 => pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"),
+            Dex::RaydiumCpmm => pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"),
+            Dex::RaydiumClmm => pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK"),
+            Dex::MeteoraDlmm => pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo"),
+            Dex::MeteoraPools => pubkey!("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB"),
+            Dex::SolFi => pubkey!("SoLFiHG9TfgtdUXUjWAxi3LtvYuFyDLVhBWxdMZxyCe"),
+            Dex::OpenBook => pubkey!("srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX"),
+        }
+    }
+
+    pub fn from_program_id(program_id: &Pubkey) -> Option<Dex> {
+        Dex::ALL.into_iter().find(|dex| dex.program_id() == *program_id)
+    }
+
+    /// tag() 的反向查找
+    pub fn from_tag(tag: &str) -> Option<Dex> {
+        Dex::ALL.into_iter().find(|dex| dex.tag() == tag)
+    }
+
+    /// 日志和输出里使用的短标签
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Dex::RaydiumAmmV4 => "raydium_amm_v4",
+            Dex::RaydiumCpmm => "raydium_cpmm",
+            Dex::RaydiumClmm => "raydium_clmm",
+            Dex::MeteoraDlmm => "meteora_dlmm",
+            Dex::MeteoraPools => "meteora_pools",
+            Dex::SolFi => "solfi",
+            Dex::OpenBook => "openbook",
+        }
+    }
+}
+
+/// Anchor 账户 discriminator: sha256("account:<name>") 的前 8 字节
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("account:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
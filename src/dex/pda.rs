@@ -0,0 +1,235 @@
+//! 各 DEX 的 PDA 推导，种子与链上程序一致，返回 (地址, bump)
+//!
+//! Raydium v4 见 [`crate::instruction::instruction::get_amm_pda_keys`]。构造指令时用这里的函数
+//! 直接算出账户，不需要额外的 RPC 查询；[`crate::dex_processor::verify`] 用它们检查池子引用的账户。
+
+use solana_program::pubkey::Pubkey;
+
+use super::Dex;
+
+fn find(seeds: &[&[u8]], program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, program)
+}
+
+/// 两个 mint 按字节序排列，(小, 大)
+fn sorted<'a>(a: &'a Pubkey, b: &'a Pubkey) -> (&'a Pubkey, &'a Pubkey) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Raydium CPMM (raydium-cp-swap)
+pub mod raydium_cpmm {
+    use super::*;
+
+    pub const POOL_SEED: &[u8] = b"pool";
+    pub const POOL_VAULT_SEED: &[u8] = b"pool_vault";
+    pub const POOL_LP_MINT_SEED: &[u8] = b"pool_lp_mint";
+    pub const OBSERVATION_SEED: &[u8] = b"observation";
+    pub const AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+
+    fn program() -> Pubkey {
+        Dex::RaydiumCpmm.program_id()
+    }
+
+    /// 用 PDA 创建的池子；创建者也可以用随机 keypair 建池，那种池子地址推导不出来
+    pub fn pool(amm_config: &Pubkey, token0_mint: &Pubkey, token1_mint: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_SEED, amm_config.as_ref(), token0_mint.as_ref(), token1_mint.as_ref()], &program())
+    }
+
+    /// 所有池子共用的 vault 和 LP mint 权限账户
+    pub fn authority() -> (Pubkey, u8) {
+        find(&[AUTH_SEED], &program())
+    }
+
+    pub fn vault(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_VAULT_SEED, pool.as_ref(), mint.as_ref()], &program())
+    }
+
+    pub fn lp_mint(pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_LP_MINT_SEED, pool.as_ref()], &program())
+    }
+
+    pub fn observation(pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[OBSERVATION_SEED, pool.as_ref()], &program())
+    }
+}
+
+/// Raydium CLMM
+pub mod raydium_clmm {
+    use super::*;
+
+    pub const POOL_SEED: &[u8] = b"pool";
+    pub const POOL_VAULT_SEED: &[u8] = b"pool_vault";
+    pub const OBSERVATION_SEED: &[u8] = b"observation";
+    pub const TICK_ARRAY_SEED: &[u8] = b"tick_array";
+    pub const POOL_TICK_ARRAY_BITMAP_SEED: &[u8] = b"pool_tick_array_bitmap_extension";
+    /// 每个 tick array 的 tick 数
+    pub const TICK_ARRAY_SIZE: i32 = 60;
+
+    fn program() -> Pubkey {
+        Dex::RaydiumClmm.program_id()
+    }
+
+    pub fn pool(amm_config: &Pubkey, token_mint0: &Pubkey, token_mint1: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_SEED, amm_config.as_ref(), token_mint0.as_ref(), token_mint1.as_ref()], &program())
+    }
+
+    pub fn vault(pool: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_VAULT_SEED, pool.as_ref(), mint.as_ref()], &program())
+    }
+
+    /// 早期的池子 observation 是 keypair 账户，不一定等于这个地址
+    pub fn observation(pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[OBSERVATION_SEED, pool.as_ref()], &program())
+    }
+
+    /// 包含 `tick` 的 tick array 的起始 tick (向下取整)
+    pub fn tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
+        let ticks_in_array = TICK_ARRAY_SIZE * i32::from(tick_spacing);
+        tick.div_euclid(ticks_in_array) * ticks_in_array
+    }
+
+    /// 种子里的起始 tick 是大端序
+    pub fn tick_array(pool: &Pubkey, start_index: i32) -> (Pubkey, u8) {
+        find(&[TICK_ARRAY_SEED, pool.as_ref(), &start_index.to_be_bytes()], &program())
+    }
+
+    pub fn tick_array_bitmap_extension(pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[POOL_TICK_ARRAY_BITMAP_SEED, pool.as_ref()], &program())
+    }
+}
+
+/// Meteora DLMM
+pub mod meteora_dlmm {
+    use super::*;
+
+    pub const ORACLE_SEED: &[u8] = b"oracle";
+    pub const BIN_ARRAY_SEED: &[u8] = b"bin_array";
+    pub const BIN_ARRAY_BITMAP_SEED: &[u8] = b"bitmap";
+    /// 每个 bin array 的 bin 数
+    pub const MAX_BIN_PER_ARRAY: i32 = 70;
+
+    fn program() -> Pubkey {
+        Dex::MeteoraDlmm.program_id()
+    }
+
+    /// 无权限 LbPair，两个 mint 顺序无关
+    pub fn lb_pair(token_x_mint: &Pubkey, token_y_mint: &Pubkey, bin_step: u16) -> (Pubkey, u8) {
+        let (min, max) = sorted(token_x_mint, token_y_mint);
+        find(&[min.as_ref(), max.as_ref(), &bin_step.to_le_bytes()], &program())
+    }
+
+    /// 种子带 base_factor 的 LbPair (LbPair.require_base_factor_seed == 1)
+    pub fn lb_pair_with_base_factor(
+        token_x_mint: &Pubkey,
+        token_y_mint: &Pubkey,
+        bin_step: u16,
+        base_factor: u16,
+    ) -> (Pubkey, u8) {
+        let (min, max) = sorted(token_x_mint, token_y_mint);
+        find(
+            &[min.as_ref(), max.as_ref(), &bin_step.to_le_bytes(), &base_factor.to_le_bytes()],
+            &program(),
+        )
+    }
+
+    /// 有权限 LbPair，由 base keypair 决定
+    pub fn permission_lb_pair(base: &Pubkey, token_x_mint: &Pubkey, token_y_mint: &Pubkey, bin_step: u16) -> (Pubkey, u8) {
+        let (min, max) = sorted(token_x_mint, token_y_mint);
+        find(&[base.as_ref(), min.as_ref(), max.as_ref(), &bin_step.to_le_bytes()], &program())
+    }
+
+    /// 包含 `bin_id` 的 bin array 序号 (向下取整)
+    pub fn bin_array_index(bin_id: i32) -> i64 {
+        i64::from(bin_id.div_euclid(MAX_BIN_PER_ARRAY))
+    }
+
+    /// 种子里的序号是小端 i64
+    pub fn bin_array(lb_pair: &Pubkey, index: i64) -> (Pubkey, u8) {
+        find(&[BIN_ARRAY_SEED, lb_pair.as_ref(), &index.to_le_bytes()], &program())
+    }
+
+    pub fn bin_array_bitmap_extension(lb_pair: &Pubkey) -> (Pubkey, u8) {
+        find(&[BIN_ARRAY_BITMAP_SEED, lb_pair.as_ref()], &program())
+    }
+
+    pub fn oracle(lb_pair: &Pubkey) -> (Pubkey, u8) {
+        find(&[ORACLE_SEED, lb_pair.as_ref()], &program())
+    }
+
+    pub fn reserve(lb_pair: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        find(&[lb_pair.as_ref(), mint.as_ref()], &program())
+    }
+}
+
+/// Meteora dynamic pools 和它依赖的 dynamic vault 程序
+pub mod meteora_pools {
+    use solana_program::pubkey;
+
+    use super::*;
+
+    pub const VAULT_PROGRAM: Pubkey = pubkey!("24Uqj9JCLxUeoC3hGfh5W3s9FM9uCHDS2SG3LYwBpyTi");
+    /// 每个 mint 的官方 vault 都用这个 base 推导
+    pub const VAULT_BASE_KEY: Pubkey = pubkey!("HWzXGcGHy4tcpYfaRDCyLNzXqBTv3E6BttpCH2vJxArv");
+    pub const VAULT_SEED: &[u8] = b"vault";
+    pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+    pub const LP_MINT_SEED: &[u8] = b"lp_mint";
+    pub const FEE_SEED: &[u8] = b"fee";
+
+    fn program() -> Pubkey {
+        Dex::MeteoraPools.program_id()
+    }
+
+    /// 种子里两个 mint 是 (大, 小) 的顺序，和 DLMM 相反
+    fn pair<'a>(a: &'a Pubkey, b: &'a Pubkey) -> [&'a [u8]; 2] {
+        let (min, max) = sorted(a, b);
+        [max.as_ref(), min.as_ref()]
+    }
+
+    /// 无权限池，`curve_type` 0 = constant product, 1 = stable
+    pub fn permissionless_pool(curve_type: u8, token_a_mint: &Pubkey, token_b_mint: &Pubkey) -> (Pubkey, u8) {
+        let [first, second] = pair(token_a_mint, token_b_mint);
+        find(&[&[curve_type], first, second], &program())
+    }
+
+    /// 指定手续费档位的无权限池
+    pub fn permissionless_pool_with_fee_tier(
+        curve_type: u8,
+        token_a_mint: &Pubkey,
+        token_b_mint: &Pubkey,
+        trade_fee_bps: u64,
+    ) -> (Pubkey, u8) {
+        let [first, second] = pair(token_a_mint, token_b_mint);
+        find(&[&[curve_type], first, second, &trade_fee_bps.to_le_bytes()], &program())
+    }
+
+    pub fn lp_mint(pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[LP_MINT_SEED, pool.as_ref()], &program())
+    }
+
+    /// 池子在 vault 里持有的 vault LP 代币账户
+    pub fn vault_lp(vault: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[vault.as_ref(), pool.as_ref()], &program())
+    }
+
+    pub fn protocol_fee(mint: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+        find(&[FEE_SEED, mint.as_ref(), pool.as_ref()], &program())
+    }
+
+    /// `mint` 的 dynamic vault
+    pub fn vault(mint: &Pubkey) -> (Pubkey, u8) {
+        find(&[VAULT_SEED, mint.as_ref(), VAULT_BASE_KEY.as_ref()], &VAULT_PROGRAM)
+    }
+
+    /// vault 实际存放代币的账户
+    pub fn vault_token(vault: &Pubkey) -> (Pubkey, u8) {
+        find(&[TOKEN_VAULT_SEED, vault.as_ref()], &VAULT_PROGRAM)
+    }
+
+    pub fn vault_lp_mint(vault: &Pubkey) -> (Pubkey, u8) {
+        find(&[LP_MINT_SEED, vault.as_ref()], &VAULT_PROGRAM)
+    }
+}
@@ -1,24 +1,40 @@
+use bytemuck::{Pod, Zeroable};
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
+use crate::common::serde_helpers::{pubkey_string, u128_string};
 use crate::common::binary_reader::{read_pubkey, read_u64, read_u8, read_u16, read_u128};
+use crate::common::binary_writer::{write_bytes, write_i32, write_pubkey, write_u128, write_u16, write_u64, write_u8};
+use crate::common::view::AccountView;
 
 // 账户数据大小常量
 pub const RAYDIUM_CLMM_POOL_SIZE: usize = 1544;
+/// Anchor 账户 discriminator: sha256("account:PoolState")[..8]
+pub const RAYDIUM_CLMM_POOL_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct RaydiumClmmLayout {
     pub bump: [u8; 1],
+    #[serde(with = "pubkey_string")]
     pub amm_config: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub owner: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_mint0: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_mint1: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_vault0: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_vault1: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub observation_key: Pubkey,
     pub mint_decimals0: u8,
     pub mint_decimals1: u8,
     pub tick_spacing: u16,
+    #[serde(with = "u128_string")]
     pub liquidity: u128,
+    #[serde(with = "u128_string")]
     pub sqrt_price_x64: u128,
     pub tick_current: i32,
     pub status: u8,
@@ -98,6 +114,104 @@ impl RaydiumClmmLayout {
             protocol_fees_token1,
         })
     }
+
+    /// try_from_slice_manual 的逆操作，生成完整的账户数据。
+    /// 结构体里没有的字段 (fee_growth、swap amounts、reward_infos 等) 写 0
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; RAYDIUM_CLMM_POOL_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &RAYDIUM_CLMM_POOL_DISCRIMINATOR);
+        write_bytes(&mut data, &mut offset, &self.bump);
+        write_pubkey(&mut data, &mut offset, &self.amm_config);
+        write_pubkey(&mut data, &mut offset, &self.owner);
+        write_pubkey(&mut data, &mut offset, &self.token_mint0);
+        write_pubkey(&mut data, &mut offset, &self.token_mint1);
+        write_pubkey(&mut data, &mut offset, &self.token_vault0);
+        write_pubkey(&mut data, &mut offset, &self.token_vault1);
+        write_pubkey(&mut data, &mut offset, &self.observation_key);
+        write_u8(&mut data, &mut offset, self.mint_decimals0);
+        write_u8(&mut data, &mut offset, self.mint_decimals1);
+        write_u16(&mut data, &mut offset, self.tick_spacing);
+        write_u128(&mut data, &mut offset, self.liquidity);
+        write_u128(&mut data, &mut offset, self.sqrt_price_x64);
+        write_i32(&mut data, &mut offset, self.tick_current);
+        offset += 4; // padding3, padding4
+        offset += 32; // fee_growth_global
+        write_u64(&mut data, &mut offset, self.protocol_fees_token0);
+        write_u64(&mut data, &mut offset, self.protocol_fees_token1);
+        offset += 64; // swap amounts
+        write_u8(&mut data, &mut offset, self.status);
+
+        data
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RaydiumClmmRewardInfoView {
+    pub reward_state: u8,
+    pub open_time: u64,
+    pub end_time: u64,
+    pub last_update_time: u64,
+    pub emissions_per_second_x64: u128,
+    pub reward_total_emissioned: u64,
+    pub reward_claimed: u64,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub authority: Pubkey,
+    pub reward_growth_global_x64: u128,
+}
+
+/// 零拷贝视图，包含 try_from_slice_manual 跳过的 padding 和 fee_growth 等字段
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RaydiumClmmPoolView {
+    pub discriminator: [u8; 8],
+    pub bump: [u8; 1],
+    pub amm_config: Pubkey,
+    pub owner: Pubkey,
+    pub token_mint0: Pubkey,
+    pub token_mint1: Pubkey,
+    pub token_vault0: Pubkey,
+    pub token_vault1: Pubkey,
+    pub observation_key: Pubkey,
+    pub mint_decimals0: u8,
+    pub mint_decimals1: u8,
+    pub tick_spacing: u16,
+    pub liquidity: u128,
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub padding3: u16,
+    pub padding4: u16,
+    pub fee_growth_global0_x64: u128,
+    pub fee_growth_global1_x64: u128,
+    pub protocol_fees_token0: u64,
+    pub protocol_fees_token1: u64,
+    pub swap_in_amount_token0: u128,
+    pub swap_out_amount_token1: u128,
+    pub swap_in_amount_token1: u128,
+    pub swap_out_amount_token0: u128,
+    pub status: u8,
+    pub padding: [u8; 7],
+    pub reward_infos: [RaydiumClmmRewardInfoView; 3],
+    pub tick_array_bitmap: [u64; 16],
+    pub total_fees_token0: u64,
+    pub total_fees_claimed_token0: u64,
+    pub total_fees_token1: u64,
+    pub total_fees_claimed_token1: u64,
+    pub fund_fees_token0: u64,
+    pub fund_fees_token1: u64,
+    pub open_time: u64,
+    pub recent_epoch: u64,
+    pub padding1: [u64; 24],
+    pub padding2: [u64; 32],
+}
+
+const _: () = assert!(std::mem::size_of::<RaydiumClmmPoolView>() == RAYDIUM_CLMM_POOL_SIZE);
+
+impl AccountView for RaydiumClmmPoolView {
+    const DISCRIMINATOR: Option<[u8; 8]> = Some(RAYDIUM_CLMM_POOL_DISCRIMINATOR);
 }
 
 pub fn print_raydium_clmm_layout(account_key: String, data: &RaydiumClmmLayout) {
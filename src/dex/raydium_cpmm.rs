@@ -1,26 +1,43 @@
+use bytemuck::{Pod, Zeroable};
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
+use crate::common::serde_helpers::{pubkey_string};
 use crate::common::binary_reader::{read_pubkey, read_u64, read_u8};
+use crate::common::binary_writer::{write_bytes, write_pubkey, write_u64, write_u8};
+use crate::common::view::AccountView;
 
 
 
 pub const RAYDIUM_CP_POOL_SIZE: usize = 637;
+/// Anchor 账户 discriminator: sha256("account:PoolState")[..8]
+pub const RAYDIUM_CP_POOL_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
 
 
 #[allow(non_snake_case)]
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[allow(dead_code)]
 pub struct RaydiumCpLayout {
     pub discriminator: u64,       // Layout discriminator
+    #[serde(with = "pubkey_string")]
     pub ammConfig: Pubkey,        // AMM Configuration
+    #[serde(with = "pubkey_string")]
     pub poolCreator: Pubkey,      // Pool creator address
+    #[serde(with = "pubkey_string")]
     pub token0Vault: Pubkey,      // Token0 vault address (previously vaultA)
+    #[serde(with = "pubkey_string")]
     pub token1Vault: Pubkey,      // Token1 vault address (previously vaultB)
+    #[serde(with = "pubkey_string")]
     pub lpMint: Pubkey,          // LP token mint address
+    #[serde(with = "pubkey_string")]
     pub token0Mint: Pubkey,       // Token0 mint address (previously mintA)
+    #[serde(with = "pubkey_string")]
     pub token1Mint: Pubkey,       // Token1 mint address (previously mintB)
+    #[serde(with = "pubkey_string")]
     pub token0Program: Pubkey,    // Token program for token0
+    #[serde(with = "pubkey_string")]
     pub token1Program: Pubkey,    // Token program for token1
+    #[serde(with = "pubkey_string")]
     pub observationKey: Pubkey,   // Observation key
     pub authBump: u8,            // Authority bump
     pub status: u8,              // Pool status
@@ -69,6 +86,74 @@ impl RaydiumCpLayout {
             openTime: read_u64(data, &mut offset),            // 新增字段
         })
     }
+
+    /// try_from_slice_manual 的逆操作，生成完整的账户数据。
+    /// 与其他 Anchor 布局一致，总是写 RAYDIUM_CP_POOL_DISCRIMINATOR (忽略 discriminator 字段)，recent_epoch 和 padding 写 0
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; RAYDIUM_CP_POOL_SIZE];
+        let mut offset = 0;
+
+        write_bytes(&mut data, &mut offset, &RAYDIUM_CP_POOL_DISCRIMINATOR);
+        write_pubkey(&mut data, &mut offset, &self.ammConfig);
+        write_pubkey(&mut data, &mut offset, &self.poolCreator);
+        write_pubkey(&mut data, &mut offset, &self.token0Vault);
+        write_pubkey(&mut data, &mut offset, &self.token1Vault);
+        write_pubkey(&mut data, &mut offset, &self.lpMint);
+        write_pubkey(&mut data, &mut offset, &self.token0Mint);
+        write_pubkey(&mut data, &mut offset, &self.token1Mint);
+        write_pubkey(&mut data, &mut offset, &self.token0Program);
+        write_pubkey(&mut data, &mut offset, &self.token1Program);
+        write_pubkey(&mut data, &mut offset, &self.observationKey);
+        write_u8(&mut data, &mut offset, self.authBump);
+        write_u8(&mut data, &mut offset, self.status);
+        write_u8(&mut data, &mut offset, self.lpMintDecimals);
+        write_u8(&mut data, &mut offset, self.mint0Decimals);
+        write_u8(&mut data, &mut offset, self.mint1Decimals);
+        write_u64(&mut data, &mut offset, self.lpSupply);
+        write_u64(&mut data, &mut offset, self.protocolFeesToken0);
+        write_u64(&mut data, &mut offset, self.protocolFeesToken1);
+        write_u64(&mut data, &mut offset, self.fundFeesToken0);
+        write_u64(&mut data, &mut offset, self.fundFeesToken1);
+        write_u64(&mut data, &mut offset, self.openTime);
+
+        data
+    }
+}
+
+/// 零拷贝视图，字段顺序与链上 PoolState 完全一致
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct RaydiumCpPoolView {
+    pub discriminator: [u8; 8],
+    pub amm_config: Pubkey,
+    pub pool_creator: Pubkey,
+    pub token0_vault: Pubkey,
+    pub token1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token0_mint: Pubkey,
+    pub token1_mint: Pubkey,
+    pub token0_program: Pubkey,
+    pub token1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub auth_bump: u8,
+    pub status: u8,
+    pub lp_mint_decimals: u8,
+    pub mint0_decimals: u8,
+    pub mint1_decimals: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token0: u64,
+    pub protocol_fees_token1: u64,
+    pub fund_fees_token0: u64,
+    pub fund_fees_token1: u64,
+    pub open_time: u64,
+    pub recent_epoch: u64,
+    pub padding: [u64; 31],
+}
+
+const _: () = assert!(std::mem::size_of::<RaydiumCpPoolView>() == RAYDIUM_CP_POOL_SIZE);
+
+impl AccountView for RaydiumCpPoolView {
+    const DISCRIMINATOR: Option<[u8; 8]> = Some(RAYDIUM_CP_POOL_DISCRIMINATOR);
 }
 
 pub fn print_raydium_cpmm_layout(ammkey: String, cp_data: &RaydiumCpLayout) {
@@ -1,24 +1,49 @@
 /// Represents the layout of Raydium data, providing methods for#![allow(unused_imports)]
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
-use crate::common::binary_reader::{read_pubkey, read_u64};
+use crate::common::serde_helpers::{pubkey_string, u128_string};
+use crate::common::binary_reader::{read_pubkey, read_u128, read_u64};
+use crate::common::binary_writer::{write_bytes, write_pubkey, write_u128, write_u64};
+use crate::instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4;
 
 
 pub const RAYDIUM_LP_V4_ACCOUNT_SIZE: usize = 752;
 pub const SERUM_MARKET_ACCOUNT_SIZE: usize = 388;
+pub const OPEN_ORDERS_ACCOUNT_SIZE: usize = 3228;
+
+// Serum 账户前后的固定填充
+pub(crate) const SERUM_HEAD_PADDING: &[u8; 5] = b"serum";
+pub(crate) const SERUM_TAIL_PADDING: &[u8; 7] = b"padding";
+
+// serum_dex::state::AccountFlag 中市场账户用到的位
+pub const SERUM_FLAG_INITIALIZED: u64 = 1 << 0;
+pub const SERUM_FLAG_MARKET: u64 = 1 << 1;
+pub const SERUM_FLAG_DISABLED: u64 = 1 << 7;
+pub const SERUM_FLAG_PERMISSIONED: u64 = 1 << 9;
+pub const SERUM_FLAG_CRANK_AUTHORITY_REQUIRED: u64 = 1 << 10;
 
 #[allow(non_snake_case)]
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[allow(dead_code)]
 pub struct RaydiumLpV4Layout {
+    #[serde(with = "pubkey_string")]
     pub baseVault: Pubkey,        // Base Currency Vault Address
+    #[serde(with = "pubkey_string")]
     pub quoteVault: Pubkey,       // Quote Coin Vault Address
+    #[serde(with = "pubkey_string")]
     pub baseMint: Pubkey,         // Base Coin Mint Address
+    #[serde(with = "pubkey_string")]
     pub quoteMint: Pubkey,        // Quote Coin Mint Address
+    #[serde(with = "pubkey_string")]
     pub lpMint: Pubkey,           // LP Token Mint Address
+    #[serde(with = "pubkey_string")]
     pub openOrders: Pubkey,       // OpenOrders Account Address
+    #[serde(with = "pubkey_string")]
     pub marketId: Pubkey,         // Market ID
+    #[serde(with = "pubkey_string")]
     pub marketProgramId: Pubkey,  // Market program ID
+    #[serde(with = "pubkey_string")]
     pub targetOrders: Pubkey,     // target orders - Account Address
 }
 
@@ -44,6 +69,39 @@ impl RaydiumLpV4Layout {
             targetOrders: read_pubkey(data, &mut offset),     // target orders Account Address
         })
     }
+
+    /// A v4 account with only these keys set, everything else zero.
+    /// Build a [`LIQUIDITY_STATE_LAYOUT_V4`] instead when the numeric fields matter.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; RAYDIUM_LP_V4_ACCOUNT_SIZE];
+        let mut offset = 336;
+        write_pubkey(&mut data, &mut offset, &self.baseVault);
+        write_pubkey(&mut data, &mut offset, &self.quoteVault);
+        write_pubkey(&mut data, &mut offset, &self.baseMint);
+        write_pubkey(&mut data, &mut offset, &self.quoteMint);
+        write_pubkey(&mut data, &mut offset, &self.lpMint);
+        write_pubkey(&mut data, &mut offset, &self.openOrders);
+        write_pubkey(&mut data, &mut offset, &self.marketId);
+        write_pubkey(&mut data, &mut offset, &self.marketProgramId);
+        write_pubkey(&mut data, &mut offset, &self.targetOrders);
+        data
+    }
+}
+
+impl From<&LIQUIDITY_STATE_LAYOUT_V4> for RaydiumLpV4Layout {
+    fn from(state: &LIQUIDITY_STATE_LAYOUT_V4) -> Self {
+        Self {
+            baseVault: state.baseVault,
+            quoteVault: state.quoteVault,
+            baseMint: state.baseMint,
+            quoteMint: state.quoteMint,
+            lpMint: state.lpMint,
+            openOrders: state.openOrders,
+            marketId: state.marketId,
+            marketProgramId: state.marketProgramId,
+            targetOrders: state.targetOrders,
+        }
+    }
 }
 
 pub fn print_raydium_lp_v4_layout(ammkey: String, raydium_data: &RaydiumLpV4Layout) {
@@ -61,31 +119,48 @@ pub fn print_raydium_lp_v4_layout(ammkey: String, raydium_data: &RaydiumLpV4Layo
     log::info!("======================================================\n");
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[allow(dead_code)]  // Add this line to suppress the warning
 pub struct SerumMarketLayout {
     pub market_flags: u64,
+    #[serde(with = "pubkey_string")]
     pub own_address: Pubkey,
     pub vault_signer_nonce: u64,
+    #[serde(with = "pubkey_string")]
     pub base_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub quote_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub base_vault: Pubkey,
+    pub base_deposits_total: u64,
+    pub base_fees_accrued: u64,
+    #[serde(with = "pubkey_string")]
     pub quote_vault: Pubkey,
+    pub quote_deposits_total: u64,
+    pub quote_fees_accrued: u64,
+    pub quote_dust_threshold: u64,
+    #[serde(with = "pubkey_string")]
     pub request_queue: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub event_queue: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub bids: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub asks: Pubkey,
     pub base_lot_size: u64,
     pub quote_lot_size: u64,
+    pub fee_rate_bps: u64,
+    pub referrer_rebates_accrued: u64,
 }
 
 impl SerumMarketLayout {
     pub fn slice_market(data: &[u8]) -> Option<Self> {
-        if data.len() < 388 { // 388 is the typical length of a Serum market account
+        if data.len() < SERUM_MARKET_ACCOUNT_SIZE {
             log::error!("Insufficient data length to parse Serum_MarketLayout");
             return None;
         }
-        let mut offset = 0;
+        // Skip the 5-byte "serum" head padding
+        let mut offset = 5;
         Some(Self {
             market_flags: read_u64(data, &mut offset),
             own_address: read_pubkey(data, &mut offset),
@@ -93,19 +168,65 @@ impl SerumMarketLayout {
             base_mint: read_pubkey(data, &mut offset),
             quote_mint: read_pubkey(data, &mut offset),
             base_vault: read_pubkey(data, &mut offset),
+            base_deposits_total: read_u64(data, &mut offset),
+            base_fees_accrued: read_u64(data, &mut offset),
             quote_vault: read_pubkey(data, &mut offset),
+            quote_deposits_total: read_u64(data, &mut offset),
+            quote_fees_accrued: read_u64(data, &mut offset),
+            quote_dust_threshold: read_u64(data, &mut offset),
             request_queue: read_pubkey(data, &mut offset),
             event_queue: read_pubkey(data, &mut offset),
             bids: read_pubkey(data, &mut offset),
             asks: read_pubkey(data, &mut offset),
             base_lot_size: read_u64(data, &mut offset),
             quote_lot_size: read_u64(data, &mut offset),
-            // ...继续解析其他字段
+            fee_rate_bps: read_u64(data, &mut offset),
+            referrer_rebates_accrued: read_u64(data, &mut offset),
         })
     }
+
+    /// Inverse of `slice_market`: a full market account including the "serum"/"padding" markers
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; SERUM_MARKET_ACCOUNT_SIZE];
+        let mut offset = 0;
+        write_bytes(&mut data, &mut offset, SERUM_HEAD_PADDING);
+        write_u64(&mut data, &mut offset, self.market_flags);
+        write_pubkey(&mut data, &mut offset, &self.own_address);
+        write_u64(&mut data, &mut offset, self.vault_signer_nonce);
+        write_pubkey(&mut data, &mut offset, &self.base_mint);
+        write_pubkey(&mut data, &mut offset, &self.quote_mint);
+        write_pubkey(&mut data, &mut offset, &self.base_vault);
+        write_u64(&mut data, &mut offset, self.base_deposits_total);
+        write_u64(&mut data, &mut offset, self.base_fees_accrued);
+        write_pubkey(&mut data, &mut offset, &self.quote_vault);
+        write_u64(&mut data, &mut offset, self.quote_deposits_total);
+        write_u64(&mut data, &mut offset, self.quote_fees_accrued);
+        write_u64(&mut data, &mut offset, self.quote_dust_threshold);
+        write_pubkey(&mut data, &mut offset, &self.request_queue);
+        write_pubkey(&mut data, &mut offset, &self.event_queue);
+        write_pubkey(&mut data, &mut offset, &self.bids);
+        write_pubkey(&mut data, &mut offset, &self.asks);
+        write_u64(&mut data, &mut offset, self.base_lot_size);
+        write_u64(&mut data, &mut offset, self.quote_lot_size);
+        write_u64(&mut data, &mut offset, self.fee_rate_bps);
+        write_u64(&mut data, &mut offset, self.referrer_rebates_accrued);
+        write_bytes(&mut data, &mut offset, SERUM_TAIL_PADDING);
+        data
+    }
+
+    /// 与 serum_dex 的 `MarketState::check_flags` / `MarketStateV2::check_flags` 相同：
+    /// 必须是已初始化的市场，权限市场(V2)可以要求 crank 权限，`allow_disabled` 时允许已禁用的市场
+    pub fn check_flags(&self, allow_disabled: bool) -> bool {
+        let required = SERUM_FLAG_INITIALIZED | SERUM_FLAG_MARKET;
+        let flags = match self.market_flags & SERUM_FLAG_PERMISSIONED {
+            0 => self.market_flags,
+            _ => self.market_flags & !(SERUM_FLAG_PERMISSIONED | SERUM_FLAG_CRANK_AUTHORITY_REQUIRED),
+        };
+        flags == required || (allow_disabled && flags == required | SERUM_FLAG_DISABLED)
+    }
 }
 
-pub fn process_market(pubkey: String, market_data: &SerumMarketLayout) {
+pub fn print_serum_market_layout(pubkey: String, market_data: &SerumMarketLayout) {
     log::info!("\n==================== Serum Market Data ====================");
     log::info!("Market Address: {}", pubkey);
     log::info!("Market Flags: {}", market_data.market_flags);
@@ -121,6 +242,164 @@ pub fn process_market(pubkey: String, market_data: &SerumMarketLayout) {
     log::info!("Asks: {}", market_data.asks);
     log::info!("Base Lot Size: {}", market_data.base_lot_size);
     log::info!("Quote Lot Size: {}", market_data.quote_lot_size);
+    log::info!("Fee Rate Bps: {}", market_data.fee_rate_bps);
     log::info!("==========================================================\n");
-    std::process::exit(0);
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[allow(dead_code)]
+pub struct OpenOrdersLayout {
+    pub account_flags: u64,
+    #[serde(with = "pubkey_string")]
+    pub market: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    pub native_coin_free: u64,   // Base tokens not locked in orders
+    pub native_coin_total: u64,  // Base tokens free + locked in orders
+    pub native_pc_free: u64,     // Quote tokens not locked in orders
+    pub native_pc_total: u64,    // Quote tokens free + locked in orders
+    #[serde(with = "u128_string")]
+    pub free_slot_bits: u128,
+    #[serde(with = "u128_string")]
+    pub is_bid_bits: u128,
+    pub referrer_rebates_accrued: u64,
+}
+
+impl OpenOrdersLayout {
+    pub fn try_from_slice_manual(data: &[u8]) -> Option<Self> {
+        if data.len() < OPEN_ORDERS_ACCOUNT_SIZE {
+            log::error!("Insufficient data length to parse OpenOrdersLayout");
+            return None;
+        }
+
+        // Skip the 5-byte "serum" head padding
+        let mut offset = 5;
+        let account_flags = read_u64(data, &mut offset);
+        let market = read_pubkey(data, &mut offset);
+        let owner = read_pubkey(data, &mut offset);
+        let native_coin_free = read_u64(data, &mut offset);
+        let native_coin_total = read_u64(data, &mut offset);
+        let native_pc_free = read_u64(data, &mut offset);
+        let native_pc_total = read_u64(data, &mut offset);
+        let free_slot_bits = read_u128(data, &mut offset);
+        let is_bid_bits = read_u128(data, &mut offset);
+
+        // Skip orders: [u128; 128] and client_order_ids: [u64; 128]
+        offset += 16 * 128 + 8 * 128;
+        let referrer_rebates_accrued = read_u64(data, &mut offset);
+
+        Some(Self {
+            account_flags,
+            market,
+            owner,
+            native_coin_free,
+            native_coin_total,
+            native_pc_free,
+            native_pc_total,
+            free_slot_bits,
+            is_bid_bits,
+            referrer_rebates_accrued,
+        })
+    }
+
+    /// Inverse of `try_from_slice_manual`. Orders and client order ids are not kept by the
+    /// layout and are written as zeros.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; OPEN_ORDERS_ACCOUNT_SIZE];
+        let mut offset = 0;
+        write_bytes(&mut data, &mut offset, SERUM_HEAD_PADDING);
+        write_u64(&mut data, &mut offset, self.account_flags);
+        write_pubkey(&mut data, &mut offset, &self.market);
+        write_pubkey(&mut data, &mut offset, &self.owner);
+        write_u64(&mut data, &mut offset, self.native_coin_free);
+        write_u64(&mut data, &mut offset, self.native_coin_total);
+        write_u64(&mut data, &mut offset, self.native_pc_free);
+        write_u64(&mut data, &mut offset, self.native_pc_total);
+        write_u128(&mut data, &mut offset, self.free_slot_bits);
+        write_u128(&mut data, &mut offset, self.is_bid_bits);
+        offset += 16 * 128 + 8 * 128;
+        write_u64(&mut data, &mut offset, self.referrer_rebates_accrued);
+        write_bytes(&mut data, &mut offset, SERUM_TAIL_PADDING);
+        data
+    }
+
+    /// Base tokens currently locked in resting orders
+    pub fn native_coin_locked(&self) -> u64 {
+        self.native_coin_total.saturating_sub(self.native_coin_free)
+    }
+
+    /// Quote tokens currently locked in resting orders
+    pub fn native_pc_locked(&self) -> u64 {
+        self.native_pc_total.saturating_sub(self.native_pc_free)
+    }
+}
+
+pub fn print_open_orders_layout(pubkey: String, data: &OpenOrdersLayout) {
+    log::info!("==================== Serum OpenOrders Data ====================");
+    log::info!("OpenOrders Address: {}", pubkey);
+    log::info!("Account Flags: {}", data.account_flags);
+    log::info!("Market: {}", data.market);
+    log::info!("Owner: {}", data.owner);
+    log::info!("Native Coin Free: {}", data.native_coin_free);
+    log::info!("Native Coin Total: {}", data.native_coin_total);
+    log::info!("Native PC Free: {}", data.native_pc_free);
+    log::info!("Native PC Total: {}", data.native_pc_total);
+    log::info!("Referrer Rebates Accrued: {}", data.referrer_rebates_accrued);
+    log::info!("==============================================================\n");
+}
+
+/// One side (base or quote) of a v4 pool's reserves, broken down by where the funds sit
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RaydiumV4SideReserve {
+    pub vault: u64,               // SPL token balance of the AMM vault
+    pub open_orders_free: u64,    // Settled but not yet withdrawn from OpenOrders
+    pub open_orders_locked: u64,  // Locked in resting orderbook orders
+    pub need_take_pnl: u64,       // Owed to the protocol, not part of the pool
+    pub total: u64,               // vault + open_orders total - need_take_pnl
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RaydiumV4Reserves {
+    pub base: RaydiumV4SideReserve,
+    pub quote: RaydiumV4SideReserve,
+}
+
+impl RaydiumV4SideReserve {
+    fn calc(vault: u64, free: u64, total_in_orders: u64, need_take_pnl: u64) -> Option<Self> {
+        let total = vault
+            .checked_add(total_in_orders)?
+            .checked_sub(need_take_pnl)?;
+        Some(Self {
+            vault,
+            open_orders_free: free,
+            open_orders_locked: total_in_orders.saturating_sub(free),
+            need_take_pnl,
+            total,
+        })
+    }
+}
+
+/// Mirrors the on-chain `calc_total_without_take_pnl`: the real pool reserves are the vault
+/// balances plus everything held by the OpenOrders account, minus the pnl owed to the protocol.
+/// Returns `None` where the program would fail with `CheckedAddOverflow`/`CheckedSubOverflow`.
+pub fn calc_total_without_take_pnl(
+    base_vault_amount: u64,
+    quote_vault_amount: u64,
+    open_orders: &OpenOrdersLayout,
+    state: &LIQUIDITY_STATE_LAYOUT_V4,
+) -> Option<RaydiumV4Reserves> {
+    Some(RaydiumV4Reserves {
+        base: RaydiumV4SideReserve::calc(
+            base_vault_amount,
+            open_orders.native_coin_free,
+            open_orders.native_coin_total,
+            state.baseNeedTakePnl,
+        )?,
+        quote: RaydiumV4SideReserve::calc(
+            quote_vault_amount,
+            open_orders.native_pc_free,
+            open_orders.native_pc_total,
+            state.quoteNeedTakePnl,
+        )?,
+    })
 }
@@ -1,23 +1,35 @@
 use log;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
+use crate::common::serde_helpers::{pubkey_string, u128_string};
 use crate::common::binary_reader::{read_pubkey, read_u64, read_u8, read_u16, read_u128};
+use crate::common::binary_writer::{write_i32, write_pubkey, write_u128, write_u16, write_u64, write_u8};
 
 // 账户数据大小常量
 pub const SOLFI_POOL_SIZE: usize = 904;
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct SolFiLayout {
+    #[serde(with = "pubkey_string")]
     pub amm_config: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub owner: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_mint0: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_mint1: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_vault0: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub token_vault1: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub observation_key: Pubkey,
     pub mint_decimals0: u8,
     pub mint_decimals1: u8,
     pub tick_spacing: u16,
+    #[serde(with = "u128_string")]
     pub liquidity: u128,
+    #[serde(with = "u128_string")]
     pub sqrt_price_x64: u128,
     pub tick_current: i32,
     pub status: u8,
@@ -32,7 +44,7 @@ impl SolFiLayout {
             return None;
         }
 
-        let mut offset = 8; // 跳过discriminator
+        let mut offset = 8; // 跳过discriminator (SolFi 布局未公开，无法校验)
         
         offset += 1; // 跳过bump字段
         
@@ -85,6 +97,36 @@ impl SolFiLayout {
             protocol_fees_token1,
         })
     }
+
+    /// try_from_slice_manual 的逆操作。SolFi 没有公开布局，discriminator 和 bump 写 0，
+    /// 只保证能被本解析器读回
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; SOLFI_POOL_SIZE];
+        let mut offset = 8; // discriminator
+
+        offset += 1; // bump
+        write_pubkey(&mut data, &mut offset, &self.amm_config);
+        write_pubkey(&mut data, &mut offset, &self.owner);
+        write_pubkey(&mut data, &mut offset, &self.token_mint0);
+        write_pubkey(&mut data, &mut offset, &self.token_mint1);
+        write_pubkey(&mut data, &mut offset, &self.token_vault0);
+        write_pubkey(&mut data, &mut offset, &self.token_vault1);
+        write_pubkey(&mut data, &mut offset, &self.observation_key);
+        write_u8(&mut data, &mut offset, self.mint_decimals0);
+        write_u8(&mut data, &mut offset, self.mint_decimals1);
+        write_u16(&mut data, &mut offset, self.tick_spacing);
+        write_u128(&mut data, &mut offset, self.liquidity);
+        write_u128(&mut data, &mut offset, self.sqrt_price_x64);
+        write_i32(&mut data, &mut offset, self.tick_current);
+        offset += 4; // padding
+        offset += 32; // fee_growth_global
+        write_u64(&mut data, &mut offset, self.protocol_fees_token0);
+        write_u64(&mut data, &mut offset, self.protocol_fees_token1);
+        offset += 64; // swap amounts
+        write_u8(&mut data, &mut offset, self.status);
+
+        data
+    }
 }
 
 pub fn print_solfi_layout(account_key: String, data: &SolFiLayout) {
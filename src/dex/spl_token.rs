@@ -0,0 +1,139 @@
+use bytemuck::{Pod, Zeroable};
+use log;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use crate::common::serde_helpers::{option_pubkey_string, pubkey_string};
+use crate::common::binary_reader::{read_pubkey, read_u32, read_u64, read_u8};
+use crate::common::binary_writer::{write_pubkey, write_u32, write_u64, write_u8};
+use crate::common::view::AccountView;
+
+// SPL Token 账户数据大小常量
+pub const SPL_TOKEN_ACCOUNT_SIZE: usize = 165;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SplTokenAccountLayout {
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub owner: Pubkey,
+    pub amount: u64,
+    #[serde(with = "option_pubkey_string")]
+    pub delegate: Option<Pubkey>,
+    pub state: u8,              // 0 = Uninitialized, 1 = Initialized, 2 = Frozen
+    pub is_native: Option<u64>,
+    pub delegated_amount: u64,
+    #[serde(with = "option_pubkey_string")]
+    pub close_authority: Option<Pubkey>,
+}
+
+impl SplTokenAccountLayout {
+    pub fn try_from_slice_manual(data: &[u8]) -> Option<Self> {
+        // Token-2022 账户会在 165 字节之后追加扩展数据，这里只解析基础部分
+        if data.len() < SPL_TOKEN_ACCOUNT_SIZE {
+            log::error!("数据长度不足，无法解析 SplTokenAccountLayout: {}", data.len());
+            return None;
+        }
+
+        let mut offset = 0;
+
+        let mint = read_pubkey(data, &mut offset);
+        let owner = read_pubkey(data, &mut offset);
+        let amount = read_u64(data, &mut offset);
+        let delegate = read_coption_pubkey(data, &mut offset);
+        let state = read_u8(data, &mut offset);
+        let is_native = read_coption_u64(data, &mut offset);
+        let delegated_amount = read_u64(data, &mut offset);
+        let close_authority = read_coption_pubkey(data, &mut offset);
+
+        Some(Self {
+            mint,
+            owner,
+            amount,
+            delegate,
+            state,
+            is_native,
+            delegated_amount,
+            close_authority,
+        })
+    }
+
+    /// try_from_slice_manual 的逆操作，生成 165 字节的基础账户 (不含 Token-2022 扩展)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; SPL_TOKEN_ACCOUNT_SIZE];
+        let mut offset = 0;
+
+        write_pubkey(&mut data, &mut offset, &self.mint);
+        write_pubkey(&mut data, &mut offset, &self.owner);
+        write_u64(&mut data, &mut offset, self.amount);
+        write_coption_pubkey(&mut data, &mut offset, self.delegate);
+        write_u8(&mut data, &mut offset, self.state);
+        write_coption_u64(&mut data, &mut offset, self.is_native);
+        write_u64(&mut data, &mut offset, self.delegated_amount);
+        write_coption_pubkey(&mut data, &mut offset, self.close_authority);
+
+        data
+    }
+}
+
+/// 只读取 token 账户的 amount 字段，金库余额更新时不需要解析整个账户
+pub fn read_token_account_amount(data: &[u8]) -> Option<u64> {
+    SplTokenAccountView::view(data).map(|view| view.amount)
+}
+
+/// 零拷贝视图，金库余额更新频率最高，优先用这个读取 amount
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct SplTokenAccountView {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub delegate_tag: u32,
+    pub delegate: Pubkey,
+    pub state: u8,
+    pub is_native_tag: u32,
+    pub is_native: u64,
+    pub delegated_amount: u64,
+    pub close_authority_tag: u32,
+    pub close_authority: Pubkey,
+}
+
+const _: () = assert!(std::mem::size_of::<SplTokenAccountView>() == SPL_TOKEN_ACCOUNT_SIZE);
+
+impl AccountView for SplTokenAccountView {}
+
+// COption 使用 4 字节 tag (0 = None, 1 = Some)，后面紧跟固定长度的值
+fn read_coption_pubkey(data: &[u8], offset: &mut usize) -> Option<Pubkey> {
+    let tag = read_u32(data, offset);
+    let value = read_pubkey(data, offset);
+    (tag == 1).then_some(value)
+}
+
+fn read_coption_u64(data: &[u8], offset: &mut usize) -> Option<u64> {
+    let tag = read_u32(data, offset);
+    let value = read_u64(data, offset);
+    (tag == 1).then_some(value)
+}
+
+fn write_coption_pubkey(data: &mut [u8], offset: &mut usize, value: Option<Pubkey>) {
+    write_u32(data, offset, value.is_some() as u32);
+    write_pubkey(data, offset, &value.unwrap_or_default());
+}
+
+fn write_coption_u64(data: &mut [u8], offset: &mut usize, value: Option<u64>) {
+    write_u32(data, offset, value.is_some() as u32);
+    write_u64(data, offset, value.unwrap_or_default());
+}
+
+pub fn print_spl_token_account_layout(account_key: String, data: &SplTokenAccountLayout) {
+    log::info!("==================== SPL Token Account 数据 ====================");
+    log::info!("Token Account: (https://solscan.io/account/{})", account_key);
+    log::info!("Mint: {}", data.mint);
+    log::info!("Owner: {}", data.owner);
+    log::info!("Amount: {}", data.amount);
+    log::info!("Delegate: {:?}", data.delegate);
+    log::info!("State: {}", data.state);
+    log::info!("Is Native: {:?}", data.is_native);
+    log::info!("Delegated Amount: {}", data.delegated_amount);
+    log::info!("Close Authority: {:?}", data.close_authority);
+    log::info!("======================================================\n");
+}
@@ -0,0 +1,199 @@
+//! Commitment tracking for processed-level streams.
+//!
+//! Updates at `processed` can belong to a fork that never gets confirmed. [`CommitmentTracker`]
+//! follows the slot stream (parents and status changes), keeps every account write per slot
+//! until its slot is confirmed, and hands back a [`Retraction`] for each write that ends up on
+//! a dead or skipped fork.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::stream::{SlotStatus, SlotUpdate};
+
+use super::AccountUpdate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetractReason {
+    /// The slot was marked dead
+    Dead,
+    /// A later slot was confirmed on a chain that does not include this slot
+    Skipped,
+}
+
+/// Account write that should be treated as never having happened
+#[derive(Debug, Clone)]
+pub struct Retraction {
+    pub update: AccountUpdate,
+    pub reason: RetractReason,
+    /// State to fall back to: the newest write still on a live fork, else the last confirmed one
+    pub restored: Option<AccountUpdate>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SlotState {
+    parent: Option<u64>,
+    status: SlotStatus,
+}
+
+#[derive(Debug, Default)]
+struct AccountHistory {
+    confirmed: Option<AccountUpdate>,
+    // unconfirmed writes by (slot, write_version)
+    pending: BTreeMap<(u64, u64), AccountUpdate>,
+}
+
+impl AccountHistory {
+    fn latest(&self) -> Option<&AccountUpdate> {
+        self.pending.values().next_back().or(self.confirmed.as_ref())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CommitmentTracker {
+    slots: BTreeMap<u64, SlotState>,
+    accounts: HashMap<Pubkey, AccountHistory>,
+    confirmed_slot: u64,
+    finalized_slot: u64,
+}
+
+impl CommitmentTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self, slot: u64) -> Option<SlotStatus> {
+        self.slots.get(&slot).map(|state| state.status)
+    }
+
+    pub fn confirmed_slot(&self) -> u64 {
+        self.confirmed_slot
+    }
+
+    pub fn finalized_slot(&self) -> u64 {
+        self.finalized_slot
+    }
+
+    /// Newest write seen for `pubkey`, confirmed or not
+    pub fn latest(&self, pubkey: &Pubkey) -> Option<&AccountUpdate> {
+        self.accounts.get(pubkey)?.latest()
+    }
+
+    /// Newest write for `pubkey` whose slot is confirmed
+    pub fn confirmed(&self, pubkey: &Pubkey) -> Option<&AccountUpdate> {
+        self.accounts.get(pubkey)?.confirmed.as_ref()
+    }
+
+    /// Records a write, `false` (and nothing recorded) when its slot is already known to be dead
+    pub fn on_account(&mut self, update: &AccountUpdate) -> bool {
+        if self.status(update.slot) == Some(SlotStatus::Dead) {
+            return false;
+        }
+        self.accounts
+            .entry(update.pubkey)
+            .or_default()
+            .pending
+            .insert((update.slot, update.write_version), update.clone());
+        true
+    }
+
+    /// Applies a slot status change, returning the writes it invalidated
+    pub fn on_slot(&mut self, update: &SlotUpdate) -> Vec<Retraction> {
+        let state = self.slots.entry(update.slot).or_insert(SlotState {
+            parent: update.parent,
+            status: update.status,
+        });
+        state.parent = update.parent.or(state.parent);
+        // commitment only moves forward, late processed-level notifications do not downgrade it
+        if !matches!(state.status, SlotStatus::Confirmed | SlotStatus::Finalized | SlotStatus::Dead)
+            || update.status == SlotStatus::Finalized
+        {
+            state.status = update.status;
+        }
+
+        match update.status {
+            SlotStatus::Dead => self.retract_fork(update.slot),
+            SlotStatus::Confirmed | SlotStatus::Finalized => {
+                let retractions = self.confirm(update.slot);
+                if update.status == SlotStatus::Finalized {
+                    self.finalize(update.slot);
+                }
+                retractions
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// `slot` and every known descendant are dead
+    fn retract_fork(&mut self, slot: u64) -> Vec<Retraction> {
+        let mut dead = HashSet::from([slot]);
+        // children always have higher slots than their parent, one ascending pass is enough
+        for (&child, state) in self.slots.range_mut(slot + 1..) {
+            if state.parent.is_some_and(|parent| dead.contains(&parent)) {
+                state.status = SlotStatus::Dead;
+                dead.insert(child);
+            }
+        }
+        self.retract(|slot| dead.contains(&slot), RetractReason::Dead)
+    }
+
+    /// Promotes writes on the chain ending at `slot`, retracts writes on forks it skipped
+    fn confirm(&mut self, slot: u64) -> Vec<Retraction> {
+        self.confirmed_slot = self.confirmed_slot.max(slot);
+
+        let mut chain = HashSet::from([slot]);
+        let mut lowest = slot;
+        let mut cursor = slot;
+        while let Some(parent) = self.slots.get(&cursor).and_then(|state| state.parent) {
+            chain.insert(parent);
+            lowest = parent;
+            if let Some(state) = self.slots.get_mut(&parent) {
+                if !matches!(state.status, SlotStatus::Finalized) {
+                    state.status = SlotStatus::Confirmed;
+                }
+            }
+            cursor = parent;
+        }
+
+        // below the oldest known ancestor there is nothing to compare with, assume those are fine
+        let on_chain = |s: u64| s <= slot && (chain.contains(&s) || s < lowest);
+        let skipped = |s: u64| s <= slot && !chain.contains(&s) && s >= lowest;
+        for history in self.accounts.values_mut() {
+            let promoted: Vec<_> = history.pending.keys().copied().filter(|(s, _)| on_chain(*s)).collect();
+            for key in promoted {
+                let update = history.pending.remove(&key).unwrap();
+                if history.confirmed.as_ref().is_none_or(|c| (c.slot, c.write_version) <= key) {
+                    history.confirmed = Some(update);
+                }
+            }
+        }
+        self.retract(skipped, RetractReason::Skipped)
+    }
+
+    fn retract(&mut self, matches: impl Fn(u64) -> bool, reason: RetractReason) -> Vec<Retraction> {
+        let mut retractions = Vec::new();
+        for history in self.accounts.values_mut() {
+            let dropped: Vec<_> = history.pending.keys().copied().filter(|(slot, _)| matches(*slot)).collect();
+            if dropped.is_empty() {
+                continue;
+            }
+            let removed: Vec<_> = dropped.iter().map(|key| history.pending.remove(key).unwrap()).collect();
+            let restored = history.latest().cloned();
+            retractions.extend(removed.into_iter().map(|update| Retraction {
+                update,
+                reason,
+                restored: restored.clone(),
+            }));
+        }
+        retractions.sort_by_key(|r| (r.update.slot, r.update.write_version));
+        retractions
+    }
+
+    /// Finalized history is never rolled back, forget everything before it
+    fn finalize(&mut self, slot: u64) {
+        self.finalized_slot = self.finalized_slot.max(slot);
+        self.slots = self.slots.split_off(&self.finalized_slot);
+    }
+}
@@ -0,0 +1,474 @@
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+use crate::common::serde_helpers::pubkey_string;
+use crate::dex::{
+    meteora_dlmm::{
+        BinArrayLayout, MeteoraLayout, OracleLayout, METEORA_DLMM_BIN_ARRAY_DISCRIMINATOR,
+        METEORA_DLMM_BIN_ARRAY_SIZE, METEORA_DLMM_ORACLE_DISCRIMINATOR, METEORA_DLMM_ORACLE_SIZE,
+        METEORA_DLMM_POOL_DISCRIMINATOR, METEORA_DLMM_POOL_SIZE,
+    },
+    meteora_pools::{MeteoraPools, METEORA_POOLS_DISCRIMINATOR, METEORA_POOLS_SIZE},
+    raydium_clmm::{RaydiumClmmLayout, RAYDIUM_CLMM_POOL_DISCRIMINATOR, RAYDIUM_CLMM_POOL_SIZE},
+    raydium_cpmm::{RaydiumCpLayout, RAYDIUM_CP_POOL_DISCRIMINATOR, RAYDIUM_CP_POOL_SIZE},
+    raydium_lp_v4::{
+        OpenOrdersLayout, SerumMarketLayout, OPEN_ORDERS_ACCOUNT_SIZE, RAYDIUM_LP_V4_ACCOUNT_SIZE,
+        SERUM_MARKET_ACCOUNT_SIZE,
+    },
+    solfi::{SolFiLayout, SOLFI_POOL_SIZE},
+    spl_token::{SplTokenAccountLayout, SPL_TOKEN_ACCOUNT_SIZE},
+    Dex,
+};
+use crate::instruction::decoder::{AmmStatus, LIQUIDITY_STATE_LAYOUT_V4};
+
+use super::{Flow, Handler, ProcessedAccount, Stage};
+
+/// Token side of a pool in DEX-agnostic terms. "Base" is whatever the DEX stores first
+/// (`baseMint`, `token0`, `token_x`, `token_a`), no price orientation is implied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolTokens {
+    #[serde(with = "pubkey_string")]
+    pub base_mint: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub quote_mint: Pubkey,
+    /// Account holding the base reserve (a Meteora vault for `meteora_pools`)
+    #[serde(with = "pubkey_string")]
+    pub base_vault: Pubkey,
+    #[serde(with = "pubkey_string")]
+    pub quote_vault: Pubkey,
+}
+
+/// Decoded account, picked by owner program and data length.
+/// Serializes as `{"layout": <layout_name>, "data": {...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "layout", content = "data", rename_all = "snake_case")]
+pub enum DecodedAccount {
+    #[serde(rename = "raydium_amm_v4")]
+    RaydiumAmmV4(Box<LIQUIDITY_STATE_LAYOUT_V4>),
+    SerumMarket(Box<SerumMarketLayout>),
+    OpenOrders(Box<OpenOrdersLayout>),
+    RaydiumCpmm(Box<RaydiumCpLayout>),
+    RaydiumClmm(Box<RaydiumClmmLayout>),
+    #[serde(rename = "meteora_dlmm_lb_pair")]
+    MeteoraDlmm(Box<MeteoraLayout>),
+    #[serde(rename = "meteora_dlmm_bin_array")]
+    MeteoraBinArray(Box<BinArrayLayout>),
+    #[serde(rename = "meteora_dlmm_oracle")]
+    MeteoraOracle(Box<OracleLayout>),
+    MeteoraPools(Box<MeteoraPools>),
+    #[serde(rename = "solfi")]
+    SolFi(Box<SolFiLayout>),
+    #[serde(rename = "spl_token_account")]
+    SplToken(Box<SplTokenAccountLayout>),
+}
+
+/// Every layout by name, with the program owning it (`None` for SPL Token) and its account size
+pub const LAYOUTS: [(&str, Option<Dex>, usize); 11] = [
+    ("raydium_amm_v4", Some(Dex::RaydiumAmmV4), RAYDIUM_LP_V4_ACCOUNT_SIZE),
+    ("serum_market", Some(Dex::OpenBook), SERUM_MARKET_ACCOUNT_SIZE),
+    ("open_orders", Some(Dex::OpenBook), OPEN_ORDERS_ACCOUNT_SIZE),
+    ("raydium_cpmm", Some(Dex::RaydiumCpmm), RAYDIUM_CP_POOL_SIZE),
+    ("raydium_clmm", Some(Dex::RaydiumClmm), RAYDIUM_CLMM_POOL_SIZE),
+    ("meteora_dlmm_lb_pair", Some(Dex::MeteoraDlmm), METEORA_DLMM_POOL_SIZE),
+    ("meteora_dlmm_bin_array", Some(Dex::MeteoraDlmm), METEORA_DLMM_BIN_ARRAY_SIZE),
+    ("meteora_dlmm_oracle", Some(Dex::MeteoraDlmm), METEORA_DLMM_ORACLE_SIZE),
+    ("meteora_pools", Some(Dex::MeteoraPools), METEORA_POOLS_SIZE),
+    ("solfi", Some(Dex::SolFi), SOLFI_POOL_SIZE),
+    ("spl_token_account", None, SPL_TOKEN_ACCOUNT_SIZE),
+];
+
+// SPL mints allow up to 255, but no program or pool we decode has ever used more than 18
+const MAX_DECIMALS: u64 = 18;
+
+/// Anchor discriminator every account of `layout` starts with, `None` for non-Anchor layouts
+/// and SolFi, whose layout is not published
+pub fn layout_discriminator(layout: &str) -> Option<[u8; 8]> {
+    match layout {
+        "raydium_cpmm" => Some(RAYDIUM_CP_POOL_DISCRIMINATOR),
+        "raydium_clmm" => Some(RAYDIUM_CLMM_POOL_DISCRIMINATOR),
+        "meteora_dlmm_lb_pair" => Some(METEORA_DLMM_POOL_DISCRIMINATOR),
+        "meteora_dlmm_bin_array" => Some(METEORA_DLMM_BIN_ARRAY_DISCRIMINATOR),
+        "meteora_dlmm_oracle" => Some(METEORA_DLMM_ORACLE_DISCRIMINATOR),
+        "meteora_pools" => Some(METEORA_POOLS_DISCRIMINATOR),
+        _ => None,
+    }
+}
+
+/// Why account data did not decode
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DecodeError {
+    #[error("unknown layout {0}")]
+    UnknownLayout(String),
+    #[error("{layout}: {len} bytes, expected {expected}")]
+    Size {
+        layout: &'static str,
+        len: usize,
+        expected: usize,
+    },
+    #[error("{layout}: discriminator {found:?}, expected {expected:?}")]
+    Discriminator {
+        layout: &'static str,
+        found: [u8; 8],
+        expected: [u8; 8],
+    },
+    #[error("{layout}: malformed account data")]
+    Malformed { layout: &'static str },
+    /// The bytes parse but hold a value the program never writes. Either the layout is the
+    /// wrong one for this account or the program was upgraded and the offsets moved.
+    #[error("{layout}: {field} {problem}, layout drift or wrong layout")]
+    Drift {
+        layout: &'static str,
+        field: &'static str,
+        problem: String,
+    },
+}
+
+impl DecodeError {
+    /// Discriminator and sanity check failures, as opposed to data that is simply not this layout
+    pub fn is_drift(&self) -> bool {
+        matches!(self, Self::Discriminator { .. } | Self::Drift { .. })
+    }
+
+    /// Layout that rejected the data, `None` when the name matched no layout
+    pub fn layout(&self) -> Option<&'static str> {
+        match self {
+            Self::UnknownLayout(_) => None,
+            Self::Size { layout, .. }
+            | Self::Discriminator { layout, .. }
+            | Self::Malformed { layout }
+            | Self::Drift { layout, .. } => Some(layout),
+        }
+    }
+
+    /// Short name of the variant, used as a metric label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::UnknownLayout(_) => "unknown",
+            Self::Size { .. } => "size",
+            Self::Discriminator { .. } => "discriminator",
+            Self::Malformed { .. } => "malformed",
+            Self::Drift { .. } => "drift",
+        }
+    }
+}
+
+impl DecodedAccount {
+    /// [`try_decode`](Self::try_decode) with every failure mapped to `None`
+    pub fn decode(owner: &solana_program::pubkey::Pubkey, data: &[u8]) -> Option<Self> {
+        Self::try_decode(owner, data).ok().flatten()
+    }
+
+    /// Picks the layout by owner and size and decodes it with the sanity checks of
+    /// [`check`](Self::check). `Ok(None)` when no layout matches the owner and size.
+    pub fn try_decode(owner: &solana_program::pubkey::Pubkey, data: &[u8]) -> Result<Option<Self>, DecodeError> {
+        match Self::layout_for_owner(owner, data.len()) {
+            Some(layout) => Self::try_decode_layout(layout, data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Layout of a `len` byte account owned by `owner`, `None` for other programs and sizes
+    pub fn layout_for_owner(owner: &solana_program::pubkey::Pubkey, len: usize) -> Option<&'static str> {
+        let dex = Dex::from_program_id(owner);
+        if dex.is_none() && *owner != spl_token::id() {
+            return None;
+        }
+        LAYOUTS
+            .iter()
+            .find(|(_, program, size)| *program == dex && *size == len)
+            .map(|(layout, ..)| *layout)
+    }
+
+    /// Decodes `data` as the named layout regardless of its owner, `None` for unknown names
+    /// and data the layout rejects. Only the size and discriminator are checked, not the
+    /// field values, so drifted accounts still decode for inspection.
+    pub fn decode_layout(layout: &str, data: &[u8]) -> Option<Self> {
+        Self::parse(layout, data).ok()
+    }
+
+    /// Decodes `data` as the named layout and runs [`check`](Self::check) on the result
+    pub fn try_decode_layout(layout: &str, data: &[u8]) -> Result<Self, DecodeError> {
+        let decoded = Self::parse(layout, data)?;
+        decoded.check()?;
+        Ok(decoded)
+    }
+
+    fn parse(layout: &str, data: &[u8]) -> Result<Self, DecodeError> {
+        let Some(&(layout, _, expected)) = LAYOUTS.iter().find(|(name, ..)| *name == layout) else {
+            return Err(DecodeError::UnknownLayout(layout.to_string()));
+        };
+        if data.len() < expected {
+            return Err(DecodeError::Size {
+                layout,
+                len: data.len(),
+                expected,
+            });
+        }
+        if let Some(discriminator) = layout_discriminator(layout) {
+            let found: [u8; 8] = data[..8].try_into().unwrap();
+            if found != discriminator {
+                return Err(DecodeError::Discriminator {
+                    layout,
+                    found,
+                    expected: discriminator,
+                });
+            }
+        }
+        Self::parse_unchecked(layout, data).ok_or(if data.len() == expected {
+            DecodeError::Malformed { layout }
+        } else {
+            DecodeError::Size {
+                layout,
+                len: data.len(),
+                expected,
+            }
+        })
+    }
+
+    fn parse_unchecked(layout: &str, data: &[u8]) -> Option<Self> {
+        match layout {
+            "raydium_amm_v4" => LIQUIDITY_STATE_LAYOUT_V4::from_bytes(data)
+                .ok()
+                .map(|d| Self::RaydiumAmmV4(Box::new(*d))),
+            "serum_market" => SerumMarketLayout::slice_market(data).map(|d| Self::SerumMarket(Box::new(d))),
+            "open_orders" => OpenOrdersLayout::try_from_slice_manual(data).map(|d| Self::OpenOrders(Box::new(d))),
+            "raydium_cpmm" => RaydiumCpLayout::try_from_slice_manual(data).map(|d| Self::RaydiumCpmm(Box::new(d))),
+            "raydium_clmm" => RaydiumClmmLayout::try_from_slice_manual(data).map(|d| Self::RaydiumClmm(Box::new(d))),
+            "meteora_dlmm_lb_pair" => {
+                MeteoraLayout::try_from_slice_manual(data).map(|d| Self::MeteoraDlmm(Box::new(d)))
+            }
+            "meteora_dlmm_bin_array" => {
+                BinArrayLayout::try_from_slice_manual(data).map(|d| Self::MeteoraBinArray(Box::new(d)))
+            }
+            "meteora_dlmm_oracle" => {
+                OracleLayout::try_from_slice_manual(data).map(|d| Self::MeteoraOracle(Box::new(d)))
+            }
+            "meteora_pools" => MeteoraPools::try_from_slice_manual(data).map(|d| Self::MeteoraPools(Box::new(d))),
+            "solfi" => SolFiLayout::try_from_slice_manual(data).map(|d| Self::SolFi(Box::new(d))),
+            "spl_token_account" => {
+                SplTokenAccountLayout::try_from_slice_manual(data).map(|d| Self::SplToken(Box::new(d)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Sanity checks on values the programs never write: decimals above 18, status and enum
+    /// fields out of range, zero mints. A failure means the offsets no longer match the
+    /// account, usually after a program upgrade, so the other fields cannot be trusted either.
+    pub fn check(&self) -> Result<(), DecodeError> {
+        let layout = self.layout_name();
+        let drift = |field: &'static str, problem: String| {
+            Err(DecodeError::Drift {
+                layout,
+                field,
+                problem,
+            })
+        };
+        let decimals = |field: &'static str, value: u64| {
+            if value > MAX_DECIMALS {
+                return drift(field, format!("is {}, above {}", value, MAX_DECIMALS));
+            }
+            Ok(())
+        };
+        let mint = |field: &'static str, value: &Pubkey| {
+            if *value == Pubkey::default() {
+                return drift(field, "is the zero key".to_string());
+            }
+            Ok(())
+        };
+        let at_most = |field: &'static str, value: u64, max: u64| {
+            if value > max {
+                return drift(field, format!("is {}, above {}", value, max));
+            }
+            Ok(())
+        };
+
+        match self {
+            Self::RaydiumAmmV4(s) => {
+                if let Err(status) = AmmStatus::try_from(s.status) {
+                    return drift("status", format!("{} is not an AmmStatus", status));
+                }
+                decimals("baseDecimal", s.baseDecimal)?;
+                decimals("quoteDecimal", s.quoteDecimal)?;
+                mint("baseMint", &s.baseMint)?;
+                mint("quoteMint", &s.quoteMint)
+            }
+            Self::SerumMarket(s) => {
+                mint("base_mint", &s.base_mint)?;
+                mint("quote_mint", &s.quote_mint)
+            }
+            Self::OpenOrders(_) => Ok(()),
+            Self::RaydiumCpmm(s) => {
+                // bit 0 deposit, bit 1 withdraw, bit 2 swap
+                at_most("status", s.status.into(), 0b111)?;
+                decimals("lpMintDecimals", s.lpMintDecimals.into())?;
+                decimals("mint0Decimals", s.mint0Decimals.into())?;
+                decimals("mint1Decimals", s.mint1Decimals.into())?;
+                mint("token0Mint", &s.token0Mint)?;
+                mint("token1Mint", &s.token1Mint)
+            }
+            Self::RaydiumClmm(s) => {
+                // bits 0-4: open position, decrease liquidity, collect fee, collect reward, swap
+                at_most("status", s.status.into(), 0b1_1111)?;
+                decimals("mint_decimals0", s.mint_decimals0.into())?;
+                decimals("mint_decimals1", s.mint_decimals1.into())?;
+                mint("token_mint0", &s.token_mint0)?;
+                mint("token_mint1", &s.token_mint1)
+            }
+            Self::MeteoraDlmm(s) => {
+                // PairStatus: Enabled, Disabled
+                at_most("status", s.status.into(), 1)?;
+                // PairType: Permissionless, Permission, CustomizablePermissionless, PermissionlessV2
+                at_most("pair_type", s.pair_type.into(), 3)?;
+                if s.bin_step == 0 {
+                    return drift("bin_step", "is 0".to_string());
+                }
+                mint("token_x_mint", &s.token_x_mint)?;
+                mint("token_y_mint", &s.token_y_mint)
+            }
+            Self::MeteoraBinArray(s) => mint("lb_pair", &s.lb_pair),
+            Self::MeteoraOracle(s) => at_most("active_size", s.active_size, s.length),
+            Self::MeteoraPools(s) => {
+                at_most("pool_type", s.pool_type.into(), 1)?;
+                // CurveType: ConstantProduct, Stable
+                at_most("curve_type", s.curve_type.into(), 1)?;
+                mint("token_a_mint", &s.token_a_mint)?;
+                mint("token_b_mint", &s.token_b_mint)
+            }
+            Self::SolFi(s) => {
+                decimals("mint_decimals0", s.mint_decimals0.into())?;
+                decimals("mint_decimals1", s.mint_decimals1.into())?;
+                mint("token_mint0", &s.token_mint0)?;
+                mint("token_mint1", &s.token_mint1)
+            }
+            Self::SplToken(s) => {
+                // AccountState: Uninitialized, Initialized, Frozen
+                at_most("state", s.state.into(), 2)?;
+                mint("mint", &s.mint)
+            }
+        }
+    }
+
+    /// Account bytes that decode back to `self`. Fields a layout does not keep are zero and
+    /// Anchor accounts get their discriminator.
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::RaydiumAmmV4(s) => s.to_bytes(),
+            Self::SerumMarket(s) => s.to_bytes(),
+            Self::OpenOrders(s) => s.to_bytes(),
+            Self::RaydiumCpmm(s) => s.to_bytes(),
+            Self::RaydiumClmm(s) => s.to_bytes(),
+            Self::MeteoraDlmm(s) => s.to_bytes(),
+            Self::MeteoraBinArray(s) => s.to_bytes(),
+            Self::MeteoraOracle(s) => s.to_bytes(),
+            Self::MeteoraPools(s) => s.to_bytes(),
+            Self::SolFi(s) => s.to_bytes(),
+            Self::SplToken(s) => s.to_bytes(),
+        }
+    }
+
+    /// Layouts whose account size is `len`, for data of unknown owner
+    pub fn layouts_for_size(len: usize) -> Vec<&'static str> {
+        LAYOUTS
+            .iter()
+            .filter(|(_, _, size)| *size == len)
+            .map(|(layout, ..)| *layout)
+            .collect()
+    }
+
+    pub fn dex(&self) -> Option<Dex> {
+        match self {
+            Self::RaydiumAmmV4(_) => Some(Dex::RaydiumAmmV4),
+            Self::SerumMarket(_) | Self::OpenOrders(_) => Some(Dex::OpenBook),
+            Self::RaydiumCpmm(_) => Some(Dex::RaydiumCpmm),
+            Self::RaydiumClmm(_) => Some(Dex::RaydiumClmm),
+            Self::MeteoraDlmm(_) | Self::MeteoraBinArray(_) | Self::MeteoraOracle(_) => {
+                Some(Dex::MeteoraDlmm)
+            }
+            Self::MeteoraPools(_) => Some(Dex::MeteoraPools),
+            Self::SolFi(_) => Some(Dex::SolFi),
+            Self::SplToken(_) => None,
+        }
+    }
+
+    /// Mints and reserve accounts for pool layouts, `None` for everything else
+    pub fn pool_tokens(&self) -> Option<PoolTokens> {
+        let (base_mint, quote_mint, base_vault, quote_vault) = match self {
+            Self::RaydiumAmmV4(s) => (s.baseMint, s.quoteMint, s.baseVault, s.quoteVault),
+            Self::RaydiumCpmm(s) => (s.token0Mint, s.token1Mint, s.token0Vault, s.token1Vault),
+            Self::RaydiumClmm(s) => (s.token_mint0, s.token_mint1, s.token_vault0, s.token_vault1),
+            Self::MeteoraDlmm(s) => (s.token_x_mint, s.token_y_mint, s.reserve_x, s.reserve_y),
+            Self::MeteoraPools(s) => (s.token_a_mint, s.token_b_mint, s.a_vault, s.b_vault),
+            Self::SolFi(s) => (s.token_mint0, s.token_mint1, s.token_vault0, s.token_vault1),
+            _ => return None,
+        };
+        Some(PoolTokens {
+            base_mint,
+            quote_mint,
+            base_vault,
+            quote_vault,
+        })
+    }
+
+    /// Layout name, used in logs and metrics
+    pub fn layout_name(&self) -> &'static str {
+        match self {
+            Self::RaydiumAmmV4(_) => "raydium_amm_v4",
+            Self::SerumMarket(_) => "serum_market",
+            Self::OpenOrders(_) => "open_orders",
+            Self::RaydiumCpmm(_) => "raydium_cpmm",
+            Self::RaydiumClmm(_) => "raydium_clmm",
+            Self::MeteoraDlmm(_) => "meteora_dlmm_lb_pair",
+            Self::MeteoraBinArray(_) => "meteora_dlmm_bin_array",
+            Self::MeteoraOracle(_) => "meteora_dlmm_oracle",
+            Self::MeteoraPools(_) => "meteora_pools",
+            Self::SolFi(_) => "solfi",
+            Self::SplToken(_) => "spl_token_account",
+        }
+    }
+}
+
+/// Decode stage. Updates that match no known layout or fail its checks are skipped unless
+/// `keep_unknown` is set.
+#[derive(Debug, Default)]
+pub struct AccountDecoder {
+    pub keep_unknown: bool,
+}
+
+impl Handler for AccountDecoder {
+    fn name(&self) -> &'static str {
+        "decode"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Decode
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let reason = match DecodedAccount::try_decode(&account.update.owner, &account.update.data) {
+            Ok(Some(decoded)) => {
+                account.decoded = Some(decoded);
+                return Ok(Flow::Continue);
+            }
+            Ok(None) => format!(
+                "no layout for owner {} with {} bytes",
+                account.update.owner,
+                account.update.data.len()
+            ),
+            Err(e) => {
+                if e.is_drift() {
+                    log::warn!("{}: {}", account.update.pubkey, e);
+                }
+                let reason = e.to_string();
+                account.decode_error = Some(e);
+                reason
+            }
+        };
+        account.decoded = None;
+        if self.keep_unknown {
+            return Ok(Flow::Continue);
+        }
+        Ok(Flow::Skip(reason))
+    }
+}
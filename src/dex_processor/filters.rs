@@ -0,0 +1,34 @@
+use crate::dex::Dex;
+
+use super::{DecodedAccount, Flow, Handler, PipelineEvent, ProcessedAccount, Stage};
+
+/// Lets through v4 pools that have never been swapped against (both swap counters zero) and
+/// raises [`PipelineEvent::NewPool`] for them. Other layouts pass untouched.
+#[derive(Debug, Default)]
+pub struct NewPoolFilter;
+
+impl Handler for NewPoolFilter {
+    fn name(&self) -> &'static str {
+        "new_pool_filter"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Filter
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let Some(DecodedAccount::RaydiumAmmV4(state)) = &account.decoded else {
+            return Ok(Flow::Continue);
+        };
+        let pass_q = state.swapQuoteInAmount == 0;
+        let pass_b = state.swapBaseOutAmount == 0;
+        if !(pass_q && pass_b) {
+            return Ok(Flow::Skip(format!("pass_q={}  pass_b={}", pass_q, pass_b)));
+        }
+        account.events.push(PipelineEvent::NewPool {
+            dex: Dex::RaydiumAmmV4,
+            pool: account.update.pubkey,
+        });
+        Ok(Flow::Continue)
+    }
+}
@@ -0,0 +1,186 @@
+//! Offline account inspection, behind the `decode` subcommand: read account bytes from
+//! wherever they were copied from, pick a layout and render the decoded fields.
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Context};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_program::pubkey::Pubkey;
+
+use crate::common::snapshot::Snapshot;
+
+use super::decode::{DecodeError, DecodedAccount, LAYOUTS};
+
+/// How the input bytes are written down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum InputEncoding {
+    /// JSON dump if it parses as one, else base58, else base64, else raw bytes. Text that is
+    /// valid in both is read as base64 only when that length fits a layout and the base58 one
+    /// does not.
+    #[default]
+    Auto,
+    Raw,
+    Base64,
+    Base58,
+    /// `solana account <pubkey> --output json`, any data encoding the RPC returns
+    Json,
+}
+
+/// Account bytes plus whatever the input said about the account
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountInput {
+    pub pubkey: Option<Pubkey>,
+    pub owner: Option<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+impl AccountInput {
+    /// Parses file contents or a string given on the command line
+    pub fn parse(input: &[u8], encoding: InputEncoding) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(input).map(str::trim);
+        match encoding {
+            InputEncoding::Raw => Ok(Self::bytes(input.to_vec())),
+            InputEncoding::Json => Self::from_json(text.context("JSON input is not UTF-8")?),
+            InputEncoding::Base64 => Ok(Self::bytes(
+                BASE64.decode(text.context("base64 input is not UTF-8")?).context("invalid base64")?,
+            )),
+            InputEncoding::Base58 => Ok(Self::bytes(
+                bs58::decode(text.context("base58 input is not UTF-8")?)
+                    .into_vec()
+                    .context("invalid base58")?,
+            )),
+            InputEncoding::Auto => {
+                let Ok(text) = text else {
+                    return Ok(Self::bytes(input.to_vec()));
+                };
+                if text.starts_with('{') {
+                    return Self::from_json(text);
+                }
+                // base58 text is often valid base64 as well, `+`, `/`, `=` and 0OIl only occur
+                // in base64 and already rule base58 out
+                let is_layout_size = |len: usize| LAYOUTS.iter().any(|(.., size)| *size == len);
+                let data = match (bs58::decode(text).into_vec().ok(), BASE64.decode(text).ok()) {
+                    (Some(base58), Some(base64)) if !is_layout_size(base58.len()) && is_layout_size(base64.len()) => {
+                        base64
+                    }
+                    (Some(base58), _) => base58,
+                    (None, Some(base64)) => base64,
+                    (None, None) => input.to_vec(),
+                };
+                Ok(Self::bytes(data))
+            }
+        }
+    }
+
+    fn bytes(data: Vec<u8>) -> Self {
+        Self {
+            pubkey: None,
+            owner: None,
+            data,
+        }
+    }
+
+    fn from_json(text: &str) -> anyhow::Result<Self> {
+        let snapshot = Snapshot::from_json(text).context("not a `solana account --output json` dump")?;
+        let mut accounts = snapshot.accounts.into_iter();
+        let (Some((pubkey, account)), None) = (accounts.next(), accounts.next()) else {
+            bail!("expected exactly one account in the dump");
+        };
+        Ok(Self {
+            pubkey: Some(pubkey),
+            owner: Some(account.owner),
+            data: account.data,
+        })
+    }
+}
+
+/// Result of decoding one [`AccountInput`]
+#[derive(Debug)]
+pub struct Inspection {
+    pub decoded: DecodedAccount,
+    /// Other layouts of the same size, only filled when the layout was guessed from size alone
+    pub alternatives: Vec<&'static str>,
+    /// Failed sanity check, the account decoded but its values look like layout drift
+    pub drift: Option<DecodeError>,
+}
+
+impl Inspection {
+    fn new(decoded: DecodedAccount, alternatives: Vec<&'static str>) -> Self {
+        let drift = decoded.check().err();
+        Self {
+            decoded,
+            alternatives,
+            drift,
+        }
+    }
+}
+
+/// Decodes with `layout` when given, else by owner and size, else by size alone. Accounts
+/// failing the sanity checks still decode, with the failure in [`Inspection::drift`].
+pub fn inspect(input: &AccountInput, layout: Option<&str>) -> anyhow::Result<Inspection> {
+    let len = input.data.len();
+    if let Some(layout) = layout {
+        if !LAYOUTS.iter().any(|(name, ..)| *name == layout) {
+            bail!("unknown layout {}, expected one of {}", layout, layout_names().join(", "));
+        }
+        let decoded = DecodedAccount::decode_layout(layout, &input.data)
+            .ok_or_else(|| anyhow!("{} bytes do not decode as {}", len, layout))?;
+        return Ok(Inspection::new(decoded, Vec::new()));
+    }
+
+    if let Some(owner) = input.owner {
+        let decoded = DecodedAccount::layout_for_owner(&owner, len)
+            .and_then(|layout| DecodedAccount::decode_layout(layout, &input.data))
+            .ok_or_else(|| anyhow!("no layout for owner {} with {} bytes, try --layout", owner, len))?;
+        return Ok(Inspection::new(decoded, Vec::new()));
+    }
+
+    let candidates = DecodedAccount::layouts_for_size(len);
+    for (i, layout) in candidates.iter().enumerate() {
+        if let Some(decoded) = DecodedAccount::decode_layout(layout, &input.data) {
+            let mut alternatives = candidates.clone();
+            alternatives.remove(i);
+            return Ok(Inspection::new(decoded, alternatives));
+        }
+    }
+    bail!("no layout is {} bytes long, pass --owner or --layout", len)
+}
+
+pub fn layout_names() -> Vec<&'static str> {
+    LAYOUTS.iter().map(|(name, ..)| *name).collect()
+}
+
+/// One `field  value` row per leaf, nested fields joined with `.` and array items as `[i]`.
+/// Arrays of plain values stay on one row.
+pub fn render_table(decoded: &DecodedAccount) -> anyhow::Result<String> {
+    let value = serde_json::to_value(decoded)?;
+    let mut rows = Vec::new();
+    flatten("", &value["data"], &mut rows);
+    let width = rows.iter().map(|(field, _)| field.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    writeln!(out, "{:width$}  {}", "layout", decoded.layout_name())?;
+    for (field, value) in rows {
+        writeln!(out, "{:width$}  {}", field, value)?;
+    }
+    Ok(out)
+}
+
+fn flatten(prefix: &str, value: &serde_json::Value, rows: &mut Vec<(String, String)>) {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                flatten(&path, value, rows);
+            }
+        }
+        Value::Array(items) if items.iter().any(|item| item.is_object() || item.is_array()) => {
+            for (i, item) in items.iter().enumerate() {
+                flatten(&format!("{}[{}]", prefix, i), item, rows);
+            }
+        }
+        Value::String(s) => rows.push((prefix.to_string(), s.clone())),
+        other => rows.push((prefix.to_string(), other.to_string())),
+    }
+}
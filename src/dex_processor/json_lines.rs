@@ -0,0 +1,127 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::common::serde_helpers::pubkey_string;
+use crate::dex::Dex;
+
+use super::{
+    DecodedAccount, Flow, Handler, PipelineEvent, ProcessedAccount, RetractReason, Retraction, Stage,
+};
+
+/// One line of JSON-lines output
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JsonRecord<A = DecodedAccount> {
+    Account {
+        slot: u64,
+        #[serde(with = "pubkey_string")]
+        pubkey: Pubkey,
+        #[serde(with = "pubkey_string")]
+        owner: Pubkey,
+        dex: Option<Dex>,
+        #[serde(flatten)]
+        account: A,
+    },
+    Event {
+        slot: u64,
+        #[serde(with = "pubkey_string")]
+        pubkey: Pubkey,
+        #[serde(with = "pubkey_string")]
+        owner: Pubkey,
+        #[serde(flatten)]
+        event: PipelineEvent,
+    },
+    /// Earlier record at `slot` is void, its fork was dropped
+    Retraction {
+        slot: u64,
+        #[serde(with = "pubkey_string")]
+        pubkey: Pubkey,
+        #[serde(with = "pubkey_string")]
+        owner: Pubkey,
+        reason: RetractReason,
+        /// Slot of the state the account falls back to
+        restored_slot: Option<u64>,
+    },
+}
+
+/// Act-stage handler writing every decoded account and every pipeline event as one JSON line
+pub struct JsonLinesSink {
+    writer: Box<dyn Write + Send>,
+}
+
+impl JsonLinesSink {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Appends to `path`, creating it if needed
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    fn write_record<A: Serialize>(&mut self, record: &JsonRecord<A>) -> anyhow::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+impl Handler for JsonLinesSink {
+    fn name(&self) -> &'static str {
+        "json_lines"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Act
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let update = &account.update;
+        if let Some(decoded) = &account.decoded {
+            self.write_record(&JsonRecord::Account {
+                slot: update.slot,
+                pubkey: update.pubkey,
+                owner: update.owner,
+                dex: decoded.dex(),
+                account: decoded,
+            })?;
+        }
+        for event in &account.events {
+            self.write_record(&JsonRecord::<&DecodedAccount>::Event {
+                slot: update.slot,
+                pubkey: update.pubkey,
+                owner: update.owner,
+                event: event.clone(),
+            })?;
+        }
+        // downstream tools tail the file, so every update is flushed as soon as it is written
+        self.writer.flush()?;
+        Ok(Flow::Continue)
+    }
+
+    fn retract(&mut self, retraction: &Retraction) -> anyhow::Result<()> {
+        let update = &retraction.update;
+        self.write_record(&JsonRecord::<&DecodedAccount>::Retraction {
+            slot: update.slot,
+            pubkey: update.pubkey,
+            owner: update.owner,
+            reason: retraction.reason,
+            restored_slot: retraction.restored.as_ref().map(|restored| restored.slot),
+        })?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::dex::{
+    meteora_dlmm::{print_bin_array_layout, print_meteora_layout, print_oracle_layout},
+    meteora_pools::print_meteora_pools_layout,
+    raydium_clmm::print_raydium_clmm_layout,
+    raydium_cpmm::print_raydium_cpmm_layout,
+    raydium_lp_v4::{
+        print_open_orders_layout, print_raydium_lp_v4_layout, print_serum_market_layout,
+        RaydiumLpV4Layout,
+    },
+    solfi::print_solfi_layout,
+    spl_token::print_spl_token_account_layout,
+};
+
+use super::{DecodedAccount, Flow, Handler, ProcessedAccount, Retraction, Stage};
+
+/// Optional act-stage handler that prints every decoded layout with the `print_*` functions
+#[derive(Debug, Default)]
+pub struct LoggingHandler;
+
+impl Handler for LoggingHandler {
+    fn name(&self) -> &'static str {
+        "logging"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Act
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let key = account.update.pubkey.to_string();
+        match &account.decoded {
+            Some(DecodedAccount::RaydiumAmmV4(state)) => {
+                print_raydium_lp_v4_layout(key, &RaydiumLpV4Layout::from(state.as_ref()));
+                log::info!(
+                    "Status: {:?}  Pool Open Time: {}  Tradable Now: {}",
+                    state.amm_status(),
+                    { state.poolOpenTime },
+                    state.can_swap_at(chrono::Utc::now().timestamp()),
+                );
+            }
+            Some(DecodedAccount::SerumMarket(data)) => print_serum_market_layout(key, data),
+            Some(DecodedAccount::OpenOrders(data)) => print_open_orders_layout(key, data),
+            Some(DecodedAccount::RaydiumCpmm(data)) => print_raydium_cpmm_layout(key, data),
+            Some(DecodedAccount::RaydiumClmm(data)) => print_raydium_clmm_layout(key, data),
+            Some(DecodedAccount::MeteoraDlmm(data)) => print_meteora_layout(key, data),
+            Some(DecodedAccount::MeteoraBinArray(data)) => print_bin_array_layout(key, data),
+            Some(DecodedAccount::MeteoraOracle(data)) => print_oracle_layout(key, data),
+            Some(DecodedAccount::MeteoraPools(data)) => print_meteora_pools_layout(key, data),
+            Some(DecodedAccount::SolFi(data)) => print_solfi_layout(key, data),
+            Some(DecodedAccount::SplToken(data)) => print_spl_token_account_layout(key, data),
+            None => log::info!(
+                "{} ({} bytes, owner {}) has no known layout",
+                key,
+                account.update.data.len(),
+                account.update.owner
+            ),
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn retract(&mut self, retraction: &Retraction) -> anyhow::Result<()> {
+        log::warn!(
+            "retracted {} at slot {} ({:?}), back to slot {:?}",
+            retraction.update.pubkey,
+            retraction.update.slot,
+            retraction.reason,
+            retraction.restored.as_ref().map(|restored| restored.slot),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+//! Account processing pipeline.
+//!
+//! Every account update runs through an ordered list of handlers grouped in four stages:
+//! decode → enrich → filter → act. Handlers return a [`Flow`] instead of printing and exiting,
+//! so the caller decides what to do with the outcome. Writes later found to be on a dropped fork
+//! are passed back to every handler as a [`Retraction`].
+
+pub mod commitment;
+pub mod decode;
+pub mod filters;
+pub mod inspect;
+pub mod json_lines;
+pub mod logging;
+pub mod upgrades;
+pub mod verify;
+pub mod watch_vaults;
+
+use std::time::Instant;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{subscribe_update::UpdateOneof, SubscribeUpdate, SubscribeUpdateAccount};
+
+use crate::common::serde_helpers::pubkey_string;
+use crate::dex::Dex;
+use crate::metrics::{Metrics, Timings};
+use crate::stream::SourceUpdate;
+
+pub use commitment::{CommitmentTracker, RetractReason, Retraction};
+pub use decode::{AccountDecoder, DecodeError, DecodedAccount, PoolTokens};
+pub use filters::NewPoolFilter;
+pub use json_lines::JsonLinesSink;
+pub use logging::LoggingHandler;
+pub use upgrades::{UpgradeGate, UpgradeMonitor, PROGRAM_DATA_FILTER};
+pub use verify::{PdaMismatch, VerifyPdas};
+pub use watch_vaults::{WatchPoolVaults, POOL_VAULTS_FILTER};
+
+/// Raw account update, owned so the payload can be moved out of the gRPC message without a copy
+#[derive(Debug, Clone, Default)]
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub owner: Pubkey,
+    pub slot: u64,
+    pub write_version: u64,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub timings: Timings,
+}
+
+impl AccountUpdate {
+    pub fn from_grpc(update: SubscribeUpdateAccount) -> anyhow::Result<Option<Self>> {
+        let Some(account) = update.account else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            pubkey: Pubkey::try_from(account.pubkey.as_slice())
+                .map_err(|_| anyhow::anyhow!("invalid account pubkey"))?,
+            owner: Pubkey::try_from(account.owner.as_slice())
+                .map_err(|_| anyhow::anyhow!("invalid owner pubkey"))?,
+            slot: update.slot,
+            write_version: account.write_version,
+            lamports: account.lamports,
+            data: account.data,
+            timings: Timings::default(),
+        }))
+    }
+
+    /// Account payload of a raw stream message, `None` for pings, slots, transactions, ...
+    pub fn from_subscribe_update(update: SubscribeUpdate) -> anyhow::Result<Option<Self>> {
+        // other payloads are not looked at, so a malformed transaction or slot is not an error here
+        if !matches!(update.update_oneof, Some(UpdateOneof::Account(_))) {
+            return Ok(None);
+        }
+        Ok(match SourceUpdate::from_subscribe_update(update)? {
+            Some(SourceUpdate::Account(account)) => Some(account),
+            _ => None,
+        })
+    }
+}
+
+/// Pipeline stages, handlers run in this order regardless of insertion order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stage {
+    Decode,
+    Enrich,
+    Filter,
+    Act,
+}
+
+/// Events raised by handlers for the caller (strategies, sinks, ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PipelineEvent {
+    NewPool {
+        dex: crate::dex::Dex,
+        #[serde(with = "pubkey_string")]
+        pool: Pubkey,
+    },
+    /// The ProgramData of `program` changed, its layouts may no longer match
+    ProgramUpgraded {
+        dex: crate::dex::Dex,
+        #[serde(with = "pubkey_string")]
+        program: Pubkey,
+        deployed_slot: u64,
+    },
+}
+
+/// An update as it moves through the pipeline
+#[derive(Debug)]
+pub struct ProcessedAccount {
+    pub update: AccountUpdate,
+    pub decoded: Option<DecodedAccount>,
+    /// Why the decoder left `decoded` empty, `None` when no layout fits the owner and size
+    pub decode_error: Option<DecodeError>,
+    pub events: Vec<PipelineEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    /// Stop processing this update, later stages are not run
+    Skip(String),
+}
+
+pub trait Handler: Send {
+    fn name(&self) -> &'static str;
+
+    fn stage(&self) -> Stage;
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow>;
+
+    /// An update this handler may have seen was on a fork that got dropped
+    fn retract(&mut self, _retraction: &Retraction) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ProcessOutcome {
+    Completed(ProcessedAccount),
+    Skipped {
+        handler: &'static str,
+        reason: String,
+        account: ProcessedAccount,
+    },
+}
+
+#[derive(Default)]
+pub struct Pipeline {
+    handlers: Vec<Box<dyn Handler>>,
+    metrics: Option<Metrics>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_handler(mut self, handler: impl Handler + 'static) -> Self {
+        self.add_handler(Box::new(handler));
+        self
+    }
+
+    /// Records stage timings, decode failures and new pools of every processed update, per DEX
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Handlers keep insertion order within a stage
+    pub fn add_handler(&mut self, handler: Box<dyn Handler>) {
+        let pos = self
+            .handlers
+            .iter()
+            .position(|h| h.stage() > handler.stage())
+            .unwrap_or(self.handlers.len());
+        self.handlers.insert(pos, handler);
+    }
+
+    pub fn handler_names(&self) -> Vec<&'static str> {
+        self.handlers.iter().map(|h| h.name()).collect()
+    }
+
+    pub fn process(&mut self, update: AccountUpdate) -> anyhow::Result<ProcessOutcome> {
+        let mut account = ProcessedAccount {
+            update,
+            decoded: None,
+            decode_error: None,
+            events: Vec::new(),
+        };
+        for i in 0..self.handlers.len() {
+            let next_stage = self.handlers.get(i + 1).map(|next| next.stage());
+            let handler = &mut self.handlers[i];
+            let flow = handler
+                .handle(&mut account)
+                .with_context(|| format!("handler {} failed for {}", handler.name(), account.update.pubkey))?;
+            let stage = handler.stage();
+            if let Flow::Skip(reason) = flow {
+                account.update.timings.stage_done(stage, Instant::now());
+                let handler = handler.name();
+                self.record_metrics(&account);
+                return Ok(ProcessOutcome::Skipped {
+                    handler,
+                    reason,
+                    account,
+                });
+            }
+            if next_stage != Some(stage) {
+                account.update.timings.stage_done(stage, Instant::now());
+            }
+        }
+        self.record_metrics(&account);
+        Ok(ProcessOutcome::Completed(account))
+    }
+
+    fn record_metrics(&self, account: &ProcessedAccount) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let dex = match &account.decoded {
+            Some(decoded) => decoded.dex(),
+            None => {
+                let dex = Dex::from_program_id(&account.update.owner);
+                // accounts no layout claims (other program accounts, program data, updates held
+                // back after an upgrade) are not failures
+                if let Some(error) = &account.decode_error {
+                    metrics.record_decode_failure(dex, error);
+                }
+                dex
+            }
+        };
+        for event in &account.events {
+            match event {
+                PipelineEvent::NewPool { dex, .. } => metrics.record_new_pool(*dex),
+                PipelineEvent::ProgramUpgraded { dex, .. } => metrics.record_program_upgrade(*dex),
+            }
+        }
+        metrics.latency.record_processed(dex, &account.update.timings);
+    }
+
+    /// Notifies every handler, in stage order
+    pub fn retract(&mut self, retraction: &Retraction) -> anyhow::Result<()> {
+        for handler in self.handlers.iter_mut() {
+            handler.retract(retraction).with_context(|| {
+                format!("handler {} failed to retract {}", handler.name(), retraction.update.pubkey)
+            })?;
+        }
+        Ok(())
+    }
+}
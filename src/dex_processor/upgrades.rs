@@ -0,0 +1,226 @@
+//! Upgrade monitoring for the DEX programs we decode.
+//!
+//! Layout offsets and sizes are hardcoded, so a program upgrade can change them without a single
+//! decode failing loudly. [`UpgradeMonitor`] follows the ProgramData account of every [`Dex`] and
+//! raises [`PipelineEvent::ProgramUpgraded`] when its deployment slot or bytecode hash changes.
+//! With an [`UpgradeGate`] it also holds back the updates of that DEX until someone confirms the
+//! layouts still match.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use log::{error, warn};
+use solana_program::{
+    bpf_loader_upgradeable,
+    hash::{hash, Hash},
+    pubkey::Pubkey,
+};
+
+use crate::dex::Dex;
+
+use super::{Flow, Handler, PipelineEvent, ProcessedAccount, Stage};
+
+/// Filter name the ProgramData accounts are subscribed under
+pub const PROGRAM_DATA_FILTER: &str = "program_data";
+
+/// `UpgradeableLoaderState::ProgramData` tag
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// ProgramData account of an upgradeable `program`
+pub fn program_data_address(program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// ProgramData account of every DEX we decode
+pub fn program_data_accounts() -> Vec<(Dex, Pubkey)> {
+    Dex::ALL
+        .into_iter()
+        .map(|dex| (dex, program_data_address(&dex.program_id())))
+        .collect()
+}
+
+/// Header of a ProgramData account and a hash of the bytecode after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramDataState {
+    /// Slot the program was last deployed or upgraded in
+    pub slot: u64,
+    pub upgrade_authority: Option<Pubkey>,
+    pub bytecode_hash: Hash,
+}
+
+impl ProgramDataState {
+    /// tag (4) + slot (8) + `Option<Pubkey>` (1 + 32)
+    pub const HEADER_LEN: usize = 45;
+
+    /// `None` unless `data` is a ProgramData account
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < Self::HEADER_LEN || data[..4] != PROGRAM_DATA_TAG.to_le_bytes() {
+            return None;
+        }
+        let slot = u64::from_le_bytes(data[4..12].try_into().ok()?);
+        let upgrade_authority = match data[12] {
+            0 => None,
+            1 => Some(Pubkey::try_from(&data[13..45]).ok()?),
+            _ => return None,
+        };
+        // `extend-program` pads the account with zeros, that alone is not a new program
+        let bytecode = &data[Self::HEADER_LEN..];
+        let end = bytecode.iter().rposition(|byte| *byte != 0).map_or(0, |i| i + 1);
+        Some(Self {
+            slot,
+            upgrade_authority,
+            bytecode_hash: hash(&bytecode[..end]),
+        })
+    }
+}
+
+/// A deployment seen on a DEX program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramUpgrade {
+    pub dex: Dex,
+    /// Last known state, `None` when the upgrade was the first write seen
+    pub previous: Option<ProgramDataState>,
+    pub current: ProgramDataState,
+    /// Slot of the update that carried the new ProgramData
+    pub seen_at: u64,
+}
+
+/// Cloneable set of DEXes held back after an upgrade, released by [`UpgradeGate::confirm`]
+#[derive(Debug, Clone, Default)]
+pub struct UpgradeGate(Arc<Mutex<HashMap<Dex, ProgramUpgrade>>>);
+
+impl UpgradeGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_paused(&self, dex: Dex) -> bool {
+        self.0.lock().unwrap().contains_key(&dex)
+    }
+
+    /// Upgrades still waiting for confirmation
+    pub fn pending(&self) -> Vec<ProgramUpgrade> {
+        self.0.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Resumes `dex`, false when it was not paused
+    pub fn confirm(&self, dex: Dex) -> bool {
+        self.0.lock().unwrap().remove(&dex).is_some()
+    }
+
+    /// Resumes every paused DEX and returns them
+    pub fn confirm_all(&self) -> Vec<Dex> {
+        self.0.lock().unwrap().drain().map(|(dex, _)| dex).collect()
+    }
+
+    fn hold(&self, upgrade: ProgramUpgrade) {
+        self.0.lock().unwrap().insert(upgrade.dex, upgrade);
+    }
+}
+
+/// Decode-stage handler, add it before [`super::AccountDecoder`] so paused updates are never
+/// decoded. ProgramData updates stop here, nothing later in the pipeline can decode them.
+pub struct UpgradeMonitor {
+    programs: HashMap<Pubkey, Dex>,
+    baselines: HashMap<Dex, ProgramDataState>,
+    gate: Option<UpgradeGate>,
+}
+
+impl Default for UpgradeMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UpgradeMonitor {
+    pub const NAME: &'static str = "upgrade_monitor";
+
+    pub fn new() -> Self {
+        Self {
+            programs: program_data_accounts().into_iter().map(|(dex, address)| (address, dex)).collect(),
+            baselines: HashMap::new(),
+            gate: None,
+        }
+    }
+
+    /// Skips every update owned by an upgraded DEX until `gate` confirms it
+    pub fn with_gate(mut self, gate: UpgradeGate) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    /// Known state of `dex`, e.g. fetched over RPC at startup, so the first write is compared to it
+    pub fn with_baseline(mut self, dex: Dex, state: ProgramDataState) -> Self {
+        self.baselines.insert(dex, state);
+        self
+    }
+
+    /// Records `state` as the latest ProgramData of `dex`, seen in an update at `slot`.
+    ///
+    /// Without a baseline only a deployment in that very slot counts as an upgrade, other writes
+    /// (authority changes, extends) become the baseline.
+    pub fn observe(&mut self, dex: Dex, slot: u64, state: ProgramDataState) -> Option<ProgramUpgrade> {
+        let previous = self.baselines.insert(dex, state);
+        let upgraded = match &previous {
+            Some(previous) => previous.slot != state.slot || previous.bytecode_hash != state.bytecode_hash,
+            None => state.slot == slot,
+        };
+        upgraded.then_some(ProgramUpgrade {
+            dex,
+            previous,
+            current: state,
+            seen_at: slot,
+        })
+    }
+}
+
+impl Handler for UpgradeMonitor {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Decode
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let update = &account.update;
+        if update.owner == bpf_loader_upgradeable::id() {
+            let Some(&dex) = self.programs.get(&update.pubkey) else {
+                return Ok(Flow::Continue);
+            };
+            let Some(state) = ProgramDataState::parse(&update.data) else {
+                return Ok(Flow::Skip(format!("{} program data is not a ProgramData account", dex.tag())));
+            };
+            let Some(upgrade) = self.observe(dex, update.slot, state) else {
+                return Ok(Flow::Skip(format!("{} program unchanged since slot {}", dex.tag(), state.slot)));
+            };
+            error!(
+                "🚨 {} program {} upgraded at slot {}  bytecode {} (was {:?})  layouts may have changed",
+                dex.tag(),
+                dex.program_id(),
+                state.slot,
+                state.bytecode_hash,
+                upgrade.previous.map(|previous| previous.bytecode_hash),
+            );
+            account.events.push(PipelineEvent::ProgramUpgraded {
+                dex,
+                program: dex.program_id(),
+                deployed_slot: state.slot,
+            });
+            if let Some(gate) = &self.gate {
+                warn!("pausing {} updates until the upgrade is confirmed", dex.tag());
+                gate.hold(upgrade);
+            }
+            return Ok(Flow::Skip(format!("{} upgraded at slot {}", dex.tag(), state.slot)));
+        }
+
+        let paused = match (&self.gate, Dex::from_program_id(&update.owner)) {
+            (Some(gate), Some(dex)) if gate.is_paused(dex) => dex,
+            _ => return Ok(Flow::Continue),
+        };
+        Ok(Flow::Skip(format!("{} paused after a program upgrade, waiting for confirmation", paused.tag())))
+    }
+}
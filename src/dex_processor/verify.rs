@@ -0,0 +1,179 @@
+//! Checks that the accounts a decoded pool points at are the PDAs its program would derive.
+//!
+//! Decoding only proves the pool itself is owned by the DEX program. The vaults, reserves and
+//! mints it references are plain pubkeys, so [`verify`] re-derives each of them with
+//! [`crate::dex::pda`]. Only CLMM pool addresses are checked, the other DEXes also accept
+//! keypair pools.
+
+use std::collections::HashMap;
+
+use log::warn;
+use solana_program::pubkey::Pubkey;
+
+use crate::dex::pda::{meteora_dlmm, meteora_pools, raydium_clmm, raydium_cpmm};
+
+use super::{DecodedAccount, Flow, Handler, ProcessedAccount, Stage};
+
+/// A referenced account that is not the expected PDA
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PdaMismatch {
+    pub field: &'static str,
+    pub expected: Pubkey,
+    pub found: Pubkey,
+}
+
+impl std::fmt::Display for PdaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is {}, expected {}", self.field, self.found, self.expected)
+    }
+}
+
+struct Checks(Vec<PdaMismatch>);
+
+impl Checks {
+    fn check(&mut self, field: &'static str, found: Pubkey, (expected, _): (Pubkey, u8)) {
+        if found != expected {
+            self.0.push(PdaMismatch { field, expected, found });
+        }
+    }
+}
+
+/// Pools [`verify`] derives accounts for; Raydium v4 pools predate its PDAs and SolFi has none
+pub fn is_verifiable(decoded: &DecodedAccount) -> bool {
+    matches!(
+        decoded,
+        DecodedAccount::RaydiumCpmm(_)
+            | DecodedAccount::RaydiumClmm(_)
+            | DecodedAccount::MeteoraDlmm(_)
+            | DecodedAccount::MeteoraPools(_)
+    )
+}
+
+/// Mismatches of the pool at `pool`, empty when every derivable account matches or the layout
+/// is not [`is_verifiable`]
+pub fn verify(pool: &Pubkey, decoded: &DecodedAccount) -> Vec<PdaMismatch> {
+    let mut checks = Checks(Vec::new());
+    match decoded {
+        DecodedAccount::RaydiumCpmm(state) => {
+            checks.check("token0Vault", state.token0Vault, raydium_cpmm::vault(pool, &state.token0Mint));
+            checks.check("token1Vault", state.token1Vault, raydium_cpmm::vault(pool, &state.token1Mint));
+            checks.check("lpMint", state.lpMint, raydium_cpmm::lp_mint(pool));
+            checks.check("observationKey", state.observationKey, raydium_cpmm::observation(pool));
+        }
+        DecodedAccount::RaydiumClmm(state) => {
+            // CLMM pools are always PDAs, unlike their observation accounts
+            checks.check("pool", *pool, raydium_clmm::pool(&state.amm_config, &state.token_mint0, &state.token_mint1));
+            checks.check("token_vault0", state.token_vault0, raydium_clmm::vault(pool, &state.token_mint0));
+            checks.check("token_vault1", state.token_vault1, raydium_clmm::vault(pool, &state.token_mint1));
+        }
+        DecodedAccount::MeteoraDlmm(state) => {
+            checks.check("reserve_x", state.reserve_x, meteora_dlmm::reserve(pool, &state.token_x_mint));
+            checks.check("reserve_y", state.reserve_y, meteora_dlmm::reserve(pool, &state.token_y_mint));
+            checks.check("oracle", state.oracle, meteora_dlmm::oracle(pool));
+        }
+        DecodedAccount::MeteoraPools(state) => {
+            checks.check("a_vault", state.a_vault, meteora_pools::vault(&state.token_a_mint));
+            checks.check("b_vault", state.b_vault, meteora_pools::vault(&state.token_b_mint));
+            checks.check("a_vault_lp", state.a_vault_lp, meteora_pools::vault_lp(&state.a_vault, pool));
+            checks.check("b_vault_lp", state.b_vault_lp, meteora_pools::vault_lp(&state.b_vault, pool));
+            // permissioned pools were set up by the admin, their LP mint and fee accounts may be keypairs
+            if state.pool_type == 1 {
+                checks.check("lp_mint", state.lp_mint, meteora_pools::lp_mint(pool));
+                checks.check(
+                    "protocol_token_a_fee",
+                    state.protocol_token_a_fee,
+                    meteora_pools::protocol_fee(&state.token_a_mint, pool),
+                );
+                checks.check(
+                    "protocol_token_b_fee",
+                    state.protocol_token_b_fee,
+                    meteora_pools::protocol_fee(&state.token_b_mint, pool),
+                );
+            }
+        }
+        _ => {}
+    }
+    checks.0
+}
+
+/// Verdicts of pools not seen for this many slots (about an hour) are dropped, the pool is
+/// derived again if it shows up later
+pub const VERDICT_SLOTS: u64 = 9_000;
+
+/// Filter-stage handler skipping pools whose referenced accounts are not the expected PDAs. Add
+/// it before [`super::NewPoolFilter`] so spoofed pools never raise events. Referenced accounts do
+/// not change after pool creation, so each pool is derived once and the verdict kept: verified
+/// pools pass from then on, rejected ones are skipped with the same reason and warned about once.
+/// Verdicts expire after [`VERDICT_SLOTS`] without an update of the pool.
+#[derive(Debug)]
+pub struct VerifyPdas {
+    /// Slot each pool was last seen at and its mismatches, `None` once verified
+    verdicts: HashMap<Pubkey, (u64, Option<String>)>,
+    expiry: u64,
+    newest_slot: u64,
+    pruned_at: u64,
+}
+
+impl Default for VerifyPdas {
+    fn default() -> Self {
+        Self {
+            verdicts: HashMap::new(),
+            expiry: VERDICT_SLOTS,
+            newest_slot: 0,
+            pruned_at: 0,
+        }
+    }
+}
+
+impl VerifyPdas {
+    /// Keep verdicts for `slots` slots without an update instead of [`VERDICT_SLOTS`]
+    pub fn with_expiry(mut self, slots: u64) -> Self {
+        self.expiry = slots;
+        self
+    }
+
+    fn prune(&mut self, slot: u64) {
+        self.newest_slot = self.newest_slot.max(slot);
+        if self.newest_slot >= self.pruned_at + self.expiry {
+            let oldest = self.newest_slot.saturating_sub(self.expiry);
+            self.verdicts.retain(|_, (seen, _)| *seen >= oldest);
+            self.pruned_at = self.newest_slot;
+        }
+    }
+}
+
+impl Handler for VerifyPdas {
+    fn name(&self) -> &'static str {
+        "verify_pdas"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Filter
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let pool = account.update.pubkey;
+        let Some(decoded) = account.decoded.as_ref().filter(|decoded| is_verifiable(decoded)) else {
+            return Ok(Flow::Continue);
+        };
+        let slot = account.update.slot;
+        self.prune(slot);
+        if let Some((seen, verdict)) = self.verdicts.get_mut(&pool) {
+            *seen = (*seen).max(slot);
+            return Ok(match verdict {
+                None => Flow::Continue,
+                Some(reason) => Flow::Skip(format!("spoofed accounts: {}", reason)),
+            });
+        }
+        let mismatches = verify(&pool, decoded);
+        if mismatches.is_empty() {
+            self.verdicts.insert(pool, (slot, None));
+            return Ok(Flow::Continue);
+        }
+        let reason = mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        warn!("⚠ {} references accounts that are not its PDAs: {}", pool, reason);
+        let skip = Flow::Skip(format!("spoofed accounts: {}", reason));
+        self.verdicts.insert(pool, (slot, Some(reason)));
+        Ok(skip)
+    }
+}
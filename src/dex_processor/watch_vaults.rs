@@ -0,0 +1,47 @@
+use log::info;
+
+use crate::stream::SubscriptionController;
+
+use super::{DecodedAccount, Flow, Handler, PipelineEvent, ProcessedAccount, Stage};
+
+/// Filter name the vaults are watched under
+pub const POOL_VAULTS_FILTER: &str = "pool_vaults";
+
+/// Act-stage handler that adds the base and quote vaults of every [`PipelineEvent::NewPool`] to
+/// the live subscription, so their balance changes stream in from the next update on
+pub struct WatchPoolVaults {
+    controller: SubscriptionController,
+}
+
+impl WatchPoolVaults {
+    pub fn new(controller: SubscriptionController) -> Self {
+        Self { controller }
+    }
+}
+
+impl Handler for WatchPoolVaults {
+    fn name(&self) -> &'static str {
+        "watch_pool_vaults"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Act
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        let is_new_pool = account
+            .events
+            .iter()
+            .any(|event| matches!(event, PipelineEvent::NewPool { pool, .. } if *pool == account.update.pubkey));
+        let Some(DecodedAccount::RaydiumAmmV4(state)) = &account.decoded else {
+            return Ok(Flow::Continue);
+        };
+        if is_new_pool {
+            let vaults = [state.baseVault, state.quoteVault];
+            if self.controller.watch(POOL_VAULTS_FILTER, vaults) {
+                info!("watching vaults {} / {} of pool {}", vaults[0], vaults[1], account.update.pubkey);
+            }
+        }
+        Ok(Flow::Continue)
+    }
+}
@@ -1,29 +1,51 @@
+use bytemuck::{Pod, Zeroable};
 use serde::{Deserialize, Serialize};
-use solana_program::pubkey;
+use solana_program::{clock::Clock, pubkey};
 use solana_sdk::pubkey::Pubkey;
 
+use crate::common::serde_helpers::{pubkey_string, u128_string};
+use crate::common::view::AccountView;
+
 pub const SOLC_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 
-#[repr(C)]                   
+/// Size of the Raydium AMM v4 pool account
+pub const LIQUIDITY_STATE_LAYOUT_V4_SIZE: usize = 752;
+
+/// Raydium AMM v4 pool state (`AmmInfo` on chain).
+///
+/// The struct is `packed`, so it can be viewed in place over account bytes of any alignment
+/// with [`LIQUIDITY_STATE_LAYOUT_V4::from_bytes`]. Fields are read by value
+/// (`let s = { state.status };` when a reference would be needed, e.g. in `format!`).
+#[repr(C, packed)]
 #[allow(non_snake_case, non_camel_case_types)]
-#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Pod, Zeroable)]
 pub struct LIQUIDITY_STATE_LAYOUT_V4 {
+    /// Pool status, see [`AmmStatus`]
     pub status: u64,
+    /// Nonce used to derive the `amm authority` program address
     pub nonce: u64,
+    /// max order count
     pub maxOrder: u64,
+    /// within this range, 5 => 5% range
     pub depth: u64,
     pub baseDecimal: u64,
     pub quoteDecimal: u64,
+    /// Orderbook machine state, see [`AmmState`]
     pub state: u64,
     pub resetFlag: u64,
+    /// min size 1->0.000001
     pub minSize: u64,
+    /// vol_max_cut_ratio numerator, systemDecimalValue as denominator
     pub volMaxCutRatio: u64,
+    /// amount wave numerator, systemDecimalValue as denominator
     pub amountWaveRatio: u64,
     pub baseLotSize: u64,
     pub quoteLotSize: u64,
     pub minPriceMultiplier: u64,
     pub maxPriceMultiplier: u64,
+    /// system decimal value, used to normalize the value of coin and pc amount
     pub systemDecimalValue: u64,
+    // fees
     pub minSeparateNumerator: u64,
     pub minSeparateDenominator: u64,
     pub tradeFeeNumerator: u64,
@@ -32,131 +54,212 @@ pub struct LIQUIDITY_STATE_LAYOUT_V4 {
     pub pnlDenominator: u64,
     pub swapFeeNumerator: u64,
     pub swapFeeDenominator: u64,
+    // state data
+    /// delay to take pnl coin
     pub baseNeedTakePnl: u64,
+    /// delay to take pnl pc
     pub quoteNeedTakePnl: u64,
     pub quoteTotalPnl: u64,
     pub baseTotalPnl: u64,
+    /// Unix timestamp after which a `WaitingTrade` pool accepts swaps
     pub poolOpenTime: u64,
     pub punishPcAmount: u64,
     pub punishCoinAmount: u64,
+    /// switch from orderbookonly to init
     pub orderbookToInitTime: u64,
     // u128('poolTotalDepositPc'),
     // u128('poolTotalDepositCoin'),
+    #[serde(with = "u128_string")]
     pub swapBaseInAmount: u128,
+    #[serde(with = "u128_string")]
     pub swapQuoteOutAmount: u128,
     pub swapBase2QuoteFee: u64,
+    #[serde(with = "u128_string")]
     pub swapQuoteInAmount: u128,
+    #[serde(with = "u128_string")]
     pub swapBaseOutAmount: u128,
     pub swapQuote2BaseFee: u64,
     // amm vault
+    #[serde(with = "pubkey_string")]
     pub baseVault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub quoteVault: Pubkey,
     // mint
+    #[serde(with = "pubkey_string")]
     pub baseMint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub quoteMint: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub lpMint: Pubkey,
     // market
+    #[serde(with = "pubkey_string")]
     pub openOrders: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub marketId: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub marketProgramId: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub targetOrders: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub withdrawQueue: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub lpVault: Pubkey,
+    #[serde(with = "pubkey_string")]
     pub owner: Pubkey,
     // true circulating supply without lock up
     pub lpReserve: u64,
     pub padding: [u64; 3],
 }
 
+const _: () = assert!(std::mem::size_of::<LIQUIDITY_STATE_LAYOUT_V4>() == LIQUIDITY_STATE_LAYOUT_V4_SIZE);
+
+impl AccountView for LIQUIDITY_STATE_LAYOUT_V4 {}
+
 impl LIQUIDITY_STATE_LAYOUT_V4 {
+    /// Borrow the state directly from account bytes without copying.
+    pub fn from_bytes(data: &[u8]) -> eyre::Result<&Self> {
+        let bytes = data.get(..LIQUIDITY_STATE_LAYOUT_V4_SIZE).ok_or_else(|| {
+            eyre::eyre!(
+                "v4 state needs {} bytes, got {}",
+                LIQUIDITY_STATE_LAYOUT_V4_SIZE,
+                data.len()
+            )
+        })?;
+        bytemuck::try_from_bytes(bytes).map_err(|e| eyre::eyre!("v4 state cast failed: {:?}", e))
+    }
+
+    /// Copy the state out of `input` and advance it past the account.
     pub fn decode(input: &mut &[u8]) -> eyre::Result<Self> {
-        let mut s = Self::default();
-        s.status = Self::unpack_u64(input)?;
-        s.nonce = Self::unpack_u64(input)?;
-        s.maxOrder = Self::unpack_u64(input)?;
-        s.depth = Self::unpack_u64(input)?;
-        s.baseDecimal = Self::unpack_u64(input)?;
-        s.quoteDecimal = Self::unpack_u64(input)?;
-        s.state = Self::unpack_u64(input)?;
-        s.resetFlag = Self::unpack_u64(input)?;
-        s.minSize = Self::unpack_u64(input)?;
-        s.volMaxCutRatio = Self::unpack_u64(input)?;
-        s.amountWaveRatio = Self::unpack_u64(input)?;
-        s.baseLotSize = Self::unpack_u64(input)?;
-        s.quoteLotSize = Self::unpack_u64(input)?;
-        s.minPriceMultiplier = Self::unpack_u64(input)?;
-        s.maxPriceMultiplier = Self::unpack_u64(input)?;
-        s.systemDecimalValue = Self::unpack_u64(input)?;
-        s.minSeparateNumerator = Self::unpack_u64(input)?;
-        s.minSeparateDenominator = Self::unpack_u64(input)?;
-        s.tradeFeeNumerator = Self::unpack_u64(input)?;
-        s.tradeFeeDenominator = Self::unpack_u64(input)?;
-        s.pnlNumerator = Self::unpack_u64(input)?;
-        s.pnlDenominator = Self::unpack_u64(input)?;
-        s.swapFeeNumerator = Self::unpack_u64(input)?;
-        s.swapFeeDenominator = Self::unpack_u64(input)?;
-        s.baseNeedTakePnl = Self::unpack_u64(input)?;
-        s.quoteNeedTakePnl = Self::unpack_u64(input)?;
-        s.quoteTotalPnl = Self::unpack_u64(input)?;
-        s.baseTotalPnl = Self::unpack_u64(input)?;
-        s.poolOpenTime = Self::unpack_u64(input)?;
-        s.punishPcAmount = Self::unpack_u64(input)?;
-        s.punishCoinAmount = Self::unpack_u64(input)?;
-        s.orderbookToInitTime = Self::unpack_u64(input)?;
-        // u128('poolTotalDepositPc'),
-        // u128('poolTotalDepositCoin'),
-        s.swapBaseInAmount = Self::unpack_u128(input)?;
-        s.swapQuoteOutAmount = Self::unpack_u128(input)?;
-        s.swapBase2QuoteFee = Self::unpack_u64(input)?;
-        s.swapQuoteInAmount = Self::unpack_u128(input)?;
-        s.swapBaseOutAmount = Self::unpack_u128(input)?;
-        s.swapQuote2BaseFee = Self::unpack_u64(input)?;
-        // amm vault
-
-        s.baseVault = Self::unpack_pubkey(input)?;
-        s.quoteVault = Self::unpack_pubkey(input)?;
-        // mint
-        s.baseMint = Self::unpack_pubkey(input)?;
-        s.quoteMint = Self::unpack_pubkey(input)?;
-        s.lpMint = Self::unpack_pubkey(input)?;
-        // market
-        s.openOrders = Self::unpack_pubkey(input)?;
-        s.marketId = Self::unpack_pubkey(input)?;
-        s.marketProgramId = Self::unpack_pubkey(input)?;
-        s.targetOrders = Self::unpack_pubkey(input)?;
-        s.withdrawQueue = Self::unpack_pubkey(input)?;
-        s.lpVault = Self::unpack_pubkey(input)?;
-        s.owner = Self::unpack_pubkey(input)?;
-        // true circulating supply without lock up
-        s.lpReserve = Self::unpack_u64(input)?;
-        s.padding = [
-            Self::unpack_u64(input)?,
-            Self::unpack_u64(input)?,
-            Self::unpack_u64(input)?,
-        ];
+        let s = *Self::from_bytes(input)?;
+        *input = &input[LIQUIDITY_STATE_LAYOUT_V4_SIZE..];
         Ok(s)
     }
-    fn unpack_u64(input: &mut &[u8]) -> eyre::Result<u64> {
-        use std::io::Read;
 
-        let mut buf = [0u8; 8];
-        input.read_exact(&mut buf)?;
-        Ok(u64::from_le_bytes(buf))
+    /// The account bytes of this state, the inverse of [`Self::from_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+
+    pub fn amm_status(&self) -> Result<AmmStatus, u64> {
+        AmmStatus::try_from(self.status)
+    }
+
+    pub fn amm_state(&self) -> Result<AmmState, u64> {
+        AmmState::try_from(self.state)
     }
 
-    fn unpack_u128(input: &mut &[u8]) -> eyre::Result<u128> {
-        use std::io::Read;
+    /// Whether a swap sent at `unix_timestamp` would pass the program's status check.
+    /// `WaitingTrade` pools become tradable once `poolOpenTime` is reached.
+    pub fn can_swap_at(&self, unix_timestamp: i64) -> bool {
+        match self.amm_status() {
+            Ok(AmmStatus::WaitingTrade) => unix_timestamp >= 0 && unix_timestamp as u64 >= self.poolOpenTime,
+            Ok(status) => status.swap_permission(),
+            Err(_) => false,
+        }
+    }
 
-        let mut buf = [0u8; 16];
-        input.read_exact(&mut buf)?;
-        Ok(u128::from_le_bytes(buf))
+    pub fn can_swap(&self, clock: &Clock) -> bool {
+        self.can_swap_at(clock.unix_timestamp)
     }
-    fn unpack_pubkey(input: &mut &[u8]) -> eyre::Result<Pubkey> {
-        use std::io::Read;
 
-        let mut buf = [0u8; 32];
-        input.read_exact(&mut buf)?;
-        Ok(Pubkey::new_from_array(buf))
+    /// Seconds until the pool opens for swaps, `None` if it is already open or can never swap
+    /// in its current status.
+    pub fn seconds_until_open(&self, unix_timestamp: i64) -> Option<u64> {
+        if self.can_swap_at(unix_timestamp) || self.amm_status() != Ok(AmmStatus::WaitingTrade) {
+            return None;
+        }
+        Some(self.poolOpenTime.saturating_sub(unix_timestamp.max(0) as u64))
+    }
+
+    pub fn orderbook_enabled(&self) -> bool {
+        self.amm_status().is_ok_and(|s| s.orderbook_permission())
+    }
+}
+
+/// `AmmInfo::status`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u64)]
+pub enum AmmStatus {
+    Uninitialized = 0,
+    Initialized = 1,
+    Disabled = 2,
+    WithdrawOnly = 3,
+    /// pool only can add or remove liquidity, can't swap and plan orders
+    LiquidityOnly = 4,
+    /// pool only can add or remove liquidity and plan orders, can't swap
+    OrderBookOnly = 5,
+    /// pool only can add or remove liquidity and swap, can't plan orders
+    SwapOnly = 6,
+    /// pool status after created, switches to SwapOnly on the first swap after poolOpenTime
+    WaitingTrade = 7,
+}
+
+impl TryFrom<u64> for AmmStatus {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Uninitialized,
+            1 => Self::Initialized,
+            2 => Self::Disabled,
+            3 => Self::WithdrawOnly,
+            4 => Self::LiquidityOnly,
+            5 => Self::OrderBookOnly,
+            6 => Self::SwapOnly,
+            7 => Self::WaitingTrade,
+            other => return Err(other),
+        })
+    }
+}
+
+impl AmmStatus {
+    pub fn deposit_permission(&self) -> bool {
+        !matches!(self, Self::Uninitialized | Self::Disabled | Self::WithdrawOnly)
+    }
+
+    pub fn withdraw_permission(&self) -> bool {
+        !matches!(self, Self::Uninitialized | Self::Disabled)
+    }
+
+    /// Swap permission ignoring `poolOpenTime`, see [`LIQUIDITY_STATE_LAYOUT_V4::can_swap_at`]
+    pub fn swap_permission(&self) -> bool {
+        matches!(self, Self::Initialized | Self::SwapOnly | Self::WaitingTrade)
+    }
+
+    pub fn orderbook_permission(&self) -> bool {
+        matches!(self, Self::Initialized | Self::OrderBookOnly)
+    }
+}
+
+/// `AmmInfo::state`, the orderbook planning state machine
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u64)]
+pub enum AmmState {
+    Invalid = 0,
+    Idle = 1,
+    CancelAllOrders = 2,
+    PlanOrders = 3,
+    CancelOrder = 4,
+    PlaceOrders = 5,
+    PurgeOrder = 6,
+    WithdrawTransfer = 7,
+}
+
+impl TryFrom<u64> for AmmState {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::Invalid,
+            1 => Self::Idle,
+            2 => Self::CancelAllOrders,
+            3 => Self::PlanOrders,
+            4 => Self::CancelOrder,
+            5 => Self::PlaceOrders,
+            6 => Self::PurgeOrder,
+            7 => Self::WithdrawTransfer,
+            other => return Err(other),
+        })
     }
 }
 
@@ -166,4 +269,4 @@ pub async fn program_address(program_id: &Pubkey) -> eyre::Result<Pubkey> {
 
     let (key, _bump_seed) = Pubkey::find_program_address(seeds, program_id);
     Ok(key)
-}
\ No newline at end of file
+}
@@ -4,7 +4,6 @@
 #![allow(deprecated)]
 
 // use crate::instruction::state::{AmmParams, Fees, LastOrderDistance, SimulateParams};
-use arrayref::array_ref;
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_program::{
@@ -18,7 +17,6 @@ use solana_sdk::{
 };
 use std::convert::TryInto;
 use std::mem::size_of;
-use solana_sdk::pubkey::Pubkey as SolanaPubkey;
 use super::{
     decoder::{program_address, LIQUIDITY_STATE_LAYOUT_V4, SOLC_MINT},
     error::AmmError,
@@ -264,25 +262,25 @@ pub enum AmmInstruction {
     ///   20. '[]` Admin Account
     MigrateToOpenBook,
 
-    ///   Set AMM params
-    ///
-    ///   0. `[]` Spl Token program id
-    ///   1. `[writable]` AMM Account.
-    ///   2. `[]` $authority derived from `create_program_address(&[AUTHORITY_AMM, &[nonce]])`.
-    ///   3. `[writable]` AMM open orders Account
-    ///   4. `[writable]` AMM target orders Account
-    ///   5. `[writable]` AMM coin vault account owned by $authority,
-    ///   6. `[writable]` AMM pc vault account owned by $authority,
-    ///   7. `[]` Market program id
-    ///   8. `[writable]` Market Account. Market program is the owner.
-    ///   9. `[writable]` Market coin vault Account
-    ///   10. `[writable]` Market pc vault Account
-    ///   11. '[]` Market vault signer Account
-    ///   12. `[writable]` Market event queue Account
-    ///   13. `[writable]` Market bids Account
-    ///   14. `[writable]` Market asks Account
-    ///   15. `[singer]` Admin Account
-    ///   16. `[]` (optional) New AMM open orders Account to replace old AMM open orders Account
+    //   Set AMM params
+    //
+    //   0. `[]` Spl Token program id
+    //   1. `[writable]` AMM Account.
+    //   2. `[]` $authority derived from `create_program_address(&[AUTHORITY_AMM, &[nonce]])`.
+    //   3. `[writable]` AMM open orders Account
+    //   4. `[writable]` AMM target orders Account
+    //   5. `[writable]` AMM coin vault account owned by $authority,
+    //   6. `[writable]` AMM pc vault account owned by $authority,
+    //   7. `[]` Market program id
+    //   8. `[writable]` Market Account. Market program is the owner.
+    //   9. `[writable]` Market coin vault Account
+    //   10. `[writable]` Market pc vault Account
+    //   11. '[]` Market vault signer Account
+    //   12. `[writable]` Market event queue Account
+    //   13. `[writable]` Market bids Account
+    //   14. `[writable]` Market asks Account
+    //   15. `[singer]` Admin Account
+    //   16. `[]` (optional) New AMM open orders Account to replace old AMM open orders Account
     // SetParams(SetParamsInstruction),
 
     ///   Withdraw Pnl from pool by protocol
@@ -460,10 +458,10 @@ impl AmmInstruction {
                 let (param, rest) = Self::unpack_u8(rest)?;
                 match param {
                     0 | 1 => {
-                        let pubkey = array_ref![rest, 0, 32];
+                        let (owner, _rest) = Self::unpack_pubkey(rest)?;
                         Self::UpdateConfigAccount(ConfigArgs {
                             param,
-                            owner: Some(Pubkey::new_from_array(*pubkey)),
+                            owner: Some(owner),
                             create_pool_fee: None,
                         })
                     }
@@ -476,16 +474,16 @@ impl AmmInstruction {
                         })
                     }
                     _ => {
-                        return Err(ProgramError::InvalidInstructionData.into());
+                        return Err(ProgramError::InvalidInstructionData);
                     }
                 }
             }
-            _ => return Err(ProgramError::InvalidInstructionData.into()),
+            _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
 
     fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
-        if input.len() >= 1 {
+        if !input.is_empty() {
             let (amount, rest) = input.split_at(1);
             let amount = amount
                 .get(..1)
@@ -494,7 +492,7 @@ impl AmmInstruction {
                 .ok_or(ProgramError::InvalidInstructionData)?;
             Ok((amount, rest))
         } else {
-            Err(ProgramError::InvalidInstructionData.into())
+            Err(ProgramError::InvalidInstructionData)
         }
     }
 
@@ -508,7 +506,7 @@ impl AmmInstruction {
                 .ok_or(ProgramError::InvalidInstructionData)?;
             Ok((amount, rest))
         } else {
-            Err(ProgramError::InvalidInstructionData.into())
+            Err(ProgramError::InvalidInstructionData)
         }
     }
 
@@ -522,14 +520,24 @@ impl AmmInstruction {
                 .ok_or(ProgramError::InvalidInstructionData)?;
             Ok((amount, rest))
         } else {
-            Err(ProgramError::InvalidInstructionData.into())
+            Err(ProgramError::InvalidInstructionData)
+        }
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (key, rest) = input.split_at(32);
+            let key = Pubkey::try_from(key).map_err(|_| ProgramError::InvalidInstructionData)?;
+            Ok((key, rest))
+        } else {
+            Err(ProgramError::InvalidInstructionData)
         }
     }
 
     /// Packs a [AmmInstruction](enum.AmmInstruction.html) into a byte buffer.
     pub fn pack(&self) -> Result<Vec<u8>, ProgramError> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
-        match &*self {
+        match self {
             Self::Initialize(InitializeInstruction { nonce, open_time }) => {
                 buf.push(0);
                 buf.push(*nonce);
@@ -622,23 +630,23 @@ impl AmmInstruction {
                         let owner = match owner {
                             Some(owner) => {
                                 if *owner == Pubkey::default() {
-                                    return Err(ProgramError::InvalidInstructionData.into());
+                                    return Err(ProgramError::InvalidInstructionData);
                                 } else {
                                     owner
                                 }
                             }
-                            None => return Err(ProgramError::InvalidInstructionData.into()),
+                            None => return Err(ProgramError::InvalidInstructionData),
                         };
                         buf.extend_from_slice(&owner.to_bytes());
                     }
                     2 => {
                         let create_pool_fee = match create_pool_fee {
                             Some(create_pool_fee) => create_pool_fee,
-                            None => return Err(ProgramError::InvalidInstructionData.into()),
+                            None => return Err(ProgramError::InvalidInstructionData),
                         };
                         buf.extend_from_slice(&create_pool_fee.to_le_bytes());
                     }
-                    _ => return Err(ProgramError::InvalidInstructionData.into()),
+                    _ => return Err(ProgramError::InvalidInstructionData),
                 }
             }
         }
@@ -658,9 +666,9 @@ pub fn initialize_amm_pool(
     pc_amount: u64,   // transfer pc asset to the pool pc vault as pool init vault
     coin_amount: u64, // transfer coin asset to the pool coin vault as pool init vault
 ) -> eyre::Result<Instruction> {
-    println!("Coin: {}\nPC: {}\nLP: {}", user_coin, user_pc, user_lp);
+    log::debug!("initialize2 token accounts: coin {} pc {} lp {}", user_coin, user_pc, user_lp);
     let amm_pool_init_instruction = initialize2(
-        &amm_program,
+        amm_program,
         &amm_keys.amm_pool,
         &amm_keys.amm_authority,
         &amm_keys.amm_open_order,
@@ -670,14 +678,14 @@ pub fn initialize_amm_pool(
         &amm_keys.amm_coin_vault,
         &amm_keys.amm_pc_vault,
         &amm_keys.amm_target,
-        &Pubkey::find_program_address(&[&AMM_CONFIG_SEED], &amm_program).0,
+        &Pubkey::find_program_address(&[AMM_CONFIG_SEED], amm_program).0,
         create_fee_detination,
         &amm_keys.market_program,
         &amm_keys.market,
-        &user_owner,
-        &user_coin,
-        &user_pc,
-        &user_lp,
+        user_owner,
+        user_coin,
+        user_pc,
+        user_lp,
         amm_keys.nonce,
         open_time,
         pc_amount,
@@ -1370,46 +1378,46 @@ pub fn get_amm_pda_keys(
     pc_mint: &Pubkey,
 ) -> AmmKeys {
     let amm_pool = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         AMM_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
-    let (amm_authority, nonce) = Pubkey::find_program_address(&[AUTHORITY_AMM], &amm_program);
+    let (amm_authority, nonce) = Pubkey::find_program_address(&[AUTHORITY_AMM], amm_program);
     let amm_open_order = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         OPEN_ORDER_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
     let amm_lp_mint = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         LP_MINT_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
     let amm_coin_vault = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         COIN_VAULT_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
     let amm_pc_vault = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         PC_VAULT_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
     let amm_target = get_associated_address_and_bump_seed(
-        &amm_program,
-        &market,
+        amm_program,
+        market,
         TARGET_ASSOCIATED_SEED,
-        &amm_program,
+        amm_program,
     )
     .0;
 
@@ -1446,21 +1454,21 @@ pub struct AmmKeys {
 }
 
 /// Suffix for amm authority seed
-pub const AUTHORITY_AMM: &'static [u8] = b"amm authority";
+pub const AUTHORITY_AMM: &[u8] = b"amm authority";
 /// Suffix for amm associated seed
-pub const AMM_ASSOCIATED_SEED: &'static [u8] = b"amm_associated_seed";
+pub const AMM_ASSOCIATED_SEED: &[u8] = b"amm_associated_seed";
 /// Suffix for target associated seed
-pub const TARGET_ASSOCIATED_SEED: &'static [u8] = b"target_associated_seed";
+pub const TARGET_ASSOCIATED_SEED: &[u8] = b"target_associated_seed";
 /// Suffix for amm open order associated seed
-pub const OPEN_ORDER_ASSOCIATED_SEED: &'static [u8] = b"open_order_associated_seed";
+pub const OPEN_ORDER_ASSOCIATED_SEED: &[u8] = b"open_order_associated_seed";
 /// Suffix for coin vault associated seed
-pub const COIN_VAULT_ASSOCIATED_SEED: &'static [u8] = b"coin_vault_associated_seed";
+pub const COIN_VAULT_ASSOCIATED_SEED: &[u8] = b"coin_vault_associated_seed";
 /// Suffix for pc vault associated seed
-pub const PC_VAULT_ASSOCIATED_SEED: &'static [u8] = b"pc_vault_associated_seed";
+pub const PC_VAULT_ASSOCIATED_SEED: &[u8] = b"pc_vault_associated_seed";
 /// Suffix for lp mint associated seed
-pub const LP_MINT_ASSOCIATED_SEED: &'static [u8] = b"lp_mint_associated_seed";
+pub const LP_MINT_ASSOCIATED_SEED: &[u8] = b"lp_mint_associated_seed";
 /// Amm config seed
-pub const AMM_CONFIG_SEED: &'static [u8] = b"amm_config_account_seed";
+pub const AMM_CONFIG_SEED: &[u8] = b"amm_config_account_seed";
 
 pub fn get_associated_address_and_bump_seed(
     info_id: &Pubkey,
@@ -1472,7 +1480,7 @@ pub fn get_associated_address_and_bump_seed(
         &[
             &info_id.to_bytes(),
             &market_address.to_bytes(),
-            &associated_seed,
+            associated_seed,
         ],
         program_id,
     )
@@ -1507,10 +1515,9 @@ pub fn swap(
     other_amount_threshold: u64,
     out: bool,
 ) -> eyre::Result<Instruction> {
-    let swap_instruction;
-    if out {
-        swap_instruction = swap_base_in(
-            &amm_program,
+    let swap_instruction = if out {
+        swap_base_in(
+            amm_program,
             &amm_keys.amm_pool,
             &amm_keys.amm_authority,
             &amm_keys.amm_open_order,
@@ -1530,10 +1537,10 @@ pub fn swap(
             user_owner,
             amount_specified,
             other_amount_threshold,
-        )?;
+        )?
     } else {
-        swap_instruction = swap_base_in(
-            &amm_program,
+        swap_base_in(
+            amm_program,
             &amm_keys.amm_pool,
             &amm_keys.amm_authority,
             &amm_keys.amm_open_order,
@@ -1553,118 +1560,60 @@ pub fn swap(
             user_owner,
             amount_specified,
             other_amount_threshold,
-        )?;
-    }
+        )?
+    };
     Ok(swap_instruction)
 }
 
-#[cfg(target_endian = "little")]
 pub async fn get_keys_for_market<'a>(
     client: &'a RpcClient,
     program_id: &'a Pubkey,
     market: &'a Pubkey,
 ) -> eyre::Result<MarketPubkeys> {
-    use std::{borrow::Cow, convert::identity};
-
-    use safe_transmute::{transmute_one_pedantic, transmute_one_to_bytes, transmute_to_bytes};
-    use serum_dex::state::{gen_vault_signer_key, AccountFlag, Market, MarketState, MarketStateV2};
-
-    let account_data: Vec<u8> = client.get_account_data(&market).await?;
-    let words: Cow<[u64]> = remove_dex_account_padding(&account_data)?;
-    let market_state: MarketState = {
-        let account_flags = Market::account_flags(&account_data)?;
-        if account_flags.intersects(AccountFlag::Permissioned) {
-            log::info!("MarketStateV2");
-            let state = transmute_one_pedantic::<MarketStateV2>(transmute_to_bytes(&words))
-                .map_err(|e| e.without_src())?;
-            state.check_flags(true)?;
-            state.inner
-        } else {
-            log::info!("MarketStateV");
-            let state = transmute_one_pedantic::<MarketState>(transmute_to_bytes(&words))
-                .map_err(|e| e.without_src())?;
-            state.check_flags(true)?;
-            state
-        }
-    };
-    let vault_signer_key =
-        gen_vault_signer_key(market_state.vault_signer_nonce, market, program_id)?;
-    assert_eq!(
-        transmute_to_bytes(&identity(market_state.own_address)),
-        market.as_ref()
-    );
+    use crate::dex::raydium_lp_v4::{SerumMarketLayout, SERUM_HEAD_PADDING, SERUM_TAIL_PADDING};
+
+    let account_data: Vec<u8> = client.get_account_data(market).await?;
+    if !account_data.starts_with(SERUM_HEAD_PADDING) || !account_data.ends_with(SERUM_TAIL_PADDING) {
+        return Err(format_err!("dex account padding mismatch"));
+    }
+    // 权限市场(MarketStateV2)只是在尾部多了几个字段，前面的布局相同
+    let market_state = SerumMarketLayout::slice_market(&account_data)
+        .ok_or_else(|| format_err!("dex account length {} is too small", account_data.len()))?;
+    if !market_state.check_flags(true) {
+        return Err(format_err!("invalid market flags {:#x}", market_state.market_flags));
+    }
+    if market_state.own_address != *market {
+        return Err(format_err!("dex account own address mismatch"));
+    }
+    // 与 serum_dex::state::gen_vault_signer_key 相同的种子
+    let vault_signer_key = Pubkey::create_program_address(
+        &[market.as_ref(), &market_state.vault_signer_nonce.to_le_bytes()],
+        program_id,
+    )?;
     Ok(MarketPubkeys {
         market: Box::new(*market),
-        req_q: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.req_q))).unwrap(),
-        ),
-        event_q: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.event_q))).unwrap(),
-        ),
-        bids: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.bids))).unwrap(),
-        ),
-        asks: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.asks))).unwrap(),
-        ),
-        coin_vault: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.coin_vault))).unwrap(),
-        ),
-        pc_vault: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.pc_vault))).unwrap(),
-        ),
+        req_q: Box::new(market_state.request_queue),
+        event_q: Box::new(market_state.event_queue),
+        bids: Box::new(market_state.bids),
+        asks: Box::new(market_state.asks),
+        coin_vault: Box::new(market_state.base_vault),
+        pc_vault: Box::new(market_state.quote_vault),
         vault_signer_key: Box::new(vault_signer_key),
-        coin_mint: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.coin_mint))).unwrap(),
-        ),
-        pc_mint: Box::new(
-            Pubkey::try_from(transmute_one_to_bytes(&identity(market_state.pc_mint))).unwrap(),
-        ),
-        coin_lot_size: market_state.coin_lot_size,
-        pc_lot_size: market_state.pc_lot_size,
+        coin_mint: Box::new(market_state.base_mint),
+        pc_mint: Box::new(market_state.quote_mint),
+        coin_lot_size: market_state.base_lot_size,
+        pc_lot_size: market_state.quote_lot_size,
     })
 }
 use eyre::format_err;
 
-use std::borrow::Cow;
-
-#[cfg(target_endian = "little")]
-fn remove_dex_account_padding<'a>(data: &'a [u8]) -> eyre::Result<Cow<'a, [u64]>> {
-    use safe_transmute::transmute_many_pedantic;
-    use serum_dex::state::{ACCOUNT_HEAD_PADDING, ACCOUNT_TAIL_PADDING};
-    let head = &data[..ACCOUNT_HEAD_PADDING.len()];
-    if data.len() < ACCOUNT_HEAD_PADDING.len() + ACCOUNT_TAIL_PADDING.len() {
-        return Err(format_err!(
-            "dex account length {} is too small to contain valid padding",
-            data.len()
-        ));
-    }
-    if head != ACCOUNT_HEAD_PADDING {
-        return Err(format_err!("dex account head padding mismatch"));
-    }
-    let tail = &data[data.len() - ACCOUNT_TAIL_PADDING.len()..];
-    if tail != ACCOUNT_TAIL_PADDING {
-        return Err(format_err!("dex account tail padding mismatch"));
-    }
-    let inner_data_range = ACCOUNT_HEAD_PADDING.len()..(data.len() - ACCOUNT_TAIL_PADDING.len());
-    let inner: &'a [u8] = &data[inner_data_range];
-    let words: Cow<'a, [u64]> = match transmute_many_pedantic::<u64>(inner) {
-        Ok(word_slice) => Cow::Borrowed(word_slice),
-        Err(transmute_error) => {
-            let word_vec = transmute_error.copy().map_err(|e| e.without_src())?;
-            Cow::Owned(word_vec)
-        }
-    };
-    Ok(words)
-}
-
 pub async fn load_amm_keys(client: &RpcClient, amm_pool: &Pubkey) -> eyre::Result<AmmKeys> {
     let mut retries = 0;
     let max_retries = 1000;
     let mut account = None;
 
     while account.is_none() && retries < max_retries {
-        match client.get_account(&amm_pool).await {
+        match client.get_account(amm_pool).await {
             Ok(acc) => account = Some(acc),
             Err(_) => {
                 retries += 1;
@@ -1704,153 +1653,27 @@ pub async fn load_amm_keys(client: &RpcClient, amm_pool: &Pubkey) -> eyre::Resul
 
 pub fn authority_id(program_id: &Pubkey, amm_seed: &[u8], nonce: u8) -> Result<Pubkey, AmmError> {
     Pubkey::create_program_address(&[amm_seed, &[nonce]], program_id)
-        .map_err(|_| AmmError::InvalidProgramAddress.into())
+        .map_err(|_| AmmError::InvalidProgramAddress)
 }
 
+/// Fetch a zero-copy layout such as [`LIQUIDITY_STATE_LAYOUT_V4`] at processed commitment.
 pub async fn get_account<T>(client: &RpcClient, addr: &Pubkey) -> eyre::Result<Option<T>>
 where
-    T: Clone,
+    T: bytemuck::Pod,
 {
     if let Some(account) = client
         .get_account_with_commitment(addr, CommitmentConfig::processed())
         .await?
         .value
     {
-        let account_data = account.data.as_slice();
-        let ret = unsafe { &*(&account_data[0] as *const u8 as *const T) };
-        Ok(Some(ret.clone()))
+        let account_data = account
+            .data
+            .get(..size_of::<T>())
+            .ok_or_else(|| format_err!("account {} is smaller than the requested layout", addr))?;
+        let ret = bytemuck::try_pod_read_unaligned::<T>(account_data)
+            .map_err(|e| format_err!("account {} layout cast failed: {:?}", addr, e))?;
+        Ok(Some(ret))
     } else {
         Ok(None)
     }
 }
-
-#[repr(C)]
-#[derive(Clone, Copy, Default, PartialEq)]
-pub struct AmmInfo {
-    /// Initialized status.
-    pub status: u64,
-    /// Nonce used in program address.
-    /// The program address is created deterministically with the nonce,
-    /// amm program id, and amm account pubkey.  This program address has
-    /// authority over the amm's token coin account, token pc account, and pool
-    /// token mint.
-    pub nonce: u64,
-    /// max order count
-    pub order_num: u64,
-    /// within this range, 5 => 5% range
-    pub depth: u64,
-    /// coin decimal
-    pub coin_decimals: u64,
-    /// pc decimal
-    pub pc_decimals: u64,
-    /// amm machine state
-    pub state: u64,
-    /// amm reset_flag
-    pub reset_flag: u64,
-    /// min size 1->0.000001
-    pub min_size: u64,
-    /// vol_max_cut_ratio numerator, sys_decimal_value as denominator
-    pub vol_max_cut_ratio: u64,
-    /// amount wave numerator, sys_decimal_value as denominator
-    pub amount_wave: u64,
-    /// coinLotSize 1 -> 0.000001
-    pub coin_lot_size: u64,
-    /// pcLotSize 1 -> 0.000001
-    pub pc_lot_size: u64,
-    /// min_cur_price: (2 * amm.order_num * amm.pc_lot_size) * max_price_multiplier
-    pub min_price_multiplier: u64,
-    /// max_cur_price: (2 * amm.order_num * amm.pc_lot_size) * max_price_multiplier
-    pub max_price_multiplier: u64,
-    /// system decimal value, used to normalize the value of coin and pc amount
-    pub sys_decimal_value: u64,
-    /// All fee information
-    pub fees: Fees,
-    /// Statistical data
-    pub state_data: StateData,
-    /// Coin vault
-    pub coin_vault: Pubkey,
-    /// Pc vault
-    pub pc_vault: Pubkey,
-    /// Coin vault mint
-    pub coin_vault_mint: Pubkey,
-    /// Pc vault mint
-    pub pc_vault_mint: Pubkey,
-    /// lp mint
-    pub lp_mint: Pubkey,
-    /// open_orders key
-    pub open_orders: Pubkey,
-    /// market key
-    pub market: Pubkey,
-    /// market program key
-    pub market_program: Pubkey,
-    /// target_orders key
-    pub target_orders: Pubkey,
-    /// padding
-    pub padding1: [u64; 8],
-    /// amm owner key
-    pub amm_owner: Pubkey,
-    /// pool lp amount
-    pub lp_amount: u64,
-    /// client order id
-    pub client_order_id: u64,
-    /// padding
-    pub padding2: [u64; 2],
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
-pub struct StateData {
-    /// delay to take pnl coin
-    pub need_take_pnl_coin: u64,
-    /// delay to take pnl pc
-    pub need_take_pnl_pc: u64,
-    /// total pnl pc
-    pub total_pnl_pc: u64,
-    /// total pnl coin
-    pub total_pnl_coin: u64,
-    /// ido pool open time
-    pub pool_open_time: u64,
-    /// padding for future updates
-    pub padding: [u64; 2],
-    /// switch from orderbookonly to init
-    pub orderbook_to_init_time: u64,
-
-    /// swap coin in amount
-    pub swap_coin_in_amount: u128,
-    /// swap pc out amount
-    pub swap_pc_out_amount: u128,
-    /// charge pc as swap fee while swap pc to coin
-    pub swap_acc_pc_fee: u64,
-
-    /// swap pc in amount
-    pub swap_pc_in_amount: u128,
-    /// swap coin out amount
-    pub swap_coin_out_amount: u128,
-    /// charge coin as swap fee while swap coin to pc
-    pub swap_acc_coin_fee: u64,
-}
-
-#[repr(C)]
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
-pub struct Fees {
-    /// numerator of the min_separate
-    pub min_separate_numerator: u64,
-    /// denominator of the min_separate
-    pub min_separate_denominator: u64,
-
-    /// numerator of the fee
-    pub trade_fee_numerator: u64,
-    /// denominator of the fee
-    /// and 'trade_fee_denominator' must be equal to 'min_separate_denominator'
-    pub trade_fee_denominator: u64,
-
-    /// numerator of the pnl
-    pub pnl_numerator: u64,
-    /// denominator of the pnl
-    pub pnl_denominator: u64,
-
-    /// numerator of the swap_fee
-    pub swap_fee_numerator: u64,
-    /// denominator of the swap_fee
-    pub swap_fee_denominator: u64,
-}
\ No newline at end of file
@@ -1,5 +1,6 @@
 pub mod decoder;
 pub mod error;
-// pub mod instruction;
+#[allow(clippy::module_inception)]
+pub mod instruction;
 // pub mod pool_ixs;
 // pub mod swap_ixs;
\ No newline at end of file
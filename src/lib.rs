@@ -6,6 +6,12 @@ pub mod common;
  // Add the config module
 pub mod dex;
 pub mod dex_processor;  // Note the change to pub
+pub mod instruction;    // LIQUIDITY_STATE_LAYOUT_V4 + decode()
+pub mod metrics;
+pub mod server;
+pub mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 // Option to re-export frequently used module items
-pub use dex_processor::raydium_lp_v4;
\ No newline at end of file
+pub use dex_processor::{Handler, Pipeline};
\ No newline at end of file
@@ -1,13 +1,9 @@
 // src/main.rs
 use clap::Parser;
-use futures::{sink::SinkExt, stream::StreamExt};
-use log::info;
-use std::{collections::HashMap, env, str::FromStr};
-use tokio::time::{interval, Duration};
-use tonic::transport::channel::ClientTlsConfig;
+use log::{info, warn};
+use std::{collections::HashMap, env, io::{BufRead, Read}, mem::offset_of, net::SocketAddr, str::FromStr};
 
 use solana_sdk::pubkey::Pubkey;
-use yellowstone_grpc_client::GeyserGrpcClient;
 use yellowstone_grpc_proto::{
     geyser::{
         subscribe_request_filter_accounts_filter::Filter as SubscribeFilterKind,
@@ -15,15 +11,26 @@ use yellowstone_grpc_proto::{
         SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
     },
     prelude::{
-        subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
-        SubscribeRequestFilterAccounts, SubscribeRequestPing,
+        CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots,
+        SubscribeRequestFilterTransactions,
     },
 };
 
-mod common;
-mod dex;
-mod dex_processor; // your existing parsers/printers
-mod instruction; // contains LIQUIDITY_STATE_LAYOUT_V4 + decode()
+use mevbot_ws_rust::dex_processor::inspect::{inspect, render_table, AccountInput, InputEncoding};
+use mevbot_ws_rust::dex_processor::upgrades::program_data_accounts;
+use mevbot_ws_rust::dex_processor::{
+    AccountDecoder, CommitmentTracker, JsonLinesSink, LoggingHandler, NewPoolFilter, Pipeline,
+    ProcessOutcome, UpgradeGate, UpgradeMonitor, VerifyPdas, WatchPoolVaults, PROGRAM_DATA_FILTER,
+};
+use mevbot_ws_rust::instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4;
+use mevbot_ws_rust::metrics::{prometheus, LatencyMetrics, Metrics};
+use mevbot_ws_rust::server::{grpc, BotEvent, EventBus, EventPublisher, EventServer};
+use mevbot_ws_rust::stream::{
+    FilterSet, FilteredSource, GrpcSource, HealthConfig, HealthSnapshot, HealthStatus, MultiplexStats,
+    Multiplexer, ReconnectingSource, Recorder, Replay, ReplaySource, ReplaySpeed, SourceUpdate,
+    SubscriptionController, UpdateSource, WebSocketSource,
+};
+use tokio::sync::watch;
 
 // ─────────── constants ───────────────────────────────
 const RAYDIUM_PROGRAM_V4: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
@@ -31,26 +38,99 @@ const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
 const OPENBOOK_PROGRAM: &str = "srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX";
 
 // ─── byte offsets inside LIQUIDITY_STATE_LAYOUT_V4 ───────────
-use instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4;
-use memoffset::offset_of;
-
-const OFFSET_QUOTE_MINT:     u64 = 432;  // TS: quoteMint@432
-const OFFSET_MARKET_PROGRAM: u64 = 560;  // TS: marketProg@560
-const OFFSET_SWAP_QUOTE_IN:  u64 = 664;  // TS: swapQ@664
-const OFFSET_SWAP_BASE_OUT:  u64 = 688;  // TS: swapB@688
+const OFFSET_QUOTE_MINT:     u64 = offset_of!(LIQUIDITY_STATE_LAYOUT_V4, quoteMint) as u64;         // 432
+const OFFSET_MARKET_PROGRAM: u64 = offset_of!(LIQUIDITY_STATE_LAYOUT_V4, marketProgramId) as u64;   // 560
+const OFFSET_SWAP_QUOTE_IN:  u64 = offset_of!(LIQUIDITY_STATE_LAYOUT_V4, swapQuoteInAmount) as u64; // 296
+const OFFSET_SWAP_BASE_OUT:  u64 = offset_of!(LIQUIDITY_STATE_LAYOUT_V4, swapBaseOutAmount) as u64; // 312
 
 // ───────── CLI args ──────────────────────────────────────
 #[derive(Debug, Clone, Parser)]
 #[clap(author, version, about)]
 struct Args {
-    /// gRPC endpoint
+    /// gRPC endpoint, repeat to fan in several providers (first arrival wins)
     #[clap(short, long,
-           default_value_t = String::from("https://solana-yellowstone-grpc.publicnode.com"))]
-    endpoint: String,
+           default_value = "https://solana-yellowstone-grpc.publicnode.com")]
+    endpoint: Vec<String>,
+
+    /// optional x-token header, repeat in the same order as --endpoint (a single one applies to all)
+    #[clap(long)]
+    x_token: Vec<String>,
+
+    /// Solana JSON-RPC WebSocket URL, used when no gRPC endpoint can be reached
+    #[clap(long)]
+    ws_url: Option<String>,
+
+    /// write decoded accounts and events as JSON lines to this file ("-" for stdout)
+    #[clap(long)]
+    json_out: Option<String>,
+
+    /// record every raw update to this file
+    #[clap(long)]
+    record: Option<String>,
+
+    /// zstd-compress the recording
+    #[clap(long, requires = "record")]
+    record_compress: bool,
+
+    /// feed the pipeline from a recording instead of the gRPC endpoint
+    #[clap(long, conflicts_with = "record")]
+    replay: Option<String>,
+
+    /// replay pacing: original, max, step (Enter advances) or a factor like 10x
+    #[clap(long, default_value = "original", requires = "replay")]
+    replay_speed: ReplaySpeed,
+
+    /// serve Prometheus metrics on http://<addr>/metrics, e.g. 127.0.0.1:9464
+    #[clap(long)]
+    metrics_addr: Option<SocketAddr>,
 
-    /// optional x-token header
+    /// serve detected pools and events to WebSocket clients on ws://<addr>, e.g. 127.0.0.1:9465
     #[clap(long)]
-    x_token: Option<String>,
+    serve_addr: Option<SocketAddr>,
+
+    /// serve the same events as typed gRPC (mevbot.events.v1.PoolEvents) on <addr>, e.g. 127.0.0.1:9466
+    #[clap(long)]
+    grpc_addr: Option<SocketAddr>,
+
+    /// publish swaps touching this mint on the `swaps` topic, repeatable
+    #[clap(long)]
+    watch_mint: Vec<Pubkey>,
+
+    /// stop processing a DEX whose program got upgraded until confirmed with SIGUSR1
+    #[clap(long)]
+    pause_on_upgrade: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+enum Command {
+    /// decode one account offline instead of streaming
+    Decode(DecodeArgs),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+struct DecodeArgs {
+    /// file with the account (raw bytes, base64, base58 or a `solana account --output json` dump),
+    /// "-" for stdin, or the base64/base58 data itself
+    input: String,
+
+    /// how the input is encoded
+    #[clap(long, value_enum, default_value = "auto")]
+    encoding: InputEncoding,
+
+    /// decode as this layout instead of detecting it from owner and size
+    #[clap(long)]
+    layout: Option<String>,
+
+    /// owning program, used for detection when the input does not carry it
+    #[clap(long)]
+    owner: Option<Pubkey>,
+
+    /// print the decoded layout as JSON instead of a table
+    #[clap(long)]
+    json: bool,
 }
 
 #[tokio::main]
@@ -64,17 +144,75 @@ async fn main() -> anyhow::Result<()> {
     info!("bot");
 
     let args = Args::parse();
+    if let Some(Command::Decode(decode)) = &args.command {
+        return decode_account(decode);
+    }
+
+    // runtime additions (new pools' vaults, ...) are merged into the live request
+    let subscription = SubscriptionController::new(new_pool_request(&args.watch_mint)?);
+
+    let metrics = Metrics::new();
+    report_latency(metrics.latency.clone());
+    if let Some(addr) = args.metrics_addr {
+        prometheus::serve(addr, metrics.clone()).await?;
+    }
+
+    let events = EventBus::default();
+    let _event_server = match args.serve_addr {
+        Some(addr) => Some(EventServer::start(addr, events.clone()).await?),
+        None => None,
+    };
+    if let Some(addr) = args.grpc_addr {
+        grpc::serve(addr, events.clone()).await?;
+    }
+
+    /* ───── processing pipeline ───── */
+    let mut upgrades = UpgradeMonitor::new();
+    if args.pause_on_upgrade {
+        let gate = UpgradeGate::new();
+        confirm_upgrades_on_signal(gate.clone())?;
+        upgrades = upgrades.with_gate(gate);
+    }
+    // the upgrade monitor goes first so paused DEXes are never decoded
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(upgrades)
+        .with_handler(AccountDecoder::default())
+        .with_handler(VerifyPdas::default())
+        .with_handler(NewPoolFilter)
+        .with_handler(LoggingHandler)
+        .with_handler(WatchPoolVaults::new(subscription.clone()))
+        .with_handler(EventPublisher::new(events.clone()));
+    match args.json_out.as_deref() {
+        Some("-") => pipeline.add_handler(Box::new(JsonLinesSink::stdout())),
+        Some(path) => pipeline.add_handler(Box::new(JsonLinesSink::create(path)?)),
+        None => {}
+    }
+
+    let mut source = open_source(&args, &subscription, &metrics).await?;
+    info!("reading updates from {}", source.name());
+
+    let mut commitment = CommitmentTracker::new();
+    loop {
+        tokio::select! {
+            update = source.next_update() => match update? {
+                Some(update) => process_update(&mut pipeline, &mut commitment, &events, update),
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => {
+                info!("interrupted, shutting down");
+                break;
+            }
+        }
+    }
+    // dropping the source closes the recorder, which finishes the zstd frame
+    drop(source);
 
-    /* ───── gRPC connection ───── */
-    let mut client = GeyserGrpcClient::build_from_shared(args.endpoint)?
-        .x_token(args.x_token)?
-        .tls_config(ClientTlsConfig::new().with_native_roots())?
-        .connect()
-        .await?;
-    let (mut tx, mut stream) = client.subscribe().await?;
+    Ok(())
+}
 
+fn new_pool_request(watch_mints: &[Pubkey]) -> anyhow::Result<SubscribeRequest> {
     /* ───── build 4 filters ───── */
-    let zero = vec![0u8];
     let f_quote = memcmp_filter(OFFSET_QUOTE_MINT, Pubkey::from_str(WSOL_MINT)?);
     let f_market = memcmp_filter(OFFSET_MARKET_PROGRAM, Pubkey::from_str(OPENBOOK_PROGRAM)?);
     let f_swap_q = byte_zero_filter(OFFSET_SWAP_QUOTE_IN);
@@ -95,79 +233,295 @@ async fn main() -> anyhow::Result<()> {
         },
     );
 
-    futures::try_join!(
-        /* TX task */
-        async move {
-            tx.send(SubscribeRequest {
-                accounts,
-                commitment: Some(CommitmentLevel::Processed as i32),
-                ..Default::default()
-            })
-            .await?;
-
-            let mut ticker = interval(Duration::from_secs(3));
-            let mut id: i32 = 0;
-            loop {
-                ticker.tick().await;
-                id += 1;
-                tx.send(SubscribeRequest {
-                    ping: Some(SubscribeRequestPing { id }),
-                    ..Default::default()
-                })
-                .await?;
-            }
-            #[allow(unreachable_code)]
-            Ok::<(), anyhow::Error>(())
+    // a deployment on any DEX we decode may move the hardcoded layout offsets
+    accounts.insert(
+        PROGRAM_DATA_FILTER.into(),
+        SubscribeRequestFilterAccounts {
+            account: program_data_accounts().iter().map(|(_, address)| address.to_string()).collect(),
+            ..Default::default()
         },
-        async move {
-            while let Some(msg) = stream.next().await {
-                let msg = msg?; // SubscribeUpdate
-                // log every incoming account message
-                if let Some(UpdateOneof::Account(acc)) = &msg.update_oneof {
-                    if let Some(acct) = &acc.account {
-                        let key = Pubkey::try_from(&acct.pubkey[..])?.to_string();
-                        let data = &acct.data;
-                        info!("→ got {} bytes for account {}", data.len(), key);
-        
-                        // now attempt decode V4 layout
-                        let mut slice: &[u8] = data;
-                        match LIQUIDITY_STATE_LAYOUT_V4::decode(&mut slice) {
-                            Ok(state) => {
-                                info!(
-                                    "counters → swapQuoteIn={}  swapBaseOut={}",
-                                    state.swapQuoteInAmount,
-                                    state.swapBaseOutAmount,
-                                );
-                                let pass_q = state.swapQuoteInAmount == 0;
-                                let pass_b = state.swapBaseOutAmount == 0;
-                                if pass_q && pass_b {
-                                    info!("NEW LP-V4 POOL: {}", key);
-                                    info!("  baseMint:     {}", state.baseMint);
-                                    info!("  lpMint:       {}", state.lpMint);
-                                    info!("  marketId:     {}", state.marketId);
-                                    info!("  poolOpenTime: {}", state.poolOpenTime);
-                                    dex_processor::raydium_lp_v4(key, acct.data.clone());
-                                } else {
-                                    info!(
-                                        "skipped {}  pass_q={}  pass_b={}",
-                                        key, pass_q, pass_b
-                                    );
-                                }
-                            }
-                            Err(err) => {
-                                info!("⚠ V4 decode failed for {}: {}", key, err);
-                            }
-                        }
-                    }
+    );
+
+    // slot progress tells a stalled stream from a quiet market, and every status change
+    // (not only processed) is needed to confirm or roll back processed writes
+    let mut slots = HashMap::new();
+    slots.insert(
+        "slots".into(),
+        SubscribeRequestFilterSlots {
+            filter_by_commitment: Some(false),
+            ..Default::default()
+        },
+    );
+
+    // swaps are only streamed for the mints someone asked for
+    let mut transactions = HashMap::new();
+    if !watch_mints.is_empty() {
+        transactions.insert(
+            "watched_mints".into(),
+            SubscribeRequestFilterTransactions {
+                vote: Some(false),
+                failed: Some(false),
+                account_include: watch_mints.iter().map(Pubkey::to_string).collect(),
+                ..Default::default()
+            },
+        );
+    }
+
+    Ok(SubscribeRequest {
+        accounts,
+        slots,
+        transactions,
+        commitment: Some(CommitmentLevel::Processed as i32),
+        ..Default::default()
+    })
+}
+
+fn decode_account(args: &DecodeArgs) -> anyhow::Result<()> {
+    let raw = match args.input.as_str() {
+        "-" => {
+            let mut raw = Vec::new();
+            std::io::stdin().read_to_end(&mut raw)?;
+            raw
+        }
+        path if std::path::Path::new(path).is_file() => std::fs::read(path)?,
+        data => data.as_bytes().to_vec(),
+    };
+    let mut input = AccountInput::parse(&raw, args.encoding)?;
+    input.owner = args.owner.or(input.owner);
+
+    let inspection = inspect(&input, args.layout.as_deref())?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&inspection.decoded)?);
+        return Ok(());
+    }
+    if let Some(pubkey) = input.pubkey {
+        println!("account  {}", pubkey);
+    }
+    if let Some(owner) = input.owner {
+        println!("owner    {}", owner);
+    }
+    println!("size     {} bytes", input.data.len());
+    if !inspection.alternatives.is_empty() {
+        println!("guessed from size, also possible: {}", inspection.alternatives.join(", "));
+    }
+    if let Some(drift) = &inspection.drift {
+        println!("warning  {}", drift);
+    }
+    println!();
+    print!("{}", render_table(&inspection.decoded)?);
+    Ok(())
+}
+
+/// Replay file, else gRPC, else the WebSocket fallback
+async fn open_source(
+    args: &Args,
+    subscription: &SubscriptionController,
+    metrics: &Metrics,
+) -> anyhow::Result<Box<dyn UpdateSource>> {
+    if let Some(path) = args.replay.as_deref() {
+        let replay = Replay::open(path, args.replay_speed)?;
+        info!("replaying {} at {:?}", path, args.replay_speed);
+        if args.replay_speed == ReplaySpeed::Stepwise {
+            let stepper = replay.stepper();
+            stepper.step();
+            info!("press Enter to release the next update");
+            tokio::task::spawn_blocking(move || {
+                for _ in std::io::stdin().lock().lines() {
+                    stepper.step();
+                }
+            });
+        }
+        // recordings may come from a wider subscription, show only what the live request would see
+        let filters = FilterSet::try_from(&new_pool_request(&args.watch_mint)?)?;
+        return Ok(Box::new(FilteredSource::new(ReplaySource::new(replay), filters)));
+    }
+
+    let request = subscription.request();
+    let x_token = |i: usize| match args.x_token.as_slice() {
+        [token] => Some(token.clone()),
+        tokens => tokens.get(i).cloned(),
+    };
+    let connects = args
+        .endpoint
+        .iter()
+        .enumerate()
+        .map(|(i, endpoint)| GrpcSource::connect(endpoint.clone(), x_token(i), request.clone()));
+    let mut sources = Vec::new();
+    let mut last_err = None;
+    for (i, result) in futures::future::join_all(connects).await.into_iter().enumerate() {
+        let endpoint = &args.endpoint[i];
+        match result {
+            Ok(mut source) => {
+                // with several endpoints only the first one is recorded, duplicates would bloat the file
+                if let (true, Some(path)) = (sources.is_empty(), args.record.as_deref()) {
+                    info!("recording {} to {} (compressed: {})", endpoint, path, args.record_compress);
+                    source = source.with_recorder(Recorder::create(path, args.record_compress)?);
                 }
+                let source = ReconnectingSource::new(endpoint.clone(), x_token(i), request.clone(), HealthConfig::default())
+                    .with_subscription(subscription.subscribe())
+                    .with_metrics(metrics.clone())
+                    .with_source(source);
+                metrics.register_health(endpoint, source.health());
+                report_health(endpoint.clone(), source.health());
+                sources.push(source);
+            }
+            Err(err) => {
+                warn!("gRPC endpoint {} unavailable: {:#}", endpoint, err);
+                last_err = Some(err);
             }
-            Ok::<(), anyhow::Error>(())
         }
-    )?;
+    }
 
+    if !sources.is_empty() {
+        if sources.len() == 1 {
+            return Ok(Box::new(sources.remove(0)));
+        }
+        let multiplexer = Multiplexer::new(sources.into_iter().map(|s| Box::new(s) as Box<dyn UpdateSource>).collect());
+        report_endpoint_stats(multiplexer.stats());
+        return Ok(Box::new(multiplexer));
+    }
+
+    let Some(ws_url) = args.ws_url.as_deref() else {
+        return Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no gRPC endpoint configured")));
+    };
+    warn!("falling back to {}", ws_url);
+    if args.record.is_some() {
+        warn!("recording only covers gRPC streams, nothing will be recorded");
+    }
+    // PubSub subscriptions are fixed at connect time, accounts watched later are not streamed
+    warn!("watching accounts at runtime needs a gRPC endpoint");
+    Ok(Box::new(WebSocketSource::connect(ws_url, &request).await?))
+}
+
+/// `kill -USR1 <pid>` confirms every pending program upgrade and resumes the paused DEXes
+#[cfg(unix)]
+fn confirm_upgrades_on_signal(gate: UpgradeGate) -> anyhow::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut confirmations = signal(SignalKind::user_defined1())?;
+    info!("DEXes pause on program upgrades, confirm with `kill -USR1 {}`", std::process::id());
+    tokio::spawn(async move {
+        while confirmations.recv().await.is_some() {
+            let resumed = gate.confirm_all();
+            if resumed.is_empty() {
+                info!("SIGUSR1: no upgrade waiting for confirmation");
+            }
+            for dex in resumed {
+                warn!("upgrade of {} confirmed, resuming", dex.tag());
+            }
+        }
+    });
     Ok(())
 }
 
+#[cfg(not(unix))]
+fn confirm_upgrades_on_signal(_gate: UpgradeGate) -> anyhow::Result<()> {
+    anyhow::bail!("--pause-on-upgrade needs SIGUSR1 to confirm upgrades, which this platform lacks")
+}
+
+fn report_endpoint_stats(stats: MultiplexStats) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            for endpoint in stats.snapshot() {
+                info!(
+                    "endpoint {}  connected: {}  received: {}  win rate: {:.1}%  mean lag: {:?}  max lag: {:?}",
+                    endpoint.name,
+                    endpoint.connected,
+                    endpoint.received,
+                    endpoint.win_rate() * 100.0,
+                    endpoint.mean_lag(),
+                    endpoint.max_lag,
+                );
+            }
+        }
+    });
+}
+
+fn report_latency(latency: LatencyMetrics) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            for series in latency.snapshot() {
+                info!("latency {}", series);
+            }
+        }
+    });
+}
+
+fn report_health(endpoint: String, mut health: watch::Receiver<HealthSnapshot>) {
+    tokio::spawn(async move {
+        while health.changed().await.is_ok() {
+            let snapshot = health.borrow_and_update().clone();
+            let message = format!(
+                "{} {}  pong rtt: {:?}  last update: {:?} ago  slot: {:?}  drift: {:?}  reconnects: {}",
+                endpoint,
+                snapshot.status,
+                snapshot.pong_rtt,
+                snapshot.since_last_update,
+                snapshot.slot,
+                snapshot.slot_drift,
+                snapshot.reconnects,
+            );
+            match snapshot.status {
+                HealthStatus::Connecting | HealthStatus::Healthy => info!("{}", message),
+                HealthStatus::Degraded(_) | HealthStatus::Stalled(_) => warn!("{}", message),
+            }
+        }
+    });
+}
+
+/// Runs one normalized update through the pipeline, shared by every source
+fn process_update(
+    pipeline: &mut Pipeline,
+    commitment: &mut CommitmentTracker,
+    events: &EventBus,
+    update: SourceUpdate,
+) {
+    // log every incoming account message
+    let update = match update {
+        SourceUpdate::Account(update) => update,
+        SourceUpdate::Slot(slot) => {
+            for retraction in commitment.on_slot(&slot) {
+                if let Err(err) = pipeline.retract(&retraction) {
+                    warn!("⚠ retraction failed for {}: {:#}", retraction.update.pubkey, err);
+                }
+            }
+            return;
+        }
+        SourceUpdate::Transaction(tx) => {
+            if let Some(swap) = BotEvent::swap(&tx) {
+                events.publish(swap);
+            }
+            return;
+        }
+    };
+    if !commitment.on_account(&update) {
+        info!("dropping {} from dead slot {}", update.pubkey, update.slot);
+        return;
+    }
+    let key = update.pubkey;
+    info!("→ got {} bytes for account {}", update.data.len(), key);
+
+    match pipeline.process(update) {
+        Ok(ProcessOutcome::Completed(account)) => {
+            for event in &account.events {
+                info!("pipeline event: {:?}", event);
+            }
+        }
+        Ok(ProcessOutcome::Skipped { handler, reason, .. }) => {
+            info!("skipped {}  [{}] {}", key, handler, reason);
+        }
+        Err(err) => {
+            info!("⚠ processing failed for {}: {:#}", key, err);
+        }
+    }
+}
+
 // ─── helper constructors ───────────────────────────────────
 fn memcmp_filter(offset: u64, key: Pubkey) -> SubscribeRequestFilterAccountsFilter {
     // pubkeys → BASE-58 (same as JS)
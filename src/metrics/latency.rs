@@ -0,0 +1,233 @@
+//! Per-update stage timestamps and the histograms they are aggregated into.
+//!
+//! Network latency (server `created_at` → receipt) is kept per source, so providers can be
+//! compared. Processing stages (receipt → decode → decision → action → send) are kept per DEX.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::Serialize;
+
+use crate::dex::Dex;
+use crate::dex_processor::Stage;
+
+/// When an update reached each stage. Wall-clock times are only used against the server's
+/// `created_at`, everything local is measured on the monotonic clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// Set by the Geyser plugin when it built the message
+    pub created_at: Option<SystemTime>,
+    pub received_at: Option<SystemTime>,
+    pub received: Option<Instant>,
+    pub decoded: Option<Instant>,
+    /// Filter stage done, the update was accepted or skipped
+    pub decided: Option<Instant>,
+    pub acted: Option<Instant>,
+    /// Set by handlers that build or send a transaction
+    pub sent: Option<Instant>,
+}
+
+impl Timings {
+    pub fn receive(&mut self) {
+        self.received_at = Some(SystemTime::now());
+        self.received = Some(Instant::now());
+    }
+
+    /// Stamps the end of a pipeline stage
+    pub fn stage_done(&mut self, stage: Stage, at: Instant) {
+        match stage {
+            Stage::Decode => self.decoded = Some(at),
+            Stage::Enrich => {}
+            Stage::Filter => self.decided = Some(at),
+            Stage::Act => self.acted = Some(at),
+        }
+    }
+
+    /// Server creation to receipt, zero when the clocks disagree the other way
+    pub fn network(&self) -> Option<Duration> {
+        let (created, received) = (self.created_at?, self.received_at?);
+        Some(received.duration_since(created).unwrap_or_default())
+    }
+
+    fn since_received(&self, at: Option<Instant>) -> Option<Duration> {
+        Some(at?.saturating_duration_since(self.received?))
+    }
+
+    /// Creation to the last stage reached
+    pub fn total(&self) -> Option<Duration> {
+        let last = self.sent.or(self.acted).or(self.decided).or(self.decoded);
+        Some(self.network()? + self.since_received(last)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scope {
+    Source,
+    Dex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyStage {
+    /// `created_at` → receipt
+    Network,
+    /// receipt → decode stage done
+    Decode,
+    /// receipt → filter stage done
+    Decide,
+    /// receipt → act stage done
+    Act,
+    /// receipt → transaction sent
+    Send,
+    /// `created_at` → last stage reached
+    Total,
+}
+
+impl LatencyStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Decode => "decode",
+            Self::Decide => "decide",
+            Self::Act => "act",
+            Self::Send => "send",
+            Self::Total => "total",
+        }
+    }
+}
+
+/// Bucket upper bounds in microseconds, 50 µs to 10 s
+pub const BUCKET_BOUNDS_US: [u64; 17] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+    2_500_000, 5_000_000, 10_000_000,
+];
+
+/// Fixed-bucket latency histogram. Quantiles are resolved to the bucket's upper bound.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Histogram {
+    /// Per bucket (not cumulative), the last one counts everything above the largest bound
+    pub buckets: [u64; BUCKET_BOUNDS_US.len() + 1],
+    pub count: u64,
+    pub sum_us: u64,
+    pub min_us: u64,
+    pub max_us: u64,
+}
+
+impl Histogram {
+    pub fn record(&mut self, value: Duration) {
+        let us = value.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = BUCKET_BOUNDS_US.partition_point(|bound| *bound < us);
+        self.buckets[bucket] += 1;
+        self.min_us = if self.count == 0 { us } else { self.min_us.min(us) };
+        self.max_us = self.max_us.max(us);
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => Duration::from_micros(self.sum_us / count),
+        }
+    }
+
+    /// Upper bound of the bucket holding quantile `q` (0.0..=1.0), capped at the maximum seen
+    pub fn quantile(&self, q: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = BUCKET_BOUNDS_US.get(bucket).copied().unwrap_or(self.max_us);
+                return Duration::from_micros(bound.min(self.max_us));
+            }
+        }
+        Duration::from_micros(self.max_us)
+    }
+}
+
+/// One exported histogram
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySeries {
+    pub scope: Scope,
+    /// Source name or DEX tag
+    pub label: String,
+    pub stage: LatencyStage,
+    pub histogram: Histogram,
+}
+
+impl fmt::Display for LatencySeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let h = &self.histogram;
+        write!(
+            f,
+            "{:?} {} {}  n={}  mean={:?}  p50={:?}  p90={:?}  p99={:?}  max={:?}",
+            self.scope,
+            self.label,
+            self.stage.as_str(),
+            h.count,
+            h.mean(),
+            h.quantile(0.5),
+            h.quantile(0.9),
+            h.quantile(0.99),
+            Duration::from_micros(h.max_us),
+        )
+    }
+}
+
+type SeriesKey = (Scope, String, LatencyStage);
+
+/// Cloneable collector shared by the sources and the pipeline
+#[derive(Clone, Default)]
+pub struct LatencyMetrics(Arc<Mutex<BTreeMap<SeriesKey, Histogram>>>);
+
+impl LatencyMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, scope: Scope, label: &str, stage: LatencyStage, value: Option<Duration>) {
+        if let Some(value) = value {
+            let mut series = self.0.lock().unwrap();
+            series.entry((scope, label.to_string(), stage)).or_default().record(value);
+        }
+    }
+
+    /// Network latency of an update as delivered by `source`
+    pub fn record_receipt(&self, source: &str, timings: &Timings) {
+        self.record(Scope::Source, source, LatencyStage::Network, timings.network());
+    }
+
+    /// Processing stages of an update that went through the pipeline
+    pub fn record_processed(&self, dex: Option<Dex>, timings: &Timings) {
+        let label = dex.map_or("unknown", |dex| dex.tag());
+        self.record(Scope::Dex, label, LatencyStage::Decode, timings.since_received(timings.decoded));
+        self.record(Scope::Dex, label, LatencyStage::Decide, timings.since_received(timings.decided));
+        self.record(Scope::Dex, label, LatencyStage::Act, timings.since_received(timings.acted));
+        self.record(Scope::Dex, label, LatencyStage::Send, timings.since_received(timings.sent));
+        self.record(Scope::Dex, label, LatencyStage::Total, timings.total());
+    }
+
+    /// Every series recorded so far, ordered by scope, label and stage
+    pub fn snapshot(&self) -> Vec<LatencySeries> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|((scope, label, stage), histogram)| LatencySeries {
+                scope: *scope,
+                label: label.clone(),
+                stage: *stage,
+                histogram: histogram.clone(),
+            })
+            .collect()
+    }
+}
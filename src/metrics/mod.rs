@@ -0,0 +1,8 @@
+//! Runtime measurements: where the time between an update's creation and our reaction goes.
+
+pub mod latency;
+pub mod prometheus;
+pub mod registry;
+
+pub use latency::{Histogram, LatencyMetrics, LatencySeries, LatencyStage, Scope, Timings};
+pub use registry::{Metrics, TxOutcome};
@@ -0,0 +1,132 @@
+//! Prometheus text exposition of [`Metrics`] on a local `/metrics` endpoint.
+
+use std::{fmt::Write, net::SocketAddr};
+
+use axum::{http::header, routing::get, Router};
+use log::info;
+use tokio::{net::TcpListener, task::JoinHandle};
+
+use super::{latency::BUCKET_BOUNDS_US, Metrics, Scope};
+
+/// (name, help, label names) of every counter family
+const COUNTERS: [(&str, &str, &[&str]); 5] = [
+    ("updates_received_total", "Updates received, per source and matched subscription filter", &["source", "filter"]),
+    (
+        "decode_failures_total",
+        "Accounts a layout claimed but rejected, per owner program, layout and reason (size, discriminator, malformed, drift)",
+        &["dex", "layout", "kind"],
+    ),
+    ("new_pools_total", "New pools detected", &["dex"]),
+    ("program_upgrades_total", "Deployments seen on the DEX programs, layouts may have changed", &["dex"]),
+    ("transactions_total", "Transactions by outcome (built, sent, confirmed, failed)", &["dex", "outcome"]),
+];
+
+const PREFIX: &str = "mevbot_";
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn labels(names: &[&str], values: &[String]) -> String {
+    let pairs: Vec<_> = names
+        .iter()
+        .zip(values)
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape(value)))
+        .collect();
+    pairs.join(",")
+}
+
+/// Renders every metric in the Prometheus text format
+pub fn render(metrics: &Metrics) -> String {
+    let mut out = String::new();
+    let registry = metrics.registry.lock().unwrap();
+
+    for (family, help, names) in COUNTERS {
+        let _ = writeln!(out, "# HELP {PREFIX}{family} {help}\n# TYPE {PREFIX}{family} counter");
+        for ((_, values), count) in registry.counters.iter().filter(|((name, _), _)| *name == family) {
+            let _ = writeln!(out, "{PREFIX}{family}{{{}}} {count}", labels(names, values));
+        }
+    }
+
+    let health: Vec<_> = registry
+        .health
+        .iter()
+        .map(|(endpoint, health)| (endpoint.clone(), health.borrow().clone()))
+        .collect();
+    drop(registry);
+    let _ = writeln!(
+        out,
+        "# HELP {PREFIX}stream_reconnects_total Stream reconnects\n# TYPE {PREFIX}stream_reconnects_total counter"
+    );
+    for (endpoint, snapshot) in &health {
+        let _ = writeln!(out, "{PREFIX}stream_reconnects_total{{endpoint=\"{}\"}} {}", escape(endpoint), snapshot.reconnects);
+    }
+    let _ = writeln!(
+        out,
+        "# HELP {PREFIX}pong_rtt_seconds Latest ping/pong round-trip\n# TYPE {PREFIX}pong_rtt_seconds gauge"
+    );
+    for (endpoint, snapshot) in &health {
+        if let Some(rtt) = snapshot.pong_rtt {
+            let _ = writeln!(out, "{PREFIX}pong_rtt_seconds{{endpoint=\"{}\"}} {}", escape(endpoint), rtt.as_secs_f64());
+        }
+    }
+    let _ = writeln!(
+        out,
+        "# HELP {PREFIX}stream_status 0 connecting, 1 healthy, 2 degraded, 3 stalled\n# TYPE {PREFIX}stream_status gauge"
+    );
+    for (endpoint, snapshot) in &health {
+        let _ = writeln!(
+            out,
+            "{PREFIX}stream_status{{endpoint=\"{}\"}} {}",
+            escape(endpoint),
+            snapshot.status.code()
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP {PREFIX}latency_seconds Update latency per stage, by source (network) or DEX (processing)\n# TYPE {PREFIX}latency_seconds histogram"
+    );
+    for series in metrics.latency.snapshot() {
+        let base = format!(
+            "scope=\"{}\",label=\"{}\",stage=\"{}\"",
+            match series.scope {
+                Scope::Source => "source",
+                Scope::Dex => "dex",
+            },
+            escape(&series.label),
+            series.stage.as_str()
+        );
+        let h = &series.histogram;
+        let mut cumulative = 0;
+        for (bound, count) in BUCKET_BOUNDS_US.iter().zip(&h.buckets) {
+            cumulative += count;
+            let le = *bound as f64 / 1e6;
+            let _ = writeln!(out, "{PREFIX}latency_seconds_bucket{{{base},le=\"{le}\"}} {cumulative}");
+        }
+        let _ = writeln!(out, "{PREFIX}latency_seconds_bucket{{{base},le=\"+Inf\"}} {}", h.count);
+        let _ = writeln!(out, "{PREFIX}latency_seconds_sum{{{base}}} {}", h.sum_us as f64 / 1e6);
+        let _ = writeln!(out, "{PREFIX}latency_seconds_count{{{base}}} {}", h.count);
+    }
+    out
+}
+
+/// Serves `GET /metrics` on `addr` (port 0 picks one), returns the bound address
+pub async fn serve(addr: SocketAddr, metrics: Metrics) -> anyhow::Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(addr).await?;
+    let addr = listener.local_addr()?;
+    let app = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], render(&metrics)) }
+        }),
+    );
+    info!("serving metrics on http://{}/metrics", addr);
+    let task = tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, app).await {
+            log::warn!("metrics server stopped: {}", err);
+        }
+    });
+    Ok((addr, task))
+}
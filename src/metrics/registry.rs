@@ -0,0 +1,108 @@
+//! Counters and gauges shared by the sources, the pipeline and the exporter.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::watch;
+
+use crate::dex::Dex;
+use crate::dex_processor::DecodeError;
+use crate::stream::HealthSnapshot;
+
+use super::LatencyMetrics;
+
+/// Fate of a transaction built by a handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TxOutcome {
+    Built,
+    Sent,
+    Confirmed,
+    Failed,
+}
+
+impl TxOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Built => "built",
+            Self::Sent => "sent",
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// Counter family name and its label values, in the order of the family's label names
+pub(crate) type CounterKey = (&'static str, Vec<String>);
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    pub(crate) counters: BTreeMap<CounterKey, u64>,
+    pub(crate) health: Vec<(String, watch::Receiver<HealthSnapshot>)>,
+}
+
+/// Cloneable metrics handle. Everything is in memory, [`super::prometheus`] renders it.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    pub latency: LatencyMetrics,
+    pub(crate) registry: Arc<Mutex<Registry>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn inc(&self, family: &'static str, labels: Vec<String>) {
+        *self.registry.lock().unwrap().counters.entry((family, labels)).or_default() += 1;
+    }
+
+    /// One update from `source`, counted once for every named filter it matched
+    pub fn record_update(&self, source: &str, filters: &[String]) {
+        for filter in filters {
+            self.inc("updates_received_total", vec![source.to_string(), filter.clone()]);
+        }
+    }
+
+    /// Account of `dex` (`None` for unknown owners) that a layout claimed but rejected
+    pub fn record_decode_failure(&self, dex: Option<Dex>, error: &DecodeError) {
+        let layout = error.layout().unwrap_or("unknown");
+        self.inc(
+            "decode_failures_total",
+            vec![dex_label(dex).to_string(), layout.to_string(), error.kind().to_string()],
+        );
+    }
+
+    pub fn record_new_pool(&self, dex: Dex) {
+        self.inc("new_pools_total", vec![dex.tag().to_string()]);
+    }
+
+    /// Deployment seen on the program of `dex`
+    pub fn record_program_upgrade(&self, dex: Dex) {
+        self.inc("program_upgrades_total", vec![dex.tag().to_string()]);
+    }
+
+    pub fn record_transaction(&self, dex: Option<Dex>, outcome: TxOutcome) {
+        self.inc("transactions_total", vec![dex_label(dex).to_string(), outcome.as_str().to_string()]);
+    }
+
+    /// Reconnects, pong round-trip and status of `endpoint` are read from `health` on every scrape
+    pub fn register_health(&self, endpoint: &str, health: watch::Receiver<HealthSnapshot>) {
+        self.registry.lock().unwrap().health.push((endpoint.to_string(), health));
+    }
+
+    /// Current value of a counter, 0 when never incremented
+    pub fn counter(&self, family: &str, labels: &[&str]) -> u64 {
+        let registry = self.registry.lock().unwrap();
+        registry
+            .counters
+            .iter()
+            .find(|((name, values), _)| *name == family && values.iter().map(String::as_str).eq(labels.iter().copied()))
+            .map_or(0, |(_, count)| *count)
+    }
+}
+
+pub(crate) fn dex_label(dex: Option<Dex>) -> &'static str {
+    dex.map_or("unknown", |dex| dex.tag())
+}
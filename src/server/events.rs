@@ -0,0 +1,242 @@
+//! Events published to clients of the embedded servers.
+
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use tokio::sync::broadcast;
+
+use crate::common::serde_helpers::{pubkey_string, pubkey_vec_string};
+use crate::dex::Dex;
+use crate::dex_processor::{Flow, Handler, PipelineEvent, PoolTokens, ProcessedAccount, Stage};
+use crate::metrics::TxOutcome;
+use crate::stream::TransactionUpdate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    NewPools,
+    PoolState,
+    Swaps,
+    Trades,
+}
+
+/// Everything a client can subscribe to, tagged with its topic
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "topic", rename_all = "snake_case")]
+pub enum BotEvent {
+    NewPools {
+        dex: Dex,
+        #[serde(with = "pubkey_string")]
+        pool: Pubkey,
+        slot: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tokens: Option<PoolTokens>,
+    },
+    /// A decoded pool account changed, `state` is the decoded layout as in the JSON-lines output
+    PoolState {
+        dex: Dex,
+        #[serde(with = "pubkey_string")]
+        pool: Pubkey,
+        slot: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tokens: Option<PoolTokens>,
+        state: serde_json::Value,
+    },
+    /// Successful transaction that invoked a DEX program
+    Swaps {
+        dex: Dex,
+        signature: String,
+        slot: u64,
+        #[serde(with = "pubkey_vec_string")]
+        accounts: Vec<Pubkey>,
+    },
+    /// Result of a transaction the bot built
+    Trades {
+        dex: Option<Dex>,
+        signature: Option<String>,
+        outcome: String,
+        slot: Option<u64>,
+    },
+}
+
+impl BotEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Self::NewPools { .. } => Topic::NewPools,
+            Self::PoolState { .. } => Topic::PoolState,
+            Self::Swaps { .. } => Topic::Swaps,
+            Self::Trades { .. } => Topic::Trades,
+        }
+    }
+
+    pub fn trade(dex: Option<Dex>, signature: Option<String>, outcome: TxOutcome, slot: Option<u64>) -> Self {
+        Self::Trades {
+            dex,
+            signature,
+            outcome: outcome.as_str().to_string(),
+            slot,
+        }
+    }
+
+    /// Swap event for a successful transaction that invoked a known DEX program
+    pub fn swap(tx: &TransactionUpdate) -> Option<Self> {
+        if tx.failed || tx.is_vote {
+            return None;
+        }
+        let dex = tx.account_keys.iter().find_map(Dex::from_program_id)?;
+        Some(Self::Swaps {
+            dex,
+            signature: tx.signature.to_string(),
+            slot: tx.slot,
+            accounts: tx.account_keys.clone(),
+        })
+    }
+
+    fn dex(&self) -> Option<Dex> {
+        match self {
+            Self::NewPools { dex, .. } | Self::PoolState { dex, .. } | Self::Swaps { dex, .. } => Some(*dex),
+            Self::Trades { dex, .. } => *dex,
+        }
+    }
+}
+
+/// Optional per-subscription filter, every field that is set must match
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+    #[serde(default)]
+    pub dex: Option<Vec<Dex>>,
+    /// Pool addresses, for `new_pools` and `pool_state`
+    #[serde(default, with = "option_pubkeys")]
+    pub pools: Option<Vec<Pubkey>>,
+    /// Mints (or any account) a swap has to touch
+    #[serde(default, with = "option_pubkeys")]
+    pub mints: Option<Vec<Pubkey>>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &BotEvent) -> bool {
+        if let Some(dexes) = &self.dex {
+            if !event.dex().is_some_and(|dex| dexes.contains(&dex)) {
+                return false;
+            }
+        }
+        if let Some(pools) = &self.pools {
+            match event {
+                BotEvent::NewPools { pool, .. } | BotEvent::PoolState { pool, .. } if pools.contains(pool) => {}
+                _ => return false,
+            }
+        }
+        if let Some(mints) = &self.mints {
+            match event {
+                BotEvent::Swaps { accounts, .. } if accounts.iter().any(|a| mints.contains(a)) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+mod option_pubkeys {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_program::pubkey::Pubkey;
+
+    use crate::common::serde_helpers::pubkey_vec_string;
+
+    #[derive(Serialize, Deserialize)]
+    struct Keys(#[serde(with = "pubkey_vec_string")] Vec<Pubkey>);
+
+    pub fn serialize<S: Serializer>(keys: &Option<Vec<Pubkey>>, serializer: S) -> Result<S::Ok, S::Error> {
+        keys.as_ref().map(|keys| Keys(keys.clone())).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Pubkey>>, D::Error> {
+        Ok(Option::<Keys>::deserialize(deserializer)?.map(|keys| keys.0))
+    }
+}
+
+/// Fan-out of [`BotEvent`]s. Publishing never waits: each client reads from its own position
+/// in a bounded ring, and a client that falls more than the capacity behind loses the oldest
+/// events instead of slowing the ingest path down.
+#[derive(Clone)]
+pub struct EventBus {
+    events: broadcast::Sender<BotEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: broadcast::channel(capacity).0,
+        }
+    }
+
+    pub fn publish(&self, event: BotEvent) {
+        // an error only means nobody is listening
+        let _ = self.events.send(event);
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.events.receiver_count() > 0
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BotEvent> {
+        self.events.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(4096)
+    }
+}
+
+/// Act-stage handler publishing new pools and decoded pool state changes
+pub struct EventPublisher {
+    bus: EventBus,
+}
+
+impl EventPublisher {
+    pub fn new(bus: EventBus) -> Self {
+        Self { bus }
+    }
+}
+
+impl Handler for EventPublisher {
+    fn name(&self) -> &'static str {
+        "event_publisher"
+    }
+
+    fn stage(&self) -> Stage {
+        Stage::Act
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        if !self.bus.has_subscribers() {
+            return Ok(Flow::Continue);
+        }
+        let slot = account.update.slot;
+        let tokens = account.decoded.as_ref().and_then(|decoded| decoded.pool_tokens());
+        for event in &account.events {
+            match event {
+                PipelineEvent::NewPool { dex, pool } => self.bus.publish(BotEvent::NewPools {
+                    dex: *dex,
+                    pool: *pool,
+                    slot,
+                    tokens: tokens.filter(|_| *pool == account.update.pubkey),
+                }),
+                // raised on ProgramData updates, which stop before the act stage
+                PipelineEvent::ProgramUpgraded { .. } => {}
+            }
+        }
+        if let Some(decoded) = &account.decoded {
+            if let Some(dex) = decoded.dex() {
+                self.bus.publish(BotEvent::PoolState {
+                    dex,
+                    pool: account.update.pubkey,
+                    slot,
+                    tokens,
+                    state: serde_json::to_value(decoded)?,
+                });
+            }
+        }
+        Ok(Flow::Continue)
+    }
+}
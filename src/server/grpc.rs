@@ -0,0 +1,191 @@
+//! gRPC counterpart of the WebSocket endpoint, for services that would rather consume typed,
+//! DEX-agnostic events than re-decode raw Yellowstone updates. The schema lives in
+//! `proto/pool_events.proto` and is compiled by `build.rs`.
+
+use std::{collections::HashSet, net::SocketAddr, pin::Pin, str::FromStr};
+
+use futures::Stream;
+use log::{debug, info, warn};
+use solana_program::pubkey::Pubkey;
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast::error::RecvError, mpsc},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::dex::Dex;
+use crate::dex_processor::PoolTokens;
+
+use super::{BotEvent, EventBus, EventFilter, Topic};
+
+pub mod proto {
+    tonic::include_proto!("mevbot.events.v1");
+}
+
+use proto::{
+    pool_event::Event,
+    pool_events_server::{PoolEvents, PoolEventsServer},
+    PoolEvent, SubscribeEventsRequest,
+};
+
+/// Serves `mevbot.events.v1.PoolEvents` for everything published on the bus
+pub struct PoolEventService {
+    bus: EventBus,
+}
+
+impl PoolEventService {
+    pub fn new(bus: EventBus) -> Self {
+        Self { bus }
+    }
+
+    pub fn into_server(self) -> PoolEventsServer<Self> {
+        PoolEventsServer::new(self)
+    }
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<PoolEvent, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl PoolEvents for PoolEventService {
+    type SubscribeStream = EventStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let (topics, filter) = parse_request(request.into_inner()).map_err(Status::invalid_argument)?;
+        let mut events = self.bus.subscribe();
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    received = events.recv() => received,
+                    // do not wait for the next matching event to notice the client left
+                    _ = tx.closed() => break,
+                };
+                let event = match received {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(missed)) => {
+                        let _ = tx.send(Err(Status::data_loss(format!("lagged, {} events missed", missed)))).await;
+                        return;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                if !topics.contains(&event.topic()) || !filter.matches(&event) {
+                    continue;
+                }
+                let Some(event) = to_proto(event) else {
+                    continue;
+                };
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
+                }
+            }
+            debug!("grpc event client went away");
+        });
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+}
+
+fn parse_request(request: SubscribeEventsRequest) -> Result<(HashSet<Topic>, EventFilter), String> {
+    let mut topics = HashSet::new();
+    for topic in request.topics() {
+        topics.insert(match topic {
+            proto::Topic::NewPools => Topic::NewPools,
+            proto::Topic::PoolState => Topic::PoolState,
+            proto::Topic::Swaps => Topic::Swaps,
+            proto::Topic::Unspecified => return Err("topic must be set".into()),
+        });
+    }
+    if topics.is_empty() {
+        topics.extend([Topic::NewPools, Topic::PoolState, Topic::Swaps]);
+    }
+
+    let dexes = request
+        .dexes
+        .iter()
+        .map(|tag| Dex::from_tag(tag).ok_or_else(|| format!("unknown dex {}", tag)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let filter = EventFilter {
+        dex: (!dexes.is_empty()).then_some(dexes),
+        pools: parse_pubkeys("pools", &request.pools)?,
+        mints: parse_pubkeys("mints", &request.mints)?,
+    };
+    Ok((topics, filter))
+}
+
+fn parse_pubkeys(field: &str, keys: &[String]) -> Result<Option<Vec<Pubkey>>, String> {
+    if keys.is_empty() {
+        return Ok(None);
+    }
+    keys.iter()
+        .map(|key| Pubkey::from_str(key).map_err(|err| format!("{}: {}: {}", field, key, err)))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+/// Normalized form of `event`, `None` for topics the gRPC schema does not carry
+pub fn to_proto(event: BotEvent) -> Option<PoolEvent> {
+    let event = match event {
+        BotEvent::NewPools { dex, pool, slot, tokens } => Event::NewPool(proto::NewPool {
+            dex: dex.tag().to_string(),
+            pool: pool.to_string(),
+            slot,
+            tokens: tokens.map(tokens_to_proto),
+        }),
+        BotEvent::PoolState {
+            dex,
+            pool,
+            slot,
+            tokens,
+            state,
+        } => Event::PoolState(proto::PoolState {
+            dex: dex.tag().to_string(),
+            pool: pool.to_string(),
+            slot,
+            tokens: tokens.map(tokens_to_proto),
+            layout_json: state.to_string(),
+        }),
+        BotEvent::Swaps {
+            dex,
+            signature,
+            slot,
+            accounts,
+        } => Event::Swap(proto::SwapEvent {
+            dex: dex.tag().to_string(),
+            signature,
+            slot,
+            accounts: accounts.iter().map(Pubkey::to_string).collect(),
+        }),
+        BotEvent::Trades { .. } => return None,
+    };
+    Some(PoolEvent { event: Some(event) })
+}
+
+fn tokens_to_proto(tokens: PoolTokens) -> proto::PoolTokens {
+    proto::PoolTokens {
+        base_mint: tokens.base_mint.to_string(),
+        quote_mint: tokens.quote_mint.to_string(),
+        base_vault: tokens.base_vault.to_string(),
+        quote_vault: tokens.quote_vault.to_string(),
+    }
+}
+
+/// Binds `addr` (port 0 picks one) and serves the event stream until the task is aborted
+pub async fn serve(addr: SocketAddr, bus: EventBus) -> anyhow::Result<(SocketAddr, JoinHandle<()>)> {
+    let listener = TcpListener::bind(addr).await?;
+    let addr = listener.local_addr()?;
+    info!("serving grpc events on {}", addr);
+    let task = tokio::spawn(async move {
+        let result = Server::builder()
+            .add_service(PoolEventService::new(bus).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await;
+        if let Err(err) = result {
+            warn!("grpc event server stopped: {}", err);
+        }
+    });
+    Ok((addr, task))
+}
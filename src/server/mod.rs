@@ -0,0 +1,9 @@
+//! Embedded server publishing what the bot detects to local clients (front-ends, other bots).
+
+pub mod events;
+pub mod grpc;
+pub mod ws;
+
+pub use events::{BotEvent, EventBus, EventFilter, EventPublisher, Topic};
+pub use grpc::PoolEventService;
+pub use ws::{ClientMessage, EventServer, ServerMessage};
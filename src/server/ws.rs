@@ -0,0 +1,152 @@
+//! WebSocket endpoint for [`BotEvent`]s.
+//!
+//! Clients send `{"op":"subscribe","id":"<id>","topic":"new_pools","filter":{"dex":["raydium_amm_v4"]}}`
+//! (the filter is optional) and `{"op":"unsubscribe","id":"<id>"}`. Matching events arrive as
+//! `{"op":"event","subscription":"<id>","event":{"topic":"new_pools",...}}`, once per matching
+//! subscription. A client that fell too far behind gets `{"op":"lagged","missed":<n>}`.
+
+use std::{collections::HashMap, net::SocketAddr};
+
+use futures::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::broadcast::error::RecvError,
+    task::JoinHandle,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+use super::{BotEvent, EventBus, EventFilter, Topic};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        id: String,
+        topic: Topic,
+        #[serde(default)]
+        filter: EventFilter,
+    },
+    Unsubscribe {
+        id: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed { id: String },
+    Unsubscribed { id: String },
+    Event { subscription: String, event: Box<BotEvent> },
+    Lagged { missed: u64 },
+    Error { message: String },
+}
+
+/// Accept loop, one task per client
+pub struct EventServer {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl EventServer {
+    /// Binds `addr` (port 0 picks one) and serves events published on `bus`
+    pub async fn start(addr: SocketAddr, bus: EventBus) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        info!("serving events on ws://{}", addr);
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        tokio::spawn(serve_client(stream, peer, bus.clone()));
+                    }
+                    Err(err) => warn!("event server accept failed: {}", err),
+                }
+            }
+        });
+        Ok(Self { addr, task })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for EventServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve_client(stream: TcpStream, peer: SocketAddr, bus: EventBus) {
+    let socket = match accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(err) => {
+            debug!("websocket handshake with {} failed: {}", peer, err);
+            return;
+        }
+    };
+    debug!("event client {} connected", peer);
+    let (mut sink, mut incoming) = socket.split();
+    // subscribed before the first request so nothing published meanwhile is missed
+    let mut events = bus.subscribe();
+    let mut subscriptions: HashMap<String, (Topic, EventFilter)> = HashMap::new();
+
+    loop {
+        let replies = tokio::select! {
+            message = incoming.next() => match message {
+                Some(Ok(Message::Text(text))) => vec![handle_request(&text, &mut subscriptions)],
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => continue,
+                Some(Err(err)) => {
+                    debug!("event client {} failed: {}", peer, err);
+                    break;
+                }
+            },
+            event = events.recv() => match event {
+                Ok(event) => subscriptions
+                    .iter()
+                    .filter(|(_, (topic, filter))| *topic == event.topic() && filter.matches(&event))
+                    .map(|(id, _)| ServerMessage::Event {
+                        subscription: id.clone(),
+                        event: Box::new(event.clone()),
+                    })
+                    .collect(),
+                Err(RecvError::Lagged(missed)) => vec![ServerMessage::Lagged { missed }],
+                Err(RecvError::Closed) => break,
+            },
+        };
+        for reply in replies {
+            let text = match serde_json::to_string(&reply) {
+                Ok(text) => text,
+                Err(err) => {
+                    warn!("could not serialize {:?}: {}", reply, err);
+                    continue;
+                }
+            };
+            if sink.send(Message::Text(text)).await.is_err() {
+                return;
+            }
+        }
+    }
+    debug!("event client {} disconnected", peer);
+}
+
+fn handle_request(text: &str, subscriptions: &mut HashMap<String, (Topic, EventFilter)>) -> ServerMessage {
+    match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::Subscribe { id, topic, filter }) => {
+            subscriptions.insert(id.clone(), (topic, filter));
+            ServerMessage::Subscribed { id }
+        }
+        Ok(ClientMessage::Unsubscribe { id }) => match subscriptions.remove(&id) {
+            Some(_) => ServerMessage::Unsubscribed { id },
+            None => ServerMessage::Error {
+                message: format!("no subscription {}", id),
+            },
+        },
+        Err(err) => ServerMessage::Error {
+            message: format!("invalid request: {}", err),
+        },
+    }
+}
@@ -0,0 +1,293 @@
+//! Client-side evaluation of Yellowstone subscription filters.
+//!
+//! Built from the same protobuf structs that are sent to the server, so sources that cannot
+//! filter (WebSocket, replay files, providers with filter limits) see exactly what a Yellowstone
+//! endpoint would have sent for the request.
+
+use std::collections::HashSet;
+
+use futures::{future::BoxFuture, FutureExt};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_program::pubkey::Pubkey;
+use spl_token::state::{Account as TokenAccount, GenericTokenAccount};
+use thiserror::Error;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_request_filter_accounts_filter::Filter as AccountFilterKind,
+    subscribe_request_filter_accounts_filter_lamports::Cmp as LamportsCmp,
+    subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData, SubscribeRequest,
+    SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions,
+};
+
+use crate::dex_processor::AccountUpdate;
+
+use super::{SourceUpdate, TransactionUpdate, UpdateSource};
+
+// same limits as the RPC `memcmp` filter
+const MAX_DATA_SIZE: usize = 128;
+const MAX_DATA_BASE58_SIZE: usize = 175;
+const MAX_DATA_BASE64_SIZE: usize = 172;
+
+/// Request the server would reject
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FilterError {
+    #[error("invalid pubkey {0}")]
+    InvalidPubkey(String),
+    #[error("memcmp data should be defined")]
+    MissingMemcmpData,
+    #[error("memcmp data too large ({0} bytes)")]
+    MemcmpTooLarge(usize),
+    #[error("invalid memcmp data: {0}")]
+    InvalidMemcmpData(String),
+    #[error("lamports comparison should be defined")]
+    MissingLamportsCmp,
+    #[error("token_account_state only allowed to be true")]
+    TokenAccountStateFalse,
+    #[error("{0} used more than once")]
+    Duplicate(&'static str),
+    #[error("filter should be defined")]
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataFilter {
+    Memcmp { offset: usize, bytes: Vec<u8> },
+    Datasize(usize),
+    TokenAccountState,
+    Lamports(LamportsCmp),
+}
+
+impl DataFilter {
+    pub fn matches(&self, lamports: u64, data: &[u8]) -> bool {
+        match self {
+            Self::Memcmp { offset, bytes } => offset
+                .checked_add(bytes.len())
+                .and_then(|end| data.get(*offset..end))
+                .is_some_and(|window| window == bytes.as_slice()),
+            Self::Datasize(size) => data.len() == *size,
+            Self::TokenAccountState => TokenAccount::valid_account_data(data),
+            Self::Lamports(cmp) => match *cmp {
+                LamportsCmp::Eq(v) => lamports == v,
+                LamportsCmp::Ne(v) => lamports != v,
+                LamportsCmp::Lt(v) => lamports < v,
+                LamportsCmp::Gt(v) => lamports > v,
+            },
+        }
+    }
+}
+
+/// One named `accounts` entry: any listed account, any listed owner, all data filters
+#[derive(Debug, Clone, Default)]
+pub struct AccountFilter {
+    pub accounts: HashSet<Pubkey>,
+    pub owners: HashSet<Pubkey>,
+    pub filters: Vec<DataFilter>,
+}
+
+impl AccountFilter {
+    pub fn matches(&self, account: &AccountUpdate) -> bool {
+        (self.accounts.is_empty() || self.accounts.contains(&account.pubkey))
+            && (self.owners.is_empty() || self.owners.contains(&account.owner))
+            && self.filters.iter().all(|f| f.matches(account.lamports, &account.data))
+    }
+}
+
+impl TryFrom<&SubscribeRequestFilterAccounts> for AccountFilter {
+    type Error = FilterError;
+
+    fn try_from(filter: &SubscribeRequestFilterAccounts) -> Result<Self, Self::Error> {
+        let mut filters = Vec::with_capacity(filter.filters.len());
+        for f in &filter.filters {
+            let data_filter = match &f.filter {
+                Some(AccountFilterKind::Memcmp(memcmp)) => DataFilter::Memcmp {
+                    offset: memcmp.offset as usize,
+                    bytes: memcmp_bytes(memcmp.data.as_ref().ok_or(FilterError::MissingMemcmpData)?)?,
+                },
+                Some(AccountFilterKind::Datasize(size)) => DataFilter::Datasize(*size as usize),
+                Some(AccountFilterKind::TokenAccountState(true)) => DataFilter::TokenAccountState,
+                Some(AccountFilterKind::TokenAccountState(false)) => return Err(FilterError::TokenAccountStateFalse),
+                Some(AccountFilterKind::Lamports(lamports)) => {
+                    DataFilter::Lamports(lamports.cmp.ok_or(FilterError::MissingLamportsCmp)?)
+                }
+                None => return Err(FilterError::Empty),
+            };
+            let unique = match data_filter {
+                DataFilter::Datasize(_) => Some("datasize"),
+                DataFilter::TokenAccountState => Some("token_account_state"),
+                _ => None,
+            };
+            if let Some(name) = unique {
+                let kind = std::mem::discriminant(&data_filter);
+                if filters.iter().any(|f| std::mem::discriminant(f) == kind) {
+                    return Err(FilterError::Duplicate(name));
+                }
+            }
+            filters.push(data_filter);
+        }
+
+        Ok(Self {
+            accounts: parse_pubkeys(&filter.account)?,
+            owners: parse_pubkeys(&filter.owner)?,
+            filters,
+        })
+    }
+}
+
+fn memcmp_bytes(data: &MemcmpData) -> Result<Vec<u8>, FilterError> {
+    let bytes = match data {
+        MemcmpData::Bytes(bytes) => bytes.clone(),
+        MemcmpData::Base58(s) if s.len() > MAX_DATA_BASE58_SIZE => {
+            return Err(FilterError::MemcmpTooLarge(s.len()))
+        }
+        MemcmpData::Base58(s) => bs58::decode(s)
+            .into_vec()
+            .map_err(|e| FilterError::InvalidMemcmpData(e.to_string()))?,
+        MemcmpData::Base64(s) if s.len() > MAX_DATA_BASE64_SIZE => {
+            return Err(FilterError::MemcmpTooLarge(s.len()))
+        }
+        MemcmpData::Base64(s) => BASE64
+            .decode(s)
+            .map_err(|e| FilterError::InvalidMemcmpData(e.to_string()))?,
+    };
+    if bytes.len() > MAX_DATA_SIZE {
+        return Err(FilterError::MemcmpTooLarge(bytes.len()));
+    }
+    Ok(bytes)
+}
+
+/// One named `transactions` entry
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub vote: Option<bool>,
+    pub failed: Option<bool>,
+    pub signature: Option<String>,
+    pub include: HashSet<Pubkey>,
+    pub exclude: HashSet<Pubkey>,
+    pub required: HashSet<Pubkey>,
+}
+
+impl TransactionFilter {
+    pub fn matches(&self, tx: &TransactionUpdate) -> bool {
+        if self.vote.is_some_and(|v| v != tx.is_vote) || self.failed.is_some_and(|f| f != tx.failed) {
+            return false;
+        }
+        if self.signature.as_ref().is_some_and(|s| *s != tx.signature.to_string()) {
+            return false;
+        }
+        let keys = &tx.account_keys;
+        (self.include.is_empty() || keys.iter().any(|k| self.include.contains(k)))
+            && !keys.iter().any(|k| self.exclude.contains(k))
+            && self.required.iter().all(|k| keys.contains(k))
+    }
+}
+
+impl TryFrom<&SubscribeRequestFilterTransactions> for TransactionFilter {
+    type Error = FilterError;
+
+    fn try_from(filter: &SubscribeRequestFilterTransactions) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vote: filter.vote,
+            failed: filter.failed,
+            signature: filter.signature.clone(),
+            include: parse_pubkeys(&filter.account_include)?,
+            exclude: parse_pubkeys(&filter.account_exclude)?,
+            required: parse_pubkeys(&filter.account_required)?,
+        })
+    }
+}
+
+fn parse_pubkeys(keys: &[String]) -> Result<HashSet<Pubkey>, FilterError> {
+    keys.iter()
+        .map(|k| k.parse().map_err(|_| FilterError::InvalidPubkey(k.clone())))
+        .collect()
+}
+
+/// Every named filter of a `SubscribeRequest`
+#[derive(Debug, Clone, Default)]
+pub struct FilterSet {
+    pub accounts: Vec<(String, AccountFilter)>,
+    pub transactions: Vec<(String, TransactionFilter)>,
+    pub slots: Vec<String>,
+}
+
+impl FilterSet {
+    /// Names of the filters `update` matches, sorted. Empty means the server would not send it.
+    pub fn matching(&self, update: &SourceUpdate) -> Vec<&str> {
+        let mut names: Vec<&str> = match update {
+            SourceUpdate::Account(account) => self
+                .accounts
+                .iter()
+                .filter(|(_, f)| f.matches(account))
+                .map(|(name, _)| name.as_str())
+                .collect(),
+            SourceUpdate::Transaction(tx) => self
+                .transactions
+                .iter()
+                .filter(|(_, f)| f.matches(tx))
+                .map(|(name, _)| name.as_str())
+                .collect(),
+            SourceUpdate::Slot(_) => self.slots.iter().map(String::as_str).collect(),
+        };
+        names.sort_unstable();
+        names
+    }
+
+    pub fn matches(&self, update: &SourceUpdate) -> bool {
+        !self.matching(update).is_empty()
+    }
+}
+
+impl TryFrom<&SubscribeRequest> for FilterSet {
+    type Error = FilterError;
+
+    fn try_from(request: &SubscribeRequest) -> Result<Self, Self::Error> {
+        let mut set = Self {
+            accounts: request
+                .accounts
+                .iter()
+                .map(|(name, f)| Ok((name.clone(), AccountFilter::try_from(f)?)))
+                .collect::<Result<_, FilterError>>()?,
+            transactions: request
+                .transactions
+                .iter()
+                .map(|(name, f)| Ok((name.clone(), TransactionFilter::try_from(f)?)))
+                .collect::<Result<_, FilterError>>()?,
+            slots: request.slots.keys().cloned().collect(),
+        };
+        // stable order, the request maps are hash maps
+        set.accounts.sort_by(|a, b| a.0.cmp(&b.0));
+        set.transactions.sort_by(|a, b| a.0.cmp(&b.0));
+        set.slots.sort();
+        Ok(set)
+    }
+}
+
+/// Drops whatever `filters` would not let through, for sources that cannot filter themselves
+pub struct FilteredSource<S> {
+    inner: S,
+    filters: FilterSet,
+}
+
+impl<S: UpdateSource> FilteredSource<S> {
+    pub fn new(inner: S, filters: FilterSet) -> Self {
+        Self { inner, filters }
+    }
+}
+
+impl<S: UpdateSource> UpdateSource for FilteredSource<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        async move {
+            while let Some(update) = self.inner.next_update().await? {
+                if self.filters.matches(&update) {
+                    return Ok(Some(update));
+                }
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
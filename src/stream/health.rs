@@ -0,0 +1,405 @@
+//! Stream health: is the subscription alive, or is the market just quiet?
+//!
+//! [`HealthMonitor`] is fed by [`GrpcSource`] (pings sent, pongs and messages received, slot
+//! updates) and turns that into a [`HealthSnapshot`]. [`ReconnectingSource`] checks it
+//! periodically, publishes it on a watch channel and reconnects when the stream stalls.
+
+use std::{collections::VecDeque, fmt};
+
+use futures::{future::BoxFuture, FutureExt};
+use log::{info, warn};
+use tokio::{
+    sync::watch,
+    time::{interval_at, sleep, Duration, Instant, Interval, MissedTickBehavior},
+};
+use yellowstone_grpc_proto::prelude::SubscribeRequest;
+
+use crate::metrics::Metrics;
+
+use super::{GrpcSource, Recorder, SourceUpdate, UpdateSource};
+
+/// Nominal slot time, used to turn wall-clock time into expected slots
+const SLOT_TIME: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy)]
+pub struct HealthConfig {
+    /// No message at all (updates or pongs) for this long means the stream is dead
+    pub stall_timeout: Duration,
+    /// An unanswered ping older than this means the stream is dead
+    pub pong_timeout: Duration,
+    /// Pong round-trip above this marks the stream degraded
+    pub max_rtt: Duration,
+    /// Slot stream behind the wall clock by more than this many slots marks it degraded
+    pub max_slot_drift: i64,
+    /// Window over which slot progress is compared with the wall clock
+    pub drift_window: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            stall_timeout: Duration::from_secs(10),
+            pong_timeout: Duration::from_secs(6),
+            max_rtt: Duration::from_millis(500),
+            max_slot_drift: 10,
+            drift_window: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// Connected, nothing measured yet
+    Connecting,
+    Healthy,
+    /// Usable but slow
+    Degraded(String),
+    /// Needs a reconnect
+    Stalled(String),
+}
+
+impl HealthStatus {
+    /// Numeric form for gauges: 0 connecting, 1 healthy, 2 degraded, 3 stalled
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Connecting => 0,
+            Self::Healthy => 1,
+            Self::Degraded(_) => 2,
+            Self::Stalled(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connecting => write!(f, "connecting"),
+            Self::Healthy => write!(f, "healthy"),
+            Self::Degraded(reason) => write!(f, "degraded: {}", reason),
+            Self::Stalled(reason) => write!(f, "stalled: {}", reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    pub status: HealthStatus,
+    /// Latest pong round-trip
+    pub pong_rtt: Option<Duration>,
+    /// Since the last message of any kind
+    pub since_last_message: Option<Duration>,
+    /// Since the last account/transaction/slot update
+    pub since_last_update: Option<Duration>,
+    pub slot: Option<u64>,
+    /// Slots the stream fell behind the wall clock over the drift window, negative when ahead
+    pub slot_drift: Option<i64>,
+    pub reconnects: u64,
+}
+
+impl Default for HealthSnapshot {
+    fn default() -> Self {
+        Self {
+            status: HealthStatus::Connecting,
+            pong_rtt: None,
+            since_last_message: None,
+            since_last_update: None,
+            slot: None,
+            slot_drift: None,
+            reconnects: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HealthMonitor {
+    config: HealthConfig,
+    connected_at: Instant,
+    outstanding_ping: Option<(i32, Instant)>,
+    pong_rtt: Option<Duration>,
+    last_message: Option<Instant>,
+    last_update: Option<Instant>,
+    // (when, slot) every time the highest slot advanced, oldest first
+    slots: VecDeque<(Instant, u64)>,
+}
+
+impl HealthMonitor {
+    pub fn new(config: HealthConfig, now: Instant) -> Self {
+        Self {
+            config,
+            connected_at: now,
+            outstanding_ping: None,
+            pong_rtt: None,
+            last_message: None,
+            last_update: None,
+            slots: VecDeque::new(),
+        }
+    }
+
+    pub fn on_ping_sent(&mut self, id: i32, now: Instant) {
+        // keep the oldest unanswered ping, it is the one that proves a stall
+        if self.outstanding_ping.is_none() {
+            self.outstanding_ping = Some((id, now));
+        }
+    }
+
+    pub fn on_pong(&mut self, id: i32, now: Instant) {
+        if let Some((sent_id, sent_at)) = self.outstanding_ping {
+            if id >= sent_id {
+                self.pong_rtt = Some(now.saturating_duration_since(sent_at));
+                self.outstanding_ping = None;
+            }
+        }
+    }
+
+    /// Any message from the server, pongs included
+    pub fn on_message(&mut self, now: Instant) {
+        self.last_message = Some(now);
+    }
+
+    pub fn on_update(&mut self, update: &SourceUpdate, now: Instant) {
+        self.last_update = Some(now);
+        if let SourceUpdate::Slot(slot) = update {
+            if self.slots.back().is_none_or(|(_, highest)| slot.slot > *highest) {
+                self.slots.push_back((now, slot.slot));
+                self.prune(now);
+            }
+        }
+    }
+
+    pub fn snapshot(&self, now: Instant) -> HealthSnapshot {
+        let since = |at: Option<Instant>| at.map(|at| now.saturating_duration_since(at));
+        let since_last_message = since(self.last_message);
+        let slot_drift = self.slot_drift(now);
+
+        let silent_for = since_last_message.unwrap_or_else(|| now.saturating_duration_since(self.connected_at));
+        let status = if silent_for > self.config.stall_timeout {
+            HealthStatus::Stalled(format!("no message for {:?}", silent_for))
+        } else if let Some(waited) = self
+            .outstanding_ping
+            .map(|(_, sent)| now.saturating_duration_since(sent))
+            .filter(|waited| *waited > self.config.pong_timeout)
+        {
+            HealthStatus::Stalled(format!("no pong for {:?}", waited))
+        } else if let Some(rtt) = self.pong_rtt.filter(|rtt| *rtt > self.config.max_rtt) {
+            HealthStatus::Degraded(format!("pong round-trip {:?}", rtt))
+        } else if let Some(drift) = slot_drift.filter(|drift| *drift > self.config.max_slot_drift) {
+            HealthStatus::Degraded(format!("slot stream {} slots behind the clock", drift))
+        } else if self.last_message.is_none() {
+            HealthStatus::Connecting
+        } else {
+            HealthStatus::Healthy
+        };
+
+        HealthSnapshot {
+            status,
+            pong_rtt: self.pong_rtt,
+            since_last_message,
+            since_last_update: since(self.last_update),
+            slot: self.slots.back().map(|(_, slot)| *slot),
+            slot_drift,
+            reconnects: 0,
+        }
+    }
+
+    /// Expected slots from the wall clock minus observed slots, over the drift window
+    fn slot_drift(&self, now: Instant) -> Option<i64> {
+        let (_, latest) = *self.slots.back()?;
+        let window_start = now.checked_sub(self.config.drift_window);
+        let (start_at, start_slot) = *self
+            .slots
+            .iter()
+            .find(|(at, _)| window_start.is_none_or(|start| *at >= start))
+            .unwrap_or_else(|| self.slots.back().unwrap());
+        let expected = (now.saturating_duration_since(start_at).as_millis() / SLOT_TIME.as_millis()) as i64;
+        Some(expected - (latest - start_slot) as i64)
+    }
+
+    /// Forgets slot samples that fell out of the drift window
+    fn prune(&mut self, now: Instant) {
+        let Some(window_start) = now.checked_sub(self.config.drift_window) else {
+            return;
+        };
+        while self.slots.len() > 1 && self.slots[1].0 <= window_start {
+            self.slots.pop_front();
+        }
+    }
+}
+
+/// [`GrpcSource`] that reconnects, with exponential backoff, when the stream ends, fails or
+/// stalls. The latest [`HealthSnapshot`] is published on [`ReconnectingSource::health`].
+pub struct ReconnectingSource {
+    endpoint: String,
+    x_token: Option<String>,
+    request: SubscribeRequest,
+    config: HealthConfig,
+    source: Option<GrpcSource>,
+    recorder: Option<Recorder>,
+    subscription: Option<watch::Receiver<SubscribeRequest>>,
+    metrics: Option<Metrics>,
+    health: watch::Sender<HealthSnapshot>,
+    check: Option<Interval>,
+    reconnects: u64,
+}
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+impl ReconnectingSource {
+    pub fn new(
+        endpoint: impl Into<String>,
+        x_token: Option<String>,
+        request: SubscribeRequest,
+        config: HealthConfig,
+    ) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            x_token,
+            request,
+            config,
+            source: None,
+            recorder: None,
+            subscription: None,
+            metrics: None,
+            health: watch::channel(HealthSnapshot::default()).0,
+            check: None,
+            reconnects: 0,
+        }
+    }
+
+    /// Uses an already connected source for the first session
+    pub fn with_source(mut self, source: GrpcSource) -> Self {
+        self.source = Some(self.attach(source));
+        self
+    }
+
+    /// The recorder survives reconnects
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Follows a [`super::SubscriptionController`], reconnects subscribe with its latest request
+    pub fn with_subscription(mut self, requests: watch::Receiver<SubscribeRequest>) -> Self {
+        if let Some(source) = self.source.take() {
+            self.source = Some(source.with_subscription(requests.clone()));
+        }
+        self.subscription = Some(requests);
+        self
+    }
+
+    /// Every session records its updates and network latency under this endpoint
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        if let Some(source) = self.source.take() {
+            self.source = Some(source.with_metrics(metrics.clone()));
+        }
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Gives a fresh session the settings that outlive it
+    fn attach(&self, mut source: GrpcSource) -> GrpcSource {
+        if let Some(requests) = &self.subscription {
+            source = source.with_subscription(requests.clone());
+        }
+        if let Some(metrics) = &self.metrics {
+            source = source.with_metrics(metrics.clone());
+        }
+        source
+    }
+
+    pub fn health(&self) -> watch::Receiver<HealthSnapshot> {
+        self.health.subscribe()
+    }
+
+    fn publish(&self, mut snapshot: HealthSnapshot) {
+        snapshot.reconnects = self.reconnects;
+        // receivers wake up on status changes, the measurements are refreshed silently
+        self.health.send_if_modified(|current| {
+            let changed = current.status != snapshot.status;
+            *current = snapshot;
+            changed
+        });
+    }
+
+    async fn connect(&mut self) -> GrpcSource {
+        let mut backoff = Duration::from_millis(250);
+        loop {
+            let request = match &self.subscription {
+                Some(requests) => requests.borrow().clone(),
+                None => self.request.clone(),
+            };
+            match GrpcSource::connect(self.endpoint.clone(), self.x_token.clone(), request).await {
+                Ok(source) => {
+                    info!("connected to {}", self.endpoint);
+                    return self.attach(source.with_health_config(self.config));
+                }
+                Err(err) => {
+                    warn!("connecting to {} failed: {:#}, retrying in {:?}", self.endpoint, err, backoff);
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+            }
+        }
+    }
+
+    fn drop_session(&mut self, reason: &str) {
+        warn!("{}: {}, reconnecting", self.endpoint, reason);
+        if let Some(mut source) = self.source.take() {
+            self.recorder = source.take_recorder();
+        }
+        self.reconnects += 1;
+        self.publish(HealthSnapshot {
+            status: HealthStatus::Stalled(reason.to_string()),
+            ..Default::default()
+        });
+    }
+
+    async fn next(&mut self) -> anyhow::Result<Option<SourceUpdate>> {
+        loop {
+            let source = match self.source.as_mut() {
+                Some(source) => source,
+                None => {
+                    let mut source = self.connect().await;
+                    if let Some(recorder) = self.recorder.take() {
+                        source = source.with_recorder(recorder);
+                    }
+                    self.source.insert(source)
+                }
+            };
+
+            let check = self.check.get_or_insert_with(|| {
+                let mut check = interval_at(Instant::now() + CHECK_INTERVAL, CHECK_INTERVAL);
+                check.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                check
+            });
+            let update = tokio::select! {
+                update = source.next_update() => update,
+                _ = check.tick() => {
+                    let snapshot = source.health(Instant::now());
+                    let stalled = matches!(snapshot.status, HealthStatus::Stalled(_));
+                    let reason = snapshot.status.to_string();
+                    self.publish(snapshot);
+                    if stalled {
+                        self.drop_session(&reason);
+                    }
+                    continue;
+                }
+            };
+            match update {
+                Ok(Some(update)) => return Ok(Some(update)),
+                Ok(None) => self.drop_session("stream closed"),
+                Err(err) => self.drop_session(&format!("stream failed: {:#}", err)),
+            }
+        }
+    }
+}
+
+impl UpdateSource for ReconnectingSource {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        self.next().boxed()
+    }
+}
@@ -0,0 +1,23 @@
+//! Update streams: sources, health, recording and replay.
+
+pub mod filter;
+pub mod health;
+pub mod multiplex;
+pub mod recorder;
+pub mod replay;
+pub mod source;
+pub mod subscription;
+
+pub use filter::{FilterError, FilterSet, FilteredSource};
+pub use health::{HealthConfig, HealthMonitor, HealthSnapshot, HealthStatus, ReconnectingSource};
+pub use multiplex::{EndpointStats, MultiplexStats, Multiplexer};
+pub use recorder::Recorder;
+pub use replay::{RecordedUpdate, RecordingReader, Replay, ReplaySpeed};
+pub use source::{
+    GrpcSource, ReplaySource, SlotStatus, SlotUpdate, SourceUpdate, TransactionUpdate, UpdateSource,
+    WebSocketSource,
+};
+pub use subscription::SubscriptionController;
+
+/// Magic at the start of every (decompressed) recording
+pub const RECORDING_MAGIC: &[u8; 8] = b"SNPRREC\x01";
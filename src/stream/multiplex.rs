@@ -0,0 +1,239 @@
+//! Fan-in of several sources subscribed with the same request.
+//!
+//! Every copy of an update is keyed (`(pubkey, slot, write_version)` for accounts, the
+//! signature for transactions, `(slot, status)` for slots). The first copy is forwarded, later
+//! copies only feed the per-endpoint statistics: how often each endpoint won and how far behind
+//! the winner it was.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use log::warn;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use tokio::{sync::mpsc, task::JoinHandle, time::Instant};
+
+use super::{SlotStatus, SourceUpdate, UpdateSource};
+
+/// Keys older than this many slots behind the newest one are forgotten
+pub(super) const DEDUP_SLOTS: u64 = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum DedupKey {
+    Account {
+        pubkey: Pubkey,
+        slot: u64,
+        write_version: u64,
+    },
+    Transaction(Signature),
+    Slot(u64, SlotStatus),
+}
+
+impl DedupKey {
+    fn of(update: &SourceUpdate) -> Self {
+        match update {
+            SourceUpdate::Account(account) => Self::Account {
+                pubkey: account.pubkey,
+                slot: account.slot,
+                write_version: account.write_version,
+            },
+            SourceUpdate::Transaction(tx) => Self::Transaction(tx.signature),
+            SourceUpdate::Slot(slot) => Self::Slot(slot.slot, slot.status),
+        }
+    }
+}
+
+struct FirstArrival {
+    at: Instant,
+    slot: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EndpointStats {
+    pub name: String,
+    /// Every update received, duplicates included
+    pub received: u64,
+    /// Updates this endpoint delivered first
+    pub wins: u64,
+    /// Copies that arrived after another endpoint's, `total_lag` is summed over these
+    pub late: u64,
+    pub total_lag: Duration,
+    pub max_lag: Duration,
+    pub connected: bool,
+}
+
+impl EndpointStats {
+    pub fn win_rate(&self) -> f64 {
+        let delivered = self.wins + self.late;
+        if delivered == 0 {
+            0.0
+        } else {
+            self.wins as f64 / delivered as f64
+        }
+    }
+
+    /// Mean delay behind the winning endpoint, counting won updates as zero
+    pub fn mean_lag(&self) -> Duration {
+        let delivered = self.wins + self.late;
+        if delivered == 0 {
+            Duration::ZERO
+        } else {
+            self.total_lag / delivered as u32
+        }
+    }
+}
+
+/// Cloneable view of the multiplexer statistics, for periodic reporting from another task
+#[derive(Clone, Default)]
+pub struct MultiplexStats(Arc<Mutex<Vec<EndpointStats>>>);
+
+impl MultiplexStats {
+    pub fn snapshot(&self) -> Vec<EndpointStats> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn update(&self, endpoint: usize, f: impl FnOnce(&mut EndpointStats)) {
+        f(&mut self.0.lock().unwrap()[endpoint])
+    }
+}
+
+type Arrival = (usize, Instant, anyhow::Result<Option<SourceUpdate>>);
+
+pub struct Multiplexer {
+    arrivals: mpsc::Receiver<Arrival>,
+    seen: HashMap<DedupKey, FirstArrival>,
+    newest_slot: u64,
+    pruned_at: u64,
+    open: usize,
+    stats: MultiplexStats,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Multiplexer {
+    pub fn new(sources: Vec<Box<dyn UpdateSource>>) -> Self {
+        let (tx, arrivals) = mpsc::channel(4096);
+        let stats = MultiplexStats(Arc::new(Mutex::new(
+            sources
+                .iter()
+                .map(|source| EndpointStats {
+                    name: source.name().to_string(),
+                    connected: true,
+                    ..Default::default()
+                })
+                .collect(),
+        )));
+        let open = sources.len();
+        let tasks = sources
+            .into_iter()
+            .enumerate()
+            .map(|(endpoint, mut source)| {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    loop {
+                        let update = source.next_update().await;
+                        // take the timestamp before queueing so channel delay is not charged to the endpoint
+                        let at = Instant::now();
+                        let done = !matches!(update, Ok(Some(_)));
+                        if tx.send((endpoint, at, update)).await.is_err() || done {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        Self {
+            arrivals,
+            seen: HashMap::new(),
+            newest_slot: 0,
+            pruned_at: 0,
+            open,
+            stats,
+            tasks,
+        }
+    }
+
+    pub fn stats(&self) -> MultiplexStats {
+        self.stats.clone()
+    }
+
+    /// Forwards the first copy of each update, `None` once every endpoint has ended
+    async fn next_unique(&mut self) -> Option<SourceUpdate> {
+        while self.open > 0 {
+            let (endpoint, at, update) = self.arrivals.recv().await?;
+            let update = match update {
+                Ok(Some(update)) => update,
+                Ok(None) => {
+                    self.disconnect(endpoint, "stream closed".into());
+                    continue;
+                }
+                Err(err) => {
+                    self.disconnect(endpoint, format!("{:#}", err));
+                    continue;
+                }
+            };
+
+            let slot = update.slot();
+            match self.seen.get(&DedupKey::of(&update)) {
+                Some(first) => {
+                    let lag = at.saturating_duration_since(first.at);
+                    self.stats.update(endpoint, |s| {
+                        s.received += 1;
+                        s.late += 1;
+                        s.total_lag += lag;
+                        s.max_lag = s.max_lag.max(lag);
+                    });
+                }
+                None => {
+                    self.seen.insert(DedupKey::of(&update), FirstArrival { at, slot });
+                    self.stats.update(endpoint, |s| {
+                        s.received += 1;
+                        s.wins += 1;
+                    });
+                    self.prune(slot);
+                    return Some(update);
+                }
+            }
+        }
+        None
+    }
+
+    fn disconnect(&mut self, endpoint: usize, reason: String) {
+        self.stats.update(endpoint, |s| {
+            warn!("endpoint {} dropped out of the fan-in: {}", s.name, reason);
+            s.connected = false;
+        });
+        self.open -= 1;
+    }
+
+    fn prune(&mut self, slot: u64) {
+        self.newest_slot = self.newest_slot.max(slot);
+        if self.newest_slot >= self.pruned_at + DEDUP_SLOTS {
+            let oldest = self.newest_slot.saturating_sub(DEDUP_SLOTS);
+            self.seen.retain(|_, first| first.slot >= oldest);
+            self.pruned_at = self.newest_slot;
+        }
+    }
+}
+
+impl Drop for Multiplexer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl UpdateSource for Multiplexer {
+    fn name(&self) -> &str {
+        "multiplexer"
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        async move { Ok(self.next_unique().await) }.boxed()
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prost::Message};
+
+use super::RECORDING_MAGIC;
+
+/// Writes `SubscribeUpdate`s to a recording file.
+///
+/// Layout after the magic: `[u64 LE receive time, unix nanos][u32 LE length][protobuf bytes]`
+/// per update. With compression on, the whole file is a single zstd stream.
+pub struct Recorder {
+    writer: Box<dyn Write + Send>,
+    records: u64,
+    last_flush: Instant,
+}
+
+// a crashed or killed bot should still leave a usable recording behind
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+impl Recorder {
+    pub fn create(path: impl AsRef<Path>, compress: bool) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(path)?);
+        let writer: Box<dyn Write + Send> = if compress {
+            Box::new(zstd::stream::write::Encoder::new(file, 3)?.auto_finish())
+        } else {
+            Box::new(file)
+        };
+        Self::new(writer)
+    }
+
+    pub fn new(mut writer: Box<dyn Write + Send>) -> io::Result<Self> {
+        writer.write_all(RECORDING_MAGIC)?;
+        Ok(Self {
+            writer,
+            records: 0,
+            last_flush: Instant::now(),
+        })
+    }
+
+    pub fn record(&mut self, update: &SubscribeUpdate, received_at: SystemTime) -> io::Result<()> {
+        let nanos = received_at
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        let payload = update.encode_to_vec();
+        let len = u32::try_from(payload.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "update larger than 4 GiB"))?;
+        self.writer.write_all(&nanos.to_le_bytes())?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&payload)?;
+        self.records += 1;
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_flush = Instant::now();
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::stream::{self, Stream};
+use tokio::{sync::Semaphore, time::Instant};
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prost::Message};
+
+use super::RECORDING_MAGIC;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Debug, Clone)]
+pub struct RecordedUpdate {
+    pub received_at: SystemTime,
+    pub update: SubscribeUpdate,
+}
+
+/// Sequential reader for files written by [`super::Recorder`], compressed or not
+pub struct RecordingReader {
+    reader: Box<dyn Read + Send>,
+}
+
+impl RecordingReader {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let compressed = file.fill_buf()?.starts_with(&ZSTD_MAGIC);
+        let reader: Box<dyn Read + Send> = if compressed {
+            Box::new(zstd::stream::read::Decoder::with_buffer(file)?)
+        } else {
+            Box::new(file)
+        };
+        Self::new(reader)
+    }
+
+    pub fn new(mut reader: Box<dyn Read + Send>) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != RECORDING_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a recording file"));
+        }
+        Ok(Self { reader })
+    }
+
+    fn read_record(&mut self) -> io::Result<Option<RecordedUpdate>> {
+        let mut header = [0u8; 12];
+        match self.reader.read_exact(&mut header[..1]) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            other => other?,
+        }
+        self.reader.read_exact(&mut header[1..])?;
+        let nanos = u64::from_le_bytes(header[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(header[8..].try_into().unwrap()) as usize;
+        // the length is untrusted, so only allocate as much as the file actually holds
+        let mut payload = Vec::new();
+        self.reader.by_ref().take(len as u64).read_to_end(&mut payload)?;
+        if payload.len() < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let update = SubscribeUpdate::decode(payload.as_slice())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Some(RecordedUpdate {
+            received_at: UNIX_EPOCH + Duration::from_nanos(nanos),
+            update,
+        }))
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = io::Result<RecordedUpdate>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// How fast a [`Replay`] releases updates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Keep the recorded gaps between updates
+    Original,
+    /// Recorded gaps divided by the factor
+    Accelerated(f64),
+    /// No delay at all
+    Max,
+    /// One update per [`ReplayStepper::step`]
+    Stepwise,
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "original" | "1" => Ok(Self::Original),
+            "max" => Ok(Self::Max),
+            "step" | "stepwise" => Ok(Self::Stepwise),
+            factor => factor
+                .trim_end_matches('x')
+                .parse::<f64>()
+                .ok()
+                .filter(|f| *f > 0.0)
+                .map(Self::Accelerated)
+                .ok_or_else(|| format!("invalid replay speed {:?}, expected original|max|step|<factor>", s)),
+        }
+    }
+}
+
+/// Releases one update per call in [`ReplaySpeed::Stepwise`] mode
+#[derive(Clone)]
+pub struct ReplayStepper(Arc<Semaphore>);
+
+impl ReplayStepper {
+    pub fn step(&self) {
+        self.0.add_permits(1);
+    }
+}
+
+/// Paced replay of a recording, yielding updates the way the live stream would
+pub struct Replay {
+    reader: RecordingReader,
+    speed: ReplaySpeed,
+    steps: Arc<Semaphore>,
+    // (first recorded timestamp, wall clock when it was released)
+    origin: Option<(SystemTime, Instant)>,
+}
+
+impl Replay {
+    pub fn open(path: impl AsRef<Path>, speed: ReplaySpeed) -> io::Result<Self> {
+        Ok(Self::new(RecordingReader::open(path)?, speed))
+    }
+
+    pub fn new(reader: RecordingReader, speed: ReplaySpeed) -> Self {
+        Self {
+            reader,
+            speed,
+            steps: Arc::new(Semaphore::new(0)),
+            origin: None,
+        }
+    }
+
+    pub fn stepper(&self) -> ReplayStepper {
+        ReplayStepper(self.steps.clone())
+    }
+
+    pub async fn next(&mut self) -> Option<io::Result<RecordedUpdate>> {
+        let record = match self.reader.next()? {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        self.pace(record.received_at).await;
+        Some(Ok(record))
+    }
+
+    async fn pace(&mut self, received_at: SystemTime) {
+        let factor = match self.speed {
+            ReplaySpeed::Max => return,
+            ReplaySpeed::Stepwise => {
+                if let Ok(permit) = self.steps.acquire().await {
+                    permit.forget();
+                }
+                return;
+            }
+            ReplaySpeed::Original => 1.0,
+            ReplaySpeed::Accelerated(factor) => factor,
+        };
+        let (first, started) = *self.origin.get_or_insert((received_at, Instant::now()));
+        let offset = received_at.duration_since(first).unwrap_or_default();
+        tokio::time::sleep_until(started + offset.div_f64(factor)).await;
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = io::Result<RecordedUpdate>> {
+        stream::unfold(self, |mut replay| async move {
+            let item = replay.next().await?;
+            Some((item, replay))
+        })
+    }
+}
@@ -0,0 +1,694 @@
+//! Normalized update sources.
+//!
+//! The processing loop only sees [`SourceUpdate`]s, so it does not care whether they come from a
+//! Yellowstone gRPC stream, a Solana JSON-RPC WebSocket or a recording on disk.
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    pin::Pin,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::{bail, Context};
+use futures::{
+    channel::mpsc::SendError,
+    future::{self, BoxFuture},
+    sink::{Sink, SinkExt},
+    stream::{Stream, StreamExt},
+    FutureExt,
+};
+use log::warn;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionLogsConfig,
+        RpcTransactionLogsFilter,
+    },
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+    rpc_response::{RpcLogsResponse, SlotUpdate as RpcSlotUpdate},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_program::{pubkey::Pubkey, vote};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+    time::{interval, Duration, Instant, Interval, MissedTickBehavior},
+};
+use tonic::{transport::channel::ClientTlsConfig, Status};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_request_filter_accounts_filter::Filter as AccountFilterKind,
+    subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData,
+    subscribe_update::UpdateOneof, CommitmentLevel, SubscribeRequest,
+    SubscribeRequestFilterAccountsFilter, SubscribeRequestPing, SubscribeUpdate,
+    SubscribeUpdateTransaction,
+};
+
+use crate::dex_processor::AccountUpdate;
+use crate::metrics::{Metrics, Timings};
+
+use super::{multiplex::DEDUP_SLOTS, FilterSet, HealthConfig, HealthMonitor, HealthSnapshot, Recorder, Replay};
+
+/// Transaction seen by a source. WebSocket `logsSubscribe` only carries the signature, status
+/// and logs, so `account_keys` there is partial, see [`TransactionUpdate::from_logs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionUpdate {
+    pub signature: Signature,
+    pub slot: u64,
+    pub account_keys: Vec<Pubkey>,
+    pub is_vote: bool,
+    pub failed: bool,
+    pub logs: Vec<String>,
+}
+
+impl TransactionUpdate {
+    /// `logsSubscribe` notification for a transaction that mentions `mention`. The logs only
+    /// name the programs invoked, so `account_keys` holds `mention` followed by those programs,
+    /// and a transaction invoking the vote program counts as a vote.
+    pub fn from_logs(slot: u64, mention: Pubkey, response: RpcLogsResponse) -> anyhow::Result<Self> {
+        let mut account_keys = vec![mention];
+        for line in &response.logs {
+            // "Program <id> invoke [<depth>]"
+            let Some((program, _)) = line.strip_prefix("Program ").and_then(|rest| rest.split_once(" invoke [")) else {
+                continue;
+            };
+            let program: Pubkey = program.parse().context("invalid program id in logs")?;
+            if !account_keys.contains(&program) {
+                account_keys.push(program);
+            }
+        }
+        Ok(Self {
+            signature: response.signature.parse().context("invalid signature")?,
+            slot,
+            is_vote: account_keys.contains(&vote::program::ID),
+            account_keys,
+            failed: response.err.is_some(),
+            logs: response.logs,
+        })
+    }
+}
+
+/// Same values as Yellowstone's `SlotStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SlotStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+    FirstShredReceived,
+    Completed,
+    CreatedBank,
+    Dead,
+}
+
+impl TryFrom<i32> for SlotStatus {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Processed),
+            1 => Ok(Self::Confirmed),
+            2 => Ok(Self::Finalized),
+            3 => Ok(Self::FirstShredReceived),
+            4 => Ok(Self::Completed),
+            5 => Ok(Self::CreatedBank),
+            6 => Ok(Self::Dead),
+            other => Err(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotUpdate {
+    pub slot: u64,
+    pub parent: Option<u64>,
+    pub status: SlotStatus,
+}
+
+#[derive(Debug, Clone)]
+pub enum SourceUpdate {
+    Account(AccountUpdate),
+    Transaction(TransactionUpdate),
+    Slot(SlotUpdate),
+}
+
+impl SourceUpdate {
+    /// `None` for messages that carry no chain data (pings, pongs) or that are not normalized yet
+    pub fn from_subscribe_update(update: SubscribeUpdate) -> anyhow::Result<Option<Self>> {
+        let timings = message_timings(&update);
+        Ok(match update.update_oneof {
+            Some(UpdateOneof::Account(account)) => AccountUpdate::from_grpc(account)?.map(|mut account| {
+                account.timings = timings;
+                Self::Account(account)
+            }),
+            Some(UpdateOneof::Transaction(tx)) => Some(Self::Transaction(transaction_from_grpc(tx)?)),
+            Some(UpdateOneof::Slot(slot)) => Some(Self::Slot(SlotUpdate {
+                slot: slot.slot,
+                parent: slot.parent,
+                status: SlotStatus::try_from(slot.status)
+                    .map_err(|status| anyhow::anyhow!("unknown slot status {}", status))?,
+            })),
+            _ => None,
+        })
+    }
+
+    pub fn slot(&self) -> u64 {
+        match self {
+            Self::Account(update) => update.slot,
+            Self::Transaction(update) => update.slot,
+            Self::Slot(update) => update.slot,
+        }
+    }
+
+    /// Only account updates carry their timings on into the pipeline
+    fn set_timings(&mut self, timings: Timings) {
+        if let Self::Account(account) = self {
+            account.timings = timings;
+        }
+    }
+}
+
+/// Timings with the plugin's `created_at`, nothing received yet
+fn message_timings(update: &SubscribeUpdate) -> Timings {
+    Timings {
+        created_at: update.created_at.and_then(|ts| ts.try_into().ok()),
+        ..Default::default()
+    }
+}
+
+fn received_now() -> Timings {
+    let mut timings = Timings::default();
+    timings.receive();
+    timings
+}
+
+fn transaction_from_grpc(update: SubscribeUpdateTransaction) -> anyhow::Result<TransactionUpdate> {
+    let info = update.transaction.context("transaction update without transaction")?;
+    let signature =
+        Signature::try_from(info.signature.as_slice()).map_err(|_| anyhow::anyhow!("invalid signature"))?;
+    let static_keys = info
+        .transaction
+        .and_then(|tx| tx.message)
+        .map(|message| message.account_keys)
+        .unwrap_or_default();
+    let (failed, logs, loaded) = match info.meta {
+        Some(meta) => (
+            meta.err.is_some(),
+            meta.log_messages,
+            [meta.loaded_writable_addresses, meta.loaded_readonly_addresses].concat(),
+        ),
+        None => (false, Vec::new(), Vec::new()),
+    };
+    let account_keys = static_keys
+        .iter()
+        .chain(&loaded)
+        .map(|key| Pubkey::try_from(key.as_slice()).map_err(|_| anyhow::anyhow!("invalid account key")))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(TransactionUpdate {
+        signature,
+        slot: update.slot,
+        account_keys,
+        is_vote: info.is_vote,
+        failed,
+        logs,
+    })
+}
+
+pub trait UpdateSource: Send {
+    /// Short label for logs
+    fn name(&self) -> &str;
+
+    /// Next normalized update, `Ok(None)` once the source is exhausted
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>>;
+}
+
+type GrpcSink = Pin<Box<dyn Sink<SubscribeRequest, Error = SendError> + Send>>;
+type GrpcStream = Pin<Box<dyn Stream<Item = Result<SubscribeUpdate, Status>> + Send>>;
+
+/// Yellowstone gRPC subscription. Keeps the connection alive with a ping every 3 s and can tee
+/// every raw message into a [`Recorder`]. Pongs and updates feed a [`HealthMonitor`].
+/// Requests published on [`GrpcSource::with_subscription`] are sent on the open stream.
+pub struct GrpcSource {
+    endpoint: String,
+    sink: GrpcSink,
+    stream: GrpcStream,
+    ping: Interval,
+    ping_id: i32,
+    // requests taken from the subscription or the ping timer but not handed to the sink yet
+    outgoing: VecDeque<SubscribeRequest>,
+    unflushed: bool,
+    recorder: Option<Recorder>,
+    health: HealthMonitor,
+    subscription: Option<watch::Receiver<SubscribeRequest>>,
+    metrics: Option<Metrics>,
+}
+
+impl GrpcSource {
+    pub async fn connect(
+        endpoint: impl Into<String>,
+        x_token: Option<String>,
+        request: SubscribeRequest,
+    ) -> anyhow::Result<Self> {
+        let endpoint = endpoint.into();
+        let mut builder = GeyserGrpcClient::build_from_shared(endpoint.clone())?.x_token(x_token)?;
+        if endpoint.starts_with("https") {
+            builder = builder.tls_config(ClientTlsConfig::new().with_native_roots())?;
+        }
+        let mut client = builder.connect().await?;
+        let (mut sink, stream) = client.subscribe().await?;
+        sink.send(request).await?;
+
+        let mut ping = interval(Duration::from_secs(3));
+        ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ping.reset();
+        Ok(Self {
+            endpoint,
+            sink: Box::pin(sink),
+            stream: Box::pin(stream),
+            ping,
+            ping_id: 0,
+            outgoing: VecDeque::new(),
+            unflushed: false,
+            recorder: None,
+            health: HealthMonitor::new(HealthConfig::default(), Instant::now()),
+            subscription: None,
+            metrics: None,
+        })
+    }
+
+    pub fn with_recorder(mut self, recorder: Recorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn with_health_config(mut self, config: HealthConfig) -> Self {
+        self.health = HealthMonitor::new(config, Instant::now());
+        self
+    }
+
+    /// Follows the requests of a [`super::SubscriptionController`]. The current one is sent right
+    /// away, in case it changed since `connect`.
+    pub fn with_subscription(mut self, mut requests: watch::Receiver<SubscribeRequest>) -> Self {
+        requests.mark_changed();
+        self.subscription = Some(requests);
+        self
+    }
+
+    /// Counts updates per matched filter and records their network latency (`created_at` →
+    /// receipt) under this endpoint
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Hands the recorder back, so a reconnected source can keep writing the same file
+    pub fn take_recorder(&mut self) -> Option<Recorder> {
+        self.recorder.take()
+    }
+
+    pub fn health(&self, now: Instant) -> HealthSnapshot {
+        self.health.snapshot(now)
+    }
+
+    /// Cancel safe: a request is queued in the same poll that takes it, and only leaves the
+    /// queue once the sink has accepted it, so dropping this future loses nothing.
+    async fn next_message(&mut self) -> anyhow::Result<Option<SubscribeUpdate>> {
+        loop {
+            self.send_outgoing().await?;
+            tokio::select! {
+                msg = self.stream.next() => {
+                    let Some(msg) = msg.transpose()? else {
+                        return Ok(None);
+                    };
+                    let now = Instant::now();
+                    self.health.on_message(now);
+                    if let Some(UpdateOneof::Pong(pong)) = &msg.update_oneof {
+                        self.health.on_pong(pong.id, now);
+                    }
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.record(&msg, SystemTime::now())?;
+                    }
+                    return Ok(Some(msg));
+                }
+                request = next_request(&mut self.subscription) => {
+                    self.outgoing.push_back(request);
+                }
+                _ = self.ping.tick() => {
+                    self.ping_id += 1;
+                    self.outgoing.push_back(SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: self.ping_id }),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    async fn send_outgoing(&mut self) -> anyhow::Result<()> {
+        // the request stays queued across the wait, popping and `start_send` happen together
+        while !self.outgoing.is_empty() {
+            future::poll_fn(|cx| self.sink.as_mut().poll_ready(cx)).await?;
+            let Some(request) = self.outgoing.pop_front() else { break };
+            let ping = request.ping.as_ref().map(|ping| ping.id);
+            self.sink.as_mut().start_send(request)?;
+            self.unflushed = true;
+            if let Some(id) = ping {
+                self.health.on_ping_sent(id, Instant::now());
+            }
+        }
+        if self.unflushed {
+            self.sink.flush().await?;
+            self.unflushed = false;
+        }
+        Ok(())
+    }
+}
+
+/// Next changed request, pending forever without a subscription or once its controller is gone
+async fn next_request(subscription: &mut Option<watch::Receiver<SubscribeRequest>>) -> SubscribeRequest {
+    if let Some(requests) = subscription {
+        if requests.changed().await.is_ok() {
+            return requests.borrow_and_update().clone();
+        }
+    }
+    std::future::pending().await
+}
+
+impl UpdateSource for GrpcSource {
+    fn name(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        async move {
+            while let Some(mut msg) = self.next_message().await? {
+                let mut timings = message_timings(&msg);
+                timings.receive();
+                let filters = std::mem::take(&mut msg.filters);
+                if let Some(mut update) = SourceUpdate::from_subscribe_update(msg)? {
+                    self.health.on_update(&update, Instant::now());
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_update(&self.endpoint, &filters);
+                        metrics.latency.record_receipt(&self.endpoint, &timings);
+                    }
+                    update.set_timings(timings);
+                    return Ok(Some(update));
+                }
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+/// Recording played back through [`Replay`], paced by its [`super::ReplaySpeed`]
+pub struct ReplaySource {
+    replay: Replay,
+}
+
+impl ReplaySource {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay }
+    }
+}
+
+impl UpdateSource for ReplaySource {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        async move {
+            while let Some(record) = self.replay.next().await {
+                let record = record?;
+                // network latency as it was when recorded, local stages measured now
+                let mut timings = message_timings(&record.update);
+                timings.receive();
+                timings.received_at = Some(record.received_at);
+                if let Some(mut update) = SourceUpdate::from_subscribe_update(record.update)? {
+                    update.set_timings(timings);
+                    return Ok(Some(update));
+                }
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+/// `slotsUpdatesSubscribe` notifications as the matching Yellowstone statuses. Only
+/// `CreatedBank` names the parent, the other notifications rely on it having been seen.
+impl From<RpcSlotUpdate> for SlotUpdate {
+    fn from(update: RpcSlotUpdate) -> Self {
+        let (parent, status) = match update {
+            RpcSlotUpdate::FirstShredReceived { .. } => (None, SlotStatus::FirstShredReceived),
+            RpcSlotUpdate::Completed { .. } => (None, SlotStatus::Completed),
+            RpcSlotUpdate::CreatedBank { parent, .. } => (Some(parent), SlotStatus::CreatedBank),
+            // a frozen bank is what the validator reports as processed
+            RpcSlotUpdate::Frozen { .. } => (None, SlotStatus::Processed),
+            RpcSlotUpdate::Dead { .. } => (None, SlotStatus::Dead),
+            RpcSlotUpdate::OptimisticConfirmation { .. } => (None, SlotStatus::Confirmed),
+            RpcSlotUpdate::Root { .. } => (None, SlotStatus::Finalized),
+        };
+        Self {
+            slot: update.slot(),
+            parent,
+            status,
+        }
+    }
+}
+
+/// Solana JSON-RPC WebSocket fallback.
+///
+/// The gRPC `SubscribeRequest` is translated into PubSub subscriptions: `owner` →
+/// `programSubscribe` (with memcmp/datasize/token-state filters), `account` → `accountSubscribe`,
+/// transaction `account_include` → one `logsSubscribe` per address and any slot filter →
+/// `slotsUpdatesSubscribe`. Each subscription runs in its own task feeding a shared channel. A
+/// transaction mentioning several watched addresses arrives once per address and is passed on
+/// the first time only.
+///
+/// `slotSubscribe` only reports processed slots, `slotsUpdatesSubscribe` also reports
+/// confirmation, rooting and dead slots, which [`crate::dex_processor::CommitmentTracker`]
+/// needs. The RPC node marks it unstable, some providers do not serve it.
+///
+/// PubSub cannot express everything a gRPC request can (lamports filters, owner and data filters
+/// on `accountSubscribe`, the vote and failed flags of transactions), so every update is
+/// re-checked against the request locally. Transactions are checked with the partial
+/// `account_keys` of [`TransactionUpdate::from_logs`], so `account_exclude` and
+/// `account_required` only see the mentioned address and the invoked programs.
+pub struct WebSocketSource {
+    url: String,
+    filters: FilterSet,
+    updates: mpsc::Receiver<anyhow::Result<SourceUpdate>>,
+    tasks: Vec<JoinHandle<()>>,
+    /// Slot of every signature passed on, forgotten [`DEDUP_SLOTS`] behind the newest
+    seen: HashMap<Signature, u64>,
+    newest_slot: u64,
+    pruned_at: u64,
+}
+
+impl WebSocketSource {
+    pub async fn connect(url: impl Into<String>, request: &SubscribeRequest) -> anyhow::Result<Self> {
+        let url = url.into();
+        let filters = FilterSet::try_from(request)?;
+        let client = Arc::new(PubsubClient::new(&url).await?);
+        let commitment = Some(match request.commitment.map(CommitmentLevel::try_from) {
+            Some(Ok(CommitmentLevel::Finalized)) => CommitmentConfig::finalized(),
+            Some(Ok(CommitmentLevel::Confirmed)) => CommitmentConfig::confirmed(),
+            _ => CommitmentConfig::processed(),
+        });
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment,
+            ..Default::default()
+        };
+        let (tx, updates) = mpsc::channel(1024);
+        let mut tasks = Vec::new();
+
+        for filter in request.accounts.values() {
+            let filters: Vec<_> = filter.filters.iter().filter_map(rpc_filter).collect();
+            for owner in &filter.owner {
+                let owner: Pubkey = owner.parse().context("invalid owner")?;
+                let config = RpcProgramAccountsConfig {
+                    filters: (!filters.is_empty()).then(|| filters.clone()),
+                    account_config: account_config.clone(),
+                    ..Default::default()
+                };
+                tasks.push(spawn_subscription(client.clone(), tx.clone(), move |client, tx| async move {
+                    let (mut stream, _unsubscribe) = client.program_subscribe(&owner, Some(config)).await?;
+                    while let Some(response) = stream.next().await {
+                        let pubkey: Pubkey = response.value.pubkey.parse().context("invalid pubkey")?;
+                        let update = account_from_ui(pubkey, response.context.slot, &response.value.account)?;
+                        if tx.send(Ok(update)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(())
+                }));
+            }
+            for account in &filter.account {
+                let pubkey: Pubkey = account.parse().context("invalid account")?;
+                let config = account_config.clone();
+                tasks.push(spawn_subscription(client.clone(), tx.clone(), move |client, tx| async move {
+                    let (mut stream, _unsubscribe) = client.account_subscribe(&pubkey, Some(config)).await?;
+                    while let Some(response) = stream.next().await {
+                        let update = account_from_ui(pubkey, response.context.slot, &response.value)?;
+                        if tx.send(Ok(update)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(())
+                }));
+            }
+        }
+
+        let mentions: BTreeSet<&String> = request
+            .transactions
+            .values()
+            .flat_map(|filter| &filter.account_include)
+            .collect();
+        for mention in mentions {
+            let mention: Pubkey = mention.parse().context("invalid account_include")?;
+            let config = RpcTransactionLogsConfig { commitment };
+            tasks.push(spawn_subscription(client.clone(), tx.clone(), move |client, tx| async move {
+                let filter = RpcTransactionLogsFilter::Mentions(vec![mention.to_string()]);
+                let (mut stream, _unsubscribe) = client.logs_subscribe(filter, config).await?;
+                while let Some(response) = stream.next().await {
+                    let update = SourceUpdate::Transaction(TransactionUpdate::from_logs(
+                        response.context.slot,
+                        mention,
+                        response.value,
+                    )?);
+                    if tx.send(Ok(update)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            }));
+        }
+
+        if !request.slots.is_empty() {
+            tasks.push(spawn_subscription(client.clone(), tx.clone(), |client, tx| async move {
+                let (mut stream, _unsubscribe) = client.slot_updates_subscribe().await?;
+                while let Some(update) = stream.next().await {
+                    if tx.send(Ok(SourceUpdate::Slot(update.into()))).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            }));
+        }
+
+        if tasks.is_empty() {
+            bail!("subscribe request has nothing to watch over WebSocket");
+        }
+        Ok(Self {
+            url,
+            filters,
+            updates,
+            tasks,
+            seen: HashMap::new(),
+            newest_slot: 0,
+            pruned_at: 0,
+        })
+    }
+
+    /// Whether `tx` is the first notification for its signature
+    fn first_arrival(&mut self, tx: &TransactionUpdate) -> bool {
+        if self.seen.insert(tx.signature, tx.slot).is_some() {
+            return false;
+        }
+        self.newest_slot = self.newest_slot.max(tx.slot);
+        if self.newest_slot >= self.pruned_at + DEDUP_SLOTS {
+            let oldest = self.newest_slot.saturating_sub(DEDUP_SLOTS);
+            self.seen.retain(|_, slot| *slot >= oldest);
+            self.pruned_at = self.newest_slot;
+        }
+        true
+    }
+}
+
+impl Drop for WebSocketSource {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+impl UpdateSource for WebSocketSource {
+    fn name(&self) -> &str {
+        &self.url
+    }
+
+    fn next_update(&mut self) -> BoxFuture<'_, anyhow::Result<Option<SourceUpdate>>> {
+        async move {
+            while let Some(update) = self.updates.recv().await.transpose()? {
+                if let SourceUpdate::Transaction(tx) = &update {
+                    if !self.first_arrival(tx) {
+                        continue;
+                    }
+                }
+                if !self.filters.matches(&update) {
+                    continue;
+                }
+                return Ok(Some(update));
+            }
+            Ok(None)
+        }
+        .boxed()
+    }
+}
+
+type UpdateSender = mpsc::Sender<anyhow::Result<SourceUpdate>>;
+
+/// Runs one PubSub subscription, a failure or a closed subscription is reported on the channel
+fn spawn_subscription<F, Fut>(client: Arc<PubsubClient>, tx: UpdateSender, run: F) -> JoinHandle<()>
+where
+    F: FnOnce(Arc<PubsubClient>, UpdateSender) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = anyhow::Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let result = run(client, tx.clone()).await;
+        let err = result.err().unwrap_or_else(|| anyhow::anyhow!("WebSocket subscription closed"));
+        if tx.send(Err(err)).await.is_err() {
+            warn!("WebSocket subscription ended after the source was dropped");
+        }
+    })
+}
+
+fn account_from_ui(
+    pubkey: Pubkey,
+    slot: u64,
+    account: &solana_account_decoder::UiAccount,
+) -> anyhow::Result<SourceUpdate> {
+    Ok(SourceUpdate::Account(AccountUpdate {
+        pubkey,
+        owner: account.owner.parse().context("invalid owner")?,
+        slot,
+        // PubSub has no write version, (pubkey, slot) is the finest ordering available
+        write_version: 0,
+        lamports: account.lamports,
+        data: account.data.decode().context("account data is not base64")?,
+        timings: received_now(),
+    }))
+}
+
+/// Server-side equivalent of a gRPC data filter, `None` when PubSub has none.
+/// Filters are validated by [`FilterSet`] before this runs.
+fn rpc_filter(filter: &SubscribeRequestFilterAccountsFilter) -> Option<RpcFilterType> {
+    Some(match filter.filter.as_ref()? {
+        AccountFilterKind::Memcmp(memcmp) => {
+            let bytes = match memcmp.data.clone()? {
+                MemcmpData::Bytes(bytes) => MemcmpEncodedBytes::Bytes(bytes),
+                MemcmpData::Base58(s) => MemcmpEncodedBytes::Base58(s),
+                MemcmpData::Base64(s) => MemcmpEncodedBytes::Base64(s),
+            };
+            RpcFilterType::Memcmp(Memcmp::new(memcmp.offset as usize, bytes))
+        }
+        AccountFilterKind::Datasize(size) => RpcFilterType::DataSize(*size),
+        AccountFilterKind::TokenAccountState(_) => RpcFilterType::TokenAccountState,
+        AccountFilterKind::Lamports(_) => return None,
+    })
+}
@@ -0,0 +1,110 @@
+//! Subscription changes at runtime.
+//!
+//! Yellowstone replaces the filters of a `Subscribe` stream whenever a new `SubscribeRequest`
+//! arrives on its sink, so watching another account does not need a reconnect.
+//! [`SubscriptionController`] owns the base request plus the accounts added at runtime and
+//! publishes the merged request; every [`GrpcSource`](super::GrpcSource) given a receiver sends
+//! it on its existing stream.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use solana_program::pubkey::Pubkey;
+use tokio::sync::watch;
+use yellowstone_grpc_proto::prelude::SubscribeRequest;
+
+struct Inner {
+    base: SubscribeRequest,
+    // filter name → accounts, each name becomes one `accounts` entry of the request
+    watched: Mutex<BTreeMap<String, BTreeSet<Pubkey>>>,
+    requests: watch::Sender<SubscribeRequest>,
+}
+
+/// Cloneable handle shared by everything that wants to add or remove watched accounts
+#[derive(Clone)]
+pub struct SubscriptionController {
+    inner: Arc<Inner>,
+}
+
+impl SubscriptionController {
+    pub fn new(base: SubscribeRequest) -> Self {
+        let (requests, _) = watch::channel(base.clone());
+        Self {
+            inner: Arc::new(Inner {
+                base,
+                watched: Mutex::new(BTreeMap::new()),
+                requests,
+            }),
+        }
+    }
+
+    /// Current merged request
+    pub fn request(&self) -> SubscribeRequest {
+        self.inner.requests.borrow().clone()
+    }
+
+    /// Receiver that sees every merged request, for [`GrpcSource::with_subscription`](super::GrpcSource::with_subscription)
+    pub fn subscribe(&self) -> watch::Receiver<SubscribeRequest> {
+        self.inner.requests.subscribe()
+    }
+
+    /// Adds `accounts` to the `filter` entry, `false` when they were all watched already
+    pub fn watch(&self, filter: &str, accounts: impl IntoIterator<Item = Pubkey>) -> bool {
+        self.update(|watched| {
+            let set = watched.entry(filter.to_string()).or_default();
+            let mut changed = false;
+            for account in accounts {
+                changed |= set.insert(account);
+            }
+            changed
+        })
+    }
+
+    /// Removes `accounts` from the `filter` entry, the entry goes away once it is empty
+    pub fn unwatch(&self, filter: &str, accounts: impl IntoIterator<Item = Pubkey>) -> bool {
+        self.update(|watched| {
+            let Some(set) = watched.get_mut(filter) else {
+                return false;
+            };
+            let mut changed = false;
+            for account in accounts {
+                changed |= set.remove(&account);
+            }
+            if set.is_empty() {
+                watched.remove(filter);
+            }
+            changed
+        })
+    }
+
+    pub fn watched(&self, filter: &str) -> Vec<Pubkey> {
+        let watched = self.inner.watched.lock().unwrap();
+        watched.get(filter).map(|set| set.iter().copied().collect()).unwrap_or_default()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BTreeMap<String, BTreeSet<Pubkey>>) -> bool) -> bool {
+        let mut watched = self.inner.watched.lock().unwrap();
+        if !f(&mut watched) {
+            return false;
+        }
+        // publish under the lock so concurrent changes cannot overtake each other
+        self.inner.requests.send_replace(merge(&self.inner.base, &watched));
+        true
+    }
+}
+
+fn merge(base: &SubscribeRequest, watched: &BTreeMap<String, BTreeSet<Pubkey>>) -> SubscribeRequest {
+    let mut request = base.clone();
+    for (filter, accounts) in watched {
+        let entry = request.accounts.entry(filter.clone()).or_default();
+        for account in accounts {
+            let account = account.to_string();
+            if !entry.account.contains(&account) {
+                entry.account.push(account);
+            }
+        }
+    }
+    request
+}
@@ -0,0 +1,92 @@
+//! Fuzz entry points, shared by the cargo-fuzz targets in `fuzz/` and the deterministic corpus
+//! run in `tests/fuzz_corpus.rs`. Each one takes arbitrary bytes and only panics on a bug: a
+//! decoder panicking on input it should reject, or a decoded value that does not survive
+//! encode → decode.
+
+use std::io::Cursor;
+
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prost::Message};
+
+use crate::common::view::AccountView;
+use crate::dex::{
+    meteora_dlmm::MeteoraLbPairView,
+    raydium_clmm::RaydiumClmmPoolView,
+    raydium_cpmm::RaydiumCpPoolView,
+    raydium_lp_v4::RaydiumLpV4Layout,
+    spl_token::{read_token_account_amount, SplTokenAccountView},
+};
+use crate::dex_processor::{decode::LAYOUTS, AccountUpdate, DecodedAccount};
+use crate::instruction::{decoder::LIQUIDITY_STATE_LAYOUT_V4, instruction::AmmInstruction};
+use crate::server::BotEvent;
+use crate::stream::{RecordingReader, SourceUpdate};
+
+/// `data` as account data, through every layout decoder and zero-copy view
+pub fn account_layouts(data: &[u8]) {
+    for (layout, ..) in LAYOUTS {
+        let Some(decoded) = DecodedAccount::decode_layout(layout, data) else {
+            continue;
+        };
+        let _ = decoded.pool_tokens();
+        let again = DecodedAccount::decode_layout(layout, &decoded.encode());
+        assert_eq!(again.as_ref(), Some(&decoded), "{} does not round-trip", layout);
+    }
+
+    if let Some(keys) = RaydiumLpV4Layout::try_from_slice_manual(data) {
+        assert_eq!(RaydiumLpV4Layout::try_from_slice_manual(&keys.to_bytes()), Some(keys));
+    }
+    let mut input = data;
+    loop {
+        let before = input;
+        let Ok(state) = LIQUIDITY_STATE_LAYOUT_V4::decode(&mut input) else {
+            break;
+        };
+        assert_eq!(state.to_bytes(), &before[..before.len() - input.len()]);
+    }
+
+    let _ = read_token_account_amount(data);
+    let _ = SplTokenAccountView::view(data);
+    let _ = RaydiumCpPoolView::view(data);
+    let _ = RaydiumClmmPoolView::view(data);
+    let _ = MeteoraLbPairView::view(data);
+}
+
+/// `data` as Raydium AMM instruction data. Unpacking ignores trailing bytes, so whatever packs
+/// again must be a prefix of the input.
+pub fn amm_instruction(data: &[u8]) {
+    let Ok(instruction) = AmmInstruction::unpack(data) else {
+        return;
+    };
+    if let Ok(packed) = instruction.pack() {
+        assert!(data.starts_with(&packed), "{:?} packs to {:?}", instruction, packed);
+        assert_eq!(AmmInstruction::unpack(&packed), Ok(instruction));
+    }
+}
+
+/// `data` as a protobuf Geyser message, normalized and handed to the account and swap decoders
+pub fn subscribe_update(data: &[u8]) {
+    let Ok(update) = SubscribeUpdate::decode(data) else {
+        return;
+    };
+    let _ = AccountUpdate::from_subscribe_update(update.clone());
+    match SourceUpdate::from_subscribe_update(update) {
+        Ok(Some(SourceUpdate::Account(account))) => {
+            let _ = DecodedAccount::decode(&account.owner, &account.data);
+        }
+        Ok(Some(SourceUpdate::Transaction(tx))) => {
+            let _ = BotEvent::swap(&tx);
+        }
+        _ => {}
+    }
+}
+
+/// `data` as a recording file, read until the first error
+pub fn recording(data: &[u8]) {
+    let Ok(reader) = RecordingReader::new(Box::new(Cursor::new(data.to_vec()))) else {
+        return;
+    };
+    for record in reader {
+        if record.is_err() {
+            break;
+        }
+    }
+}
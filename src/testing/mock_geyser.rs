@@ -0,0 +1,334 @@
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{Stream, StreamExt};
+use solana_program::pubkey::Pubkey;
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, mpsc, watch},
+    task::JoinHandle,
+};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use yellowstone_grpc_proto::{
+    geyser::{
+        geyser_server::{Geyser, GeyserServer},
+        subscribe_update::UpdateOneof,
+    },
+    prelude::{
+        GetBlockHeightRequest, GetBlockHeightResponse, GetLatestBlockhashRequest,
+        GetLatestBlockhashResponse, GetSlotRequest, GetSlotResponse, GetVersionRequest,
+        GetVersionResponse, IsBlockhashValidRequest, IsBlockhashValidResponse, Message,
+        PingRequest, PongResponse, SubscribeRequest, SubscribeUpdate, SubscribeUpdateAccount,
+        SubscribeUpdateAccountInfo, SubscribeUpdatePong, SubscribeUpdateSlot, SubscribeUpdateTransaction,
+        SubscribeUpdateTransactionInfo, Transaction, TransactionError, TransactionStatusMeta,
+    },
+};
+
+use crate::stream::{FilterSet, SlotStatus, SourceUpdate};
+
+/// Local Yellowstone `Subscribe` endpoint fed by [`MockGeyserServer::push`].
+///
+/// Each subscriber only receives the pushed updates that match its latest `SubscribeRequest`,
+/// evaluated with [`FilterSet`], with `filters` set to the names of the matching filters like
+/// the real plugin does.
+/// Pings are answered with a pong on the same stream.
+pub struct MockGeyserServer {
+    addr: SocketAddr,
+    updates: broadcast::Sender<SubscribeUpdate>,
+    subscribers: watch::Receiver<usize>,
+    requests: watch::Receiver<usize>,
+    closed: watch::Sender<bool>,
+    task: JoinHandle<Result<(), tonic::transport::Error>>,
+}
+
+impl MockGeyserServer {
+    /// Binds an ephemeral port on localhost and starts serving
+    pub async fn start() -> anyhow::Result<Self> {
+        Self::start_at(SocketAddr::from(([127, 0, 0, 1], 0))).await
+    }
+
+    /// Serves on `addr`, e.g. the address of a server that was shut down to simulate a restart
+    pub async fn start_at(addr: SocketAddr) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let addr = listener.local_addr()?;
+        let (updates, _) = broadcast::channel(1024);
+        let (subscribers_tx, subscribers) = watch::channel(0);
+        let (requests_tx, requests) = watch::channel(0);
+        let (closed, closed_rx) = watch::channel(false);
+
+        let service = MockGeyser {
+            updates: updates.clone(),
+            subscribers: Arc::new(subscribers_tx),
+            requests: Arc::new(requests_tx),
+            closed: closed_rx,
+        };
+        let task = tokio::spawn(
+            Server::builder()
+                .add_service(GeyserServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        Ok(Self {
+            addr,
+            updates,
+            subscribers,
+            requests,
+            closed,
+            task,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Plain-text endpoint for `GeyserGrpcClient::build_from_shared`
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Sends an update to every current subscriber whose filters match it
+    pub fn push(&self, update: SubscribeUpdate) {
+        // no receivers only means nobody subscribed yet
+        let _ = self.updates.send(update);
+    }
+
+    /// Waits until `count` clients have sent their first `SubscribeRequest`.
+    /// Updates pushed before that are not seen by those clients.
+    pub async fn wait_for_subscribers(&mut self, count: usize, timeout: Duration) -> anyhow::Result<()> {
+        tokio::time::timeout(timeout, self.subscribers.wait_for(|n| *n >= count))
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out waiting for {} subscribers", count))??;
+        Ok(())
+    }
+
+    /// Waits until `count` valid filter requests (first or replacement, pings excluded) have
+    /// been applied across all clients
+    pub async fn wait_for_requests(&mut self, count: usize, timeout: Duration) -> anyhow::Result<()> {
+        tokio::time::timeout(timeout, self.requests.wait_for(|n| *n >= count))
+            .await
+            .map_err(|_| anyhow::anyhow!("timed out waiting for {} requests", count))??;
+        Ok(())
+    }
+
+    /// Ends every open `Subscribe` stream and stops accepting connections.
+    /// Clients see their stream finish cleanly, like after a server restart.
+    pub fn shutdown(self) {}
+}
+
+impl Drop for MockGeyserServer {
+    fn drop(&mut self) {
+        self.closed.send_replace(true);
+        self.task.abort();
+    }
+}
+
+/// Scripted account write, as the plugin would send it
+pub fn account_update(pubkey: &Pubkey, owner: &Pubkey, slot: u64, lamports: u64, data: Vec<u8>) -> SubscribeUpdate {
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: pubkey.to_bytes().to_vec(),
+                lamports,
+                owner: owner.to_bytes().to_vec(),
+                data,
+                write_version: slot,
+                ..Default::default()
+            }),
+            slot,
+            is_startup: false,
+        })),
+        ..Default::default()
+    }
+}
+
+/// Scripted transaction touching `account_keys`, `failed` sets a non-empty status error
+pub fn transaction_update(slot: u64, signature: [u8; 64], account_keys: &[Pubkey], failed: bool) -> SubscribeUpdate {
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: signature.to_vec(),
+                is_vote: false,
+                transaction: Some(Transaction {
+                    signatures: vec![signature.to_vec()],
+                    message: Some(Message {
+                        account_keys: account_keys.iter().map(|k| k.to_bytes().to_vec()).collect(),
+                        ..Default::default()
+                    }),
+                }),
+                meta: Some(TransactionStatusMeta {
+                    err: failed.then(|| TransactionError { err: vec![1] }),
+                    ..Default::default()
+                }),
+                index: 0,
+            }),
+            slot,
+        })),
+        ..Default::default()
+    }
+}
+
+/// Scripted slot status change
+pub fn slot_update(slot: u64, parent: Option<u64>, status: SlotStatus) -> SubscribeUpdate {
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+            slot,
+            parent,
+            status: status as i32,
+            dead_error: None,
+        })),
+        ..Default::default()
+    }
+}
+
+struct MockGeyser {
+    updates: broadcast::Sender<SubscribeUpdate>,
+    subscribers: Arc<watch::Sender<usize>>,
+    requests: Arc<watch::Sender<usize>>,
+    closed: watch::Receiver<bool>,
+}
+
+async fn wait_closed(mut closed: watch::Receiver<bool>) {
+    // a dropped sender means the server handle is gone, which closes everything as well
+    let _ = closed.wait_for(|closed| *closed).await;
+}
+
+/// Names of the filters `update` matches, `None` when it should not be sent
+fn matching_filters(filters: &FilterSet, update: &SubscribeUpdate) -> Option<Vec<String>> {
+    let update = SourceUpdate::from_subscribe_update(update.clone()).ok()??;
+    let names = filters.matching(&update);
+    (!names.is_empty()).then(|| names.into_iter().map(String::from).collect())
+}
+
+type UpdateStream = Pin<Box<dyn Stream<Item = Result<SubscribeUpdate, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Geyser for MockGeyser {
+    type SubscribeStream = UpdateStream;
+
+    async fn subscribe(
+        &self,
+        request: Request<Streaming<SubscribeRequest>>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let mut requests = request.into_inner();
+        let mut updates = self.updates.subscribe();
+        let subscribers = self.subscribers.clone();
+        let applied = self.requests.clone();
+        let (tx, rx) = mpsc::channel(1024);
+        let subscription = Arc::new(Mutex::new(None::<FilterSet>));
+
+        // client → server: filter changes and pings
+        let requests_tx = tx.clone();
+        let requests_subscription = subscription.clone();
+        let requests_closed = self.closed.clone();
+        tokio::spawn(async move {
+            loop {
+                let request = tokio::select! {
+                    request = requests.next() => request,
+                    _ = wait_closed(requests_closed.clone()) => break,
+                };
+                let request = match request {
+                    Some(Ok(request)) => request,
+                    _ => break,
+                };
+                if let Some(ping) = request.ping {
+                    let pong = SubscribeUpdate {
+                        update_oneof: Some(UpdateOneof::Pong(SubscribeUpdatePong { id: ping.id })),
+                        ..Default::default()
+                    };
+                    if requests_tx.send(Ok(pong)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                match FilterSet::try_from(&request) {
+                    Ok(new) => {
+                        let first = requests_subscription.lock().unwrap().replace(new).is_none();
+                        if first {
+                            subscribers.send_modify(|n| *n += 1);
+                        }
+                        applied.send_modify(|n| *n += 1);
+                    }
+                    Err(err) => {
+                        let _ = requests_tx.send(Err(Status::invalid_argument(err.to_string()))).await;
+                        break;
+                    }
+                }
+            }
+        });
+
+        // server → client: pushed updates that pass the current filters
+        let closed = self.closed.clone();
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    received = updates.recv() => received,
+                    _ = wait_closed(closed.clone()) => break,
+                };
+                let mut update = match received {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let names = match subscription.lock().unwrap().as_ref() {
+                    Some(filters) => matching_filters(filters, &update),
+                    None => None,
+                };
+                let Some(names) = names else {
+                    continue;
+                };
+                update.filters = names;
+                if tx.send(Ok(update)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+        Ok(Response::new(PongResponse {
+            count: request.into_inner().count,
+        }))
+    }
+
+    async fn get_latest_blockhash(
+        &self,
+        _request: Request<GetLatestBlockhashRequest>,
+    ) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+        Err(Status::unimplemented("mock server only streams updates"))
+    }
+
+    async fn get_block_height(
+        &self,
+        _request: Request<GetBlockHeightRequest>,
+    ) -> Result<Response<GetBlockHeightResponse>, Status> {
+        Err(Status::unimplemented("mock server only streams updates"))
+    }
+
+    async fn get_slot(&self, _request: Request<GetSlotRequest>) -> Result<Response<GetSlotResponse>, Status> {
+        Err(Status::unimplemented("mock server only streams updates"))
+    }
+
+    async fn is_blockhash_valid(
+        &self,
+        _request: Request<IsBlockhashValidRequest>,
+    ) -> Result<Response<IsBlockhashValidResponse>, Status> {
+        Err(Status::unimplemented("mock server only streams updates"))
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<GetVersionRequest>,
+    ) -> Result<Response<GetVersionResponse>, Status> {
+        Ok(Response::new(GetVersionResponse {
+            version: "mock".into(),
+        }))
+    }
+}
@@ -0,0 +1,9 @@
+//! Test support: an in-process Yellowstone endpoint so the subscribe → decode → handler path
+//! can run without network access, and the entry points the fuzz targets drive. Only built for
+//! tests and with the `testing` feature.
+
+pub mod fuzz;
+pub mod mock_geyser;
+
+pub use mock_geyser::{account_update, slot_update, transaction_update, MockGeyserServer};
+pub use crate::common::snapshot::Snapshot;
@@ -0,0 +1,162 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use solana_client::rpc_response::{SlotTransactionStats, SlotUpdate as RpcSlotUpdate};
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::{
+    dex_processor::{AccountUpdate, CommitmentTracker, JsonLinesSink, Pipeline, RetractReason},
+    stream::{SlotStatus, SlotUpdate},
+};
+
+fn slot(slot: u64, parent: u64, status: SlotStatus) -> SlotUpdate {
+    SlotUpdate {
+        slot,
+        parent: Some(parent),
+        status,
+    }
+}
+
+fn write(pubkey: Pubkey, slot: u64, lamports: u64) -> AccountUpdate {
+    AccountUpdate {
+        pubkey,
+        slot,
+        write_version: slot,
+        lamports,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn dead_slot_rolls_back_its_fork_to_the_confirmed_state() {
+    let pool = Pubkey::new_unique();
+    let mut tracker = CommitmentTracker::new();
+    tracker.on_slot(&slot(100, 99, SlotStatus::Processed));
+    assert!(tracker.on_account(&write(pool, 100, 1)));
+    assert!(tracker.on_slot(&slot(100, 99, SlotStatus::Confirmed)).is_empty());
+    assert_eq!(tracker.confirmed(&pool).unwrap().lamports, 1);
+
+    // 101 ← 102 both get written, then 101 dies
+    tracker.on_slot(&slot(101, 100, SlotStatus::Processed));
+    tracker.on_account(&write(pool, 101, 2));
+    tracker.on_slot(&slot(102, 101, SlotStatus::Processed));
+    tracker.on_account(&write(pool, 102, 3));
+    assert_eq!(tracker.latest(&pool).unwrap().lamports, 3);
+
+    let retractions = tracker.on_slot(&slot(101, 100, SlotStatus::Dead));
+    assert_eq!(retractions.len(), 2);
+    assert!(retractions.iter().all(|r| r.reason == RetractReason::Dead));
+    assert_eq!(retractions.iter().map(|r| r.update.slot).collect::<Vec<_>>(), vec![101, 102]);
+    assert_eq!(retractions[0].restored.as_ref().unwrap().lamports, 1);
+    assert_eq!(tracker.latest(&pool).unwrap().lamports, 1);
+    assert_eq!(tracker.status(102), Some(SlotStatus::Dead));
+
+    // a write for the dead fork arriving late is refused
+    assert!(!tracker.on_account(&write(pool, 102, 4)));
+}
+
+#[test]
+fn confirming_a_sibling_retracts_the_skipped_fork() {
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut tracker = CommitmentTracker::new();
+    tracker.on_slot(&slot(200, 199, SlotStatus::Confirmed));
+    // 201 and 202 both build on 200, only 202 makes it
+    tracker.on_slot(&slot(201, 200, SlotStatus::Processed));
+    tracker.on_account(&write(a, 201, 1));
+    tracker.on_slot(&slot(202, 200, SlotStatus::Processed));
+    tracker.on_account(&write(b, 202, 2));
+    tracker.on_slot(&slot(203, 202, SlotStatus::Processed));
+    tracker.on_account(&write(b, 203, 3));
+
+    let retractions = tracker.on_slot(&slot(203, 202, SlotStatus::Confirmed));
+    assert_eq!(retractions.len(), 1);
+    assert_eq!(retractions[0].update.pubkey, a);
+    assert_eq!(retractions[0].reason, RetractReason::Skipped);
+    assert!(retractions[0].restored.is_none());
+    assert_eq!(tracker.confirmed(&b).unwrap().lamports, 3);
+    assert_eq!(tracker.status(202), Some(SlotStatus::Confirmed));
+    assert_eq!(tracker.confirmed_slot(), 203);
+
+    // a late processed notification does not downgrade, finalizing forgets older slots
+    tracker.on_slot(&slot(203, 202, SlotStatus::Processed));
+    assert_eq!(tracker.status(203), Some(SlotStatus::Confirmed));
+    assert!(tracker.on_slot(&slot(203, 202, SlotStatus::Finalized)).is_empty());
+    assert_eq!(tracker.finalized_slot(), 203);
+    assert_eq!(tracker.status(201), None);
+}
+
+#[test]
+fn websocket_slot_updates_drive_the_tracker() {
+    let pool = Pubkey::new_unique();
+    let mut tracker = CommitmentTracker::new();
+
+    // 401 and 402 both build on 400, only 402 is confirmed
+    tracker.on_slot(&RpcSlotUpdate::CreatedBank { slot: 401, parent: 400, timestamp: 0 }.into());
+    tracker.on_slot(&RpcSlotUpdate::CreatedBank { slot: 402, parent: 400, timestamp: 0 }.into());
+    tracker.on_account(&write(pool, 401, 1));
+    let frozen: SlotUpdate = RpcSlotUpdate::Frozen {
+        slot: 401,
+        timestamp: 0,
+        stats: SlotTransactionStats {
+            num_transaction_entries: 0,
+            num_successful_transactions: 0,
+            num_failed_transactions: 0,
+            max_transactions_per_entry: 0,
+        },
+    }
+    .into();
+    assert_eq!((frozen.parent, frozen.status), (None, SlotStatus::Processed));
+    tracker.on_slot(&frozen);
+
+    let retractions = tracker.on_slot(&RpcSlotUpdate::OptimisticConfirmation { slot: 402, timestamp: 0 }.into());
+    assert_eq!(retractions.len(), 1);
+    assert_eq!(retractions[0].reason, RetractReason::Skipped);
+    assert_eq!(tracker.status(402), Some(SlotStatus::Confirmed));
+
+    tracker.on_slot(&RpcSlotUpdate::Root { slot: 402, timestamp: 0 }.into());
+    assert_eq!(tracker.finalized_slot(), 402);
+
+    tracker.on_slot(&RpcSlotUpdate::CreatedBank { slot: 403, parent: 402, timestamp: 0 }.into());
+    let dead = RpcSlotUpdate::Dead {
+        slot: 403,
+        timestamp: 0,
+        err: "shred insert failed".to_string(),
+    };
+    tracker.on_slot(&dead.into());
+    assert_eq!(tracker.status(403), Some(SlotStatus::Dead));
+}
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn retractions_reach_the_handlers() {
+    let buffer = SharedBuffer::default();
+    let mut pipeline = Pipeline::new().with_handler(JsonLinesSink::new(buffer.clone()));
+    let pool = Pubkey::new_unique();
+    let mut tracker = CommitmentTracker::new();
+    tracker.on_account(&write(pool, 300, 1));
+    for retraction in tracker.on_slot(&slot(300, 299, SlotStatus::Dead)) {
+        pipeline.retract(&retraction).unwrap();
+    }
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+    assert_eq!(line["kind"], "retraction");
+    assert_eq!(line["pubkey"], pool.to_string());
+    assert_eq!(line["slot"], 300);
+    assert_eq!(line["reason"], "dead");
+    assert!(line["restored_slot"].is_null());
+}
@@ -0,0 +1,132 @@
+use proptest::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::{
+    dex::{
+        meteora_dlmm::{Bin, BinArrayLayout, OracleLayout, METEORA_DLMM_BINS_PER_ARRAY, METEORA_DLMM_ORACLE_DISCRIMINATOR},
+        raydium_cpmm::{RaydiumCpLayout, RAYDIUM_CP_POOL_DISCRIMINATOR},
+        raydium_lp_v4::RaydiumLpV4Layout,
+        spl_token::{read_token_account_amount, SplTokenAccountLayout},
+        Dex,
+    },
+    dex_processor::{
+        decode::{layout_discriminator, LAYOUTS},
+        DecodedAccount,
+    },
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+};
+
+proptest! {
+    /// Every field value a decoder can produce is reachable from some bytes, so decoding random
+    /// bytes behind a valid discriminator covers the whole value space of each layout
+    #[test]
+    fn decode_inverts_encode(index in 0..LAYOUTS.len(), seed in prop::collection::vec(any::<u8>(), 16_384)) {
+        let (layout, _, size) = LAYOUTS[index];
+        let mut seed = seed[..size].to_vec();
+        if let Some(discriminator) = layout_discriminator(layout) {
+            seed[..8].copy_from_slice(&discriminator);
+        }
+        let value = DecodedAccount::decode_layout(layout, &seed).unwrap();
+        let bytes = value.encode();
+        prop_assert_eq!(bytes.len(), size);
+        prop_assert_eq!(DecodedAccount::decode_layout(layout, &bytes), Some(value));
+    }
+
+    #[test]
+    fn amm_v4_encodes_every_byte(seed in prop::collection::vec(any::<u8>(), 752)) {
+        let state = *LIQUIDITY_STATE_LAYOUT_V4::from_bytes(&seed).unwrap();
+        prop_assert_eq!(state.to_bytes(), seed);
+    }
+
+    #[test]
+    fn lp_v4_keys_round_trip(keys in prop::array::uniform9(any::<[u8; 32]>())) {
+        let [base_vault, quote_vault, base_mint, quote_mint, lp_mint, open_orders, market, market_program, target] =
+            keys.map(Pubkey::new_from_array);
+        let layout = RaydiumLpV4Layout {
+            baseVault: base_vault,
+            quoteVault: quote_vault,
+            baseMint: base_mint,
+            quoteMint: quote_mint,
+            lpMint: lp_mint,
+            openOrders: open_orders,
+            marketId: market,
+            marketProgramId: market_program,
+            targetOrders: target,
+        };
+        let bytes = layout.to_bytes();
+        prop_assert_eq!(RaydiumLpV4Layout::try_from_slice_manual(&bytes).unwrap(), layout.clone());
+        let state = LIQUIDITY_STATE_LAYOUT_V4::from_bytes(&bytes).unwrap();
+        prop_assert_eq!(RaydiumLpV4Layout::from(state), layout);
+    }
+}
+
+#[test]
+fn builds_a_synthetic_cpmm_pool_with_its_vaults() {
+    let (mint0, mint1) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (vault0, vault1) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let pool = RaydiumCpLayout {
+        token0Vault: vault0,
+        token1Vault: vault1,
+        token0Mint: mint0,
+        token1Mint: mint1,
+        mint0Decimals: 9,
+        mint1Decimals: 6,
+        status: 0,
+        openTime: 1_718_000_000,
+        ..Default::default()
+    };
+    let data = pool.to_bytes();
+    assert_eq!(data[..8], RAYDIUM_CP_POOL_DISCRIMINATOR);
+
+    let decoded = DecodedAccount::decode(&Dex::RaydiumCpmm.program_id(), &data).unwrap();
+    let tokens = decoded.pool_tokens().unwrap();
+    assert_eq!((tokens.base_mint, tokens.quote_vault), (mint0, vault1));
+
+    let reserve = SplTokenAccountLayout {
+        mint: mint0,
+        owner: Pubkey::new_unique(),
+        amount: 5_000_000_000,
+        state: 1,
+        ..Default::default()
+    };
+    let vault = reserve.to_bytes();
+    assert_eq!(read_token_account_amount(&vault), Some(5_000_000_000));
+    assert_eq!(SplTokenAccountLayout::try_from_slice_manual(&vault).unwrap(), reserve);
+}
+
+/// Layouts that keep the discriminator as a field still encode the Anchor one, like the layouts
+/// that do not keep it
+#[test]
+fn encoders_write_the_anchor_discriminator() {
+    for discriminator in [0, u64::MAX] {
+        let pool = RaydiumCpLayout {
+            discriminator,
+            ..Default::default()
+        };
+        assert_eq!(pool.to_bytes()[..8], RAYDIUM_CP_POOL_DISCRIMINATOR);
+        let oracle = OracleLayout {
+            discriminator,
+            ..Default::default()
+        };
+        assert_eq!(oracle.to_bytes()[..8], METEORA_DLMM_ORACLE_DISCRIMINATOR);
+    }
+}
+
+#[test]
+fn short_bin_arrays_are_zero_filled() {
+    let bin = Bin {
+        amount_x: 1,
+        price: 1 << 64,
+        ..Default::default()
+    };
+    let layout = BinArrayLayout {
+        index: -3,
+        bins: vec![bin.clone()],
+        ..Default::default()
+    };
+    let decoded = BinArrayLayout::try_from_slice_manual(&layout.to_bytes()).unwrap();
+    assert_eq!(decoded.index, -3);
+    assert_eq!(decoded.bins.len(), METEORA_DLMM_BINS_PER_ARRAY);
+    assert_eq!(decoded.bins[0], bin);
+    assert!(decoded.bins[1..].iter().all(|b| *b == Bin::default()));
+}
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use solana_program::pubkey::Pubkey;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    metrics::TxOutcome,
+    server::{BotEvent, ClientMessage, EventBus, EventFilter, EventServer, ServerMessage, Topic},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(server: &EventServer) -> Client {
+    let (client, _) = connect_async(format!("ws://{}", server.addr())).await.unwrap();
+    client
+}
+
+async fn send(client: &mut Client, message: &ClientMessage) {
+    client
+        .send(Message::Text(serde_json::to_string(message).unwrap()))
+        .await
+        .unwrap();
+}
+
+async fn receive(client: &mut Client) -> ServerMessage {
+    loop {
+        let message = tokio::time::timeout(TIMEOUT, client.next()).await.unwrap().unwrap().unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+fn new_pool(dex: Dex, slot: u64) -> BotEvent {
+    BotEvent::NewPools {
+        dex,
+        pool: Pubkey::new_unique(),
+        slot,
+        tokens: None,
+    }
+}
+
+#[tokio::test]
+async fn subscriptions_receive_matching_events_only() {
+    let bus = EventBus::default();
+    let server = EventServer::start("127.0.0.1:0".parse().unwrap(), bus.clone()).await.unwrap();
+    let mut client = connect(&server).await;
+
+    send(
+        &mut client,
+        &ClientMessage::Subscribe {
+            id: "amm".into(),
+            topic: Topic::NewPools,
+            filter: EventFilter {
+                dex: Some(vec![Dex::RaydiumAmmV4]),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+    assert_eq!(receive(&mut client).await, ServerMessage::Subscribed { id: "amm".into() });
+
+    // other dex and other topic are filtered out, the last event is the first one delivered
+    bus.publish(new_pool(Dex::RaydiumCpmm, 1));
+    bus.publish(BotEvent::trade(Some(Dex::RaydiumAmmV4), None, TxOutcome::Sent, Some(2)));
+    let wanted = new_pool(Dex::RaydiumAmmV4, 3);
+    bus.publish(wanted.clone());
+    assert_eq!(
+        receive(&mut client).await,
+        ServerMessage::Event {
+            subscription: "amm".into(),
+            event: Box::new(wanted),
+        }
+    );
+
+    send(&mut client, &ClientMessage::Unsubscribe { id: "amm".into() }).await;
+    assert_eq!(receive(&mut client).await, ServerMessage::Unsubscribed { id: "amm".into() });
+    send(&mut client, &ClientMessage::Unsubscribe { id: "amm".into() }).await;
+    assert!(matches!(receive(&mut client).await, ServerMessage::Error { .. }));
+}
+
+#[tokio::test]
+async fn invalid_requests_get_an_error() {
+    let bus = EventBus::default();
+    let server = EventServer::start("127.0.0.1:0".parse().unwrap(), bus).await.unwrap();
+    let mut client = connect(&server).await;
+
+    client
+        .send(Message::Text(r#"{"op":"subscribe","id":"x","topic":"nope"}"#.into()))
+        .await
+        .unwrap();
+    match receive(&mut client).await {
+        ServerMessage::Error { message } => assert!(message.starts_with("invalid request"), "{}", message),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // the connection stays usable
+    send(
+        &mut client,
+        &ClientMessage::Subscribe {
+            id: "x".into(),
+            topic: Topic::Swaps,
+            filter: EventFilter::default(),
+        },
+    )
+    .await;
+    assert_eq!(receive(&mut client).await, ServerMessage::Subscribed { id: "x".into() });
+}
+
+#[tokio::test]
+async fn slow_clients_are_told_how_much_they_missed() {
+    let bus = EventBus::new(4);
+    let server = EventServer::start("127.0.0.1:0".parse().unwrap(), bus.clone()).await.unwrap();
+    let mut client = connect(&server).await;
+
+    send(
+        &mut client,
+        &ClientMessage::Subscribe {
+            id: "all".into(),
+            topic: Topic::NewPools,
+            filter: EventFilter::default(),
+        },
+    )
+    .await;
+    assert_eq!(receive(&mut client).await, ServerMessage::Subscribed { id: "all".into() });
+
+    // published faster than the client task can forward them
+    for slot in 0..10 {
+        bus.publish(new_pool(Dex::MeteoraDlmm, slot));
+    }
+    let mut lagged = false;
+    let mut slots = Vec::new();
+    while slots.last() != Some(&9) {
+        match receive(&mut client).await {
+            ServerMessage::Lagged { missed } => {
+                assert!(missed > 0);
+                lagged = true;
+            }
+            ServerMessage::Event { event, .. } => match *event {
+                BotEvent::NewPools { slot, .. } => slots.push(slot),
+                other => panic!("unexpected {:?}", other),
+            },
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(lagged);
+    assert!(slots.len() < 10);
+}
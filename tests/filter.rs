@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use yellowstone_grpc_proto::prelude::{
+    subscribe_request_filter_accounts_filter::Filter as AccountFilterKind,
+    subscribe_request_filter_accounts_filter_lamports::Cmp as LamportsCmp,
+    subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData, SubscribeRequest,
+    SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter,
+    SubscribeRequestFilterAccountsFilterLamports, SubscribeRequestFilterAccountsFilterMemcmp,
+    SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+};
+
+use mevbot_ws_rust::{
+    dex_processor::AccountUpdate,
+    stream::{
+        filter::{AccountFilter, TransactionFilter},
+        FilterError, FilterSet, SlotStatus, SlotUpdate, SourceUpdate, TransactionUpdate,
+    },
+};
+
+fn memcmp(offset: u64, data: MemcmpData) -> SubscribeRequestFilterAccountsFilter {
+    SubscribeRequestFilterAccountsFilter {
+        filter: Some(AccountFilterKind::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+            offset,
+            data: Some(data),
+        })),
+    }
+}
+
+fn kind(filter: AccountFilterKind) -> SubscribeRequestFilterAccountsFilter {
+    SubscribeRequestFilterAccountsFilter { filter: Some(filter) }
+}
+
+fn lamports(cmp: LamportsCmp) -> SubscribeRequestFilterAccountsFilter {
+    kind(AccountFilterKind::Lamports(SubscribeRequestFilterAccountsFilterLamports { cmp: Some(cmp) }))
+}
+
+fn data_filter(filters: Vec<SubscribeRequestFilterAccountsFilter>) -> Result<AccountFilter, FilterError> {
+    AccountFilter::try_from(&SubscribeRequestFilterAccounts {
+        filters,
+        ..Default::default()
+    })
+}
+
+fn account(lamports: u64, data: Vec<u8>) -> AccountUpdate {
+    AccountUpdate {
+        lamports,
+        data,
+        ..Default::default()
+    }
+}
+
+fn token_account(state: u8) -> Vec<u8> {
+    let mut data = vec![0; 165];
+    data[108] = state;
+    data
+}
+
+#[test]
+fn memcmp_encodings_are_equivalent() {
+    let data = vec![0, 1, 2, 3, 4, 5];
+    let needle = [2u8, 3, 4];
+    for encoded in [
+        MemcmpData::Bytes(needle.to_vec()),
+        MemcmpData::Base58(bs58::encode(needle).into_string()),
+        MemcmpData::Base64(BASE64.encode(needle)),
+    ] {
+        let filter = data_filter(vec![memcmp(2, encoded.clone())]).unwrap();
+        assert!(filter.matches(&account(0, data.clone())), "{:?}", encoded);
+        let shifted = data_filter(vec![memcmp(1, encoded)]).unwrap();
+        assert!(!shifted.matches(&account(0, data.clone())));
+    }
+}
+
+#[test]
+fn memcmp_past_the_end_never_matches() {
+    let data = vec![7; 4];
+    assert!(data_filter(vec![memcmp(0, MemcmpData::Bytes(vec![7; 4]))])
+        .unwrap()
+        .matches(&account(0, data.clone())));
+    assert!(!data_filter(vec![memcmp(2, MemcmpData::Bytes(vec![7; 3]))])
+        .unwrap()
+        .matches(&account(0, data.clone())));
+    assert!(!data_filter(vec![memcmp(u64::MAX, MemcmpData::Bytes(vec![7]))])
+        .unwrap()
+        .matches(&account(0, data.clone())));
+    // empty data at the end is still in bounds
+    assert!(data_filter(vec![memcmp(4, MemcmpData::Bytes(vec![]))])
+        .unwrap()
+        .matches(&account(0, data)));
+}
+
+#[test]
+fn datasize_lamports_and_token_state() {
+    let datasize = data_filter(vec![kind(AccountFilterKind::Datasize(3))]).unwrap();
+    assert!(datasize.matches(&account(0, vec![0; 3])));
+    assert!(!datasize.matches(&account(0, vec![0; 4])));
+
+    let cases = [
+        (LamportsCmp::Eq(10), [false, true, false]),
+        (LamportsCmp::Ne(10), [true, false, true]),
+        (LamportsCmp::Lt(10), [true, false, false]),
+        (LamportsCmp::Gt(10), [false, false, true]),
+    ];
+    for (cmp, expected) in cases {
+        let filter = data_filter(vec![lamports(cmp)]).unwrap();
+        let got = [9, 10, 11].map(|l| filter.matches(&account(l, Vec::new())));
+        assert_eq!(got, expected, "{:?}", cmp);
+    }
+
+    let token_state = data_filter(vec![kind(AccountFilterKind::TokenAccountState(true))]).unwrap();
+    assert!(token_state.matches(&account(0, token_account(1))));
+    assert!(token_state.matches(&account(0, token_account(2))));
+    assert!(!token_state.matches(&account(0, token_account(0))));
+    assert!(!token_state.matches(&account(0, vec![1; 82])));
+}
+
+#[test]
+fn accounts_and_owners_are_or_lists_combined_with_and() {
+    let (a, b, owner, other_owner) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let filter = AccountFilter::try_from(&SubscribeRequestFilterAccounts {
+        account: vec![a.to_string(), b.to_string()],
+        owner: vec![owner.to_string()],
+        filters: vec![kind(AccountFilterKind::Datasize(1))],
+        ..Default::default()
+    })
+    .unwrap();
+    let update = |pubkey, owner, len| AccountUpdate {
+        pubkey,
+        owner,
+        data: vec![0; len],
+        ..Default::default()
+    };
+    assert!(filter.matches(&update(a, owner, 1)));
+    assert!(filter.matches(&update(b, owner, 1)));
+    assert!(!filter.matches(&update(Pubkey::new_unique(), owner, 1)));
+    assert!(!filter.matches(&update(a, other_owner, 1)));
+    assert!(!filter.matches(&update(a, owner, 2)));
+
+    // no accounts, owners or filters: everything
+    let everything = AccountFilter::try_from(&SubscribeRequestFilterAccounts::default()).unwrap();
+    assert!(everything.matches(&update(Pubkey::new_unique(), other_owner, 5)));
+}
+
+#[test]
+fn invalid_filters_are_rejected_like_the_server() {
+    let rejected = [
+        (vec![kind(AccountFilterKind::TokenAccountState(false))], FilterError::TokenAccountStateFalse),
+        (
+            vec![kind(AccountFilterKind::Datasize(1)), kind(AccountFilterKind::Datasize(2))],
+            FilterError::Duplicate("datasize"),
+        ),
+        (
+            vec![
+                kind(AccountFilterKind::TokenAccountState(true)),
+                kind(AccountFilterKind::TokenAccountState(true)),
+            ],
+            FilterError::Duplicate("token_account_state"),
+        ),
+        (vec![memcmp(0, MemcmpData::Bytes(vec![0; 129]))], FilterError::MemcmpTooLarge(129)),
+        (
+            vec![SubscribeRequestFilterAccountsFilter {
+                filter: Some(AccountFilterKind::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                    offset: 0,
+                    data: None,
+                })),
+            }],
+            FilterError::MissingMemcmpData,
+        ),
+        (vec![SubscribeRequestFilterAccountsFilter { filter: None }], FilterError::Empty),
+    ];
+    for (filters, expected) in rejected {
+        assert_eq!(data_filter(filters).unwrap_err(), expected);
+    }
+
+    assert!(matches!(
+        data_filter(vec![memcmp(0, MemcmpData::Base58("0OIl".into()))]),
+        Err(FilterError::InvalidMemcmpData(_))
+    ));
+    assert!(data_filter(vec![memcmp(0, MemcmpData::Bytes(vec![0; 128]))]).is_ok());
+    assert_eq!(
+        AccountFilter::try_from(&SubscribeRequestFilterAccounts {
+            owner: vec!["nope".into()],
+            ..Default::default()
+        })
+        .unwrap_err(),
+        FilterError::InvalidPubkey("nope".into())
+    );
+}
+
+#[test]
+fn transaction_filters() {
+    let (pool, wallet, noise) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let tx = |keys: Vec<Pubkey>, failed, is_vote| TransactionUpdate {
+        signature: Signature::default(),
+        slot: 1,
+        account_keys: keys,
+        is_vote,
+        failed,
+        logs: Vec::new(),
+    };
+    let filter = TransactionFilter::try_from(&SubscribeRequestFilterTransactions {
+        vote: Some(false),
+        failed: Some(false),
+        account_include: vec![pool.to_string(), wallet.to_string()],
+        account_exclude: vec![noise.to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(filter.matches(&tx(vec![pool], false, false)));
+    assert!(filter.matches(&tx(vec![wallet], false, false)));
+    assert!(!filter.matches(&tx(vec![pool], true, false)));
+    assert!(!filter.matches(&tx(vec![pool], false, true)));
+    assert!(!filter.matches(&tx(vec![pool, noise], false, false)));
+    assert!(!filter.matches(&tx(vec![Pubkey::new_unique()], false, false)));
+
+    let required = TransactionFilter::try_from(&SubscribeRequestFilterTransactions {
+        account_required: vec![pool.to_string(), wallet.to_string()],
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(required.matches(&tx(vec![wallet, noise, pool], true, false)));
+    assert!(!required.matches(&tx(vec![pool], false, false)));
+}
+
+#[test]
+fn filter_set_reports_matching_names() {
+    let owner = Pubkey::new_unique();
+    let request = SubscribeRequest {
+        accounts: HashMap::from([
+            (
+                "by_owner".to_string(),
+                SubscribeRequestFilterAccounts {
+                    owner: vec![owner.to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "big".to_string(),
+                SubscribeRequestFilterAccounts {
+                    filters: vec![lamports(LamportsCmp::Gt(1_000))],
+                    ..Default::default()
+                },
+            ),
+        ]),
+        slots: HashMap::from([("slots".to_string(), SubscribeRequestFilterSlots::default())]),
+        ..Default::default()
+    };
+    let set = FilterSet::try_from(&request).unwrap();
+
+    let update = |owner, lamports| {
+        SourceUpdate::Account(AccountUpdate {
+            owner,
+            lamports,
+            ..Default::default()
+        })
+    };
+    assert_eq!(set.matching(&update(owner, 5_000)), vec!["big", "by_owner"]);
+    assert_eq!(set.matching(&update(owner, 1)), vec!["by_owner"]);
+    assert!(!set.matches(&update(Pubkey::new_unique(), 1)));
+    assert_eq!(
+        set.matching(&SourceUpdate::Slot(SlotUpdate {
+            slot: 1,
+            parent: None,
+            status: SlotStatus::Processed
+        })),
+        vec!["slots"]
+    );
+
+    let without_slots = FilterSet::try_from(&SubscribeRequest::default()).unwrap();
+    assert!(!without_slots.matches(&SourceUpdate::Slot(SlotUpdate {
+        slot: 1,
+        parent: None,
+        status: SlotStatus::Processed
+    })));
+}
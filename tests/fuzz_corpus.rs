@@ -0,0 +1,174 @@
+//! Deterministic run of the fuzz entry points over mutated fixtures, so regressions the cargo-fuzz
+//! targets in `fuzz/` would find also fail `cargo test` on stable
+
+use std::path::PathBuf;
+
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_proto::{geyser::SubscribeUpdate, prost::Message};
+
+use mevbot_ws_rust::{
+    dex_processor::decode::LAYOUTS,
+    instruction::instruction::AmmInstruction,
+    stream::{SlotStatus, RECORDING_MAGIC},
+    testing::{account_update, fuzz, slot_update, transaction_update, Snapshot},
+};
+
+/// xorshift64, fixed seed so failures reproduce
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next() as u8).collect()
+    }
+
+    /// Flips, overwrites, truncates or extends `seed`, the way libFuzzer's default mutators do
+    fn mutate(&mut self, seed: &[u8]) -> Vec<u8> {
+        let mut data = seed.to_vec();
+        for _ in 0..=self.below(4) {
+            match self.below(5) {
+                0 if !data.is_empty() => {
+                    let i = self.below(data.len());
+                    data[i] ^= 1 << self.below(8);
+                }
+                1 if !data.is_empty() => {
+                    let i = self.below(data.len());
+                    data[i] = [0, 1, 0x7f, 0x80, 0xff][self.below(5)];
+                }
+                2 => data.truncate(self.below(data.len() + 1)),
+                3 => {
+                    let extra = self.below(64);
+                    data.extend(self.bytes(extra));
+                }
+                _ => {
+                    let extra = self.below(64);
+                    let at = self.below(data.len() + 1);
+                    data.splice(at..at, self.bytes(extra));
+                }
+            }
+        }
+        data
+    }
+}
+
+fn fixtures() -> Vec<Vec<u8>> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layouts");
+    LAYOUTS
+        .iter()
+        .map(|(layout, ..)| {
+            let snapshot = Snapshot::load(dir.join(format!("{}.json", layout))).unwrap();
+            snapshot.accounts.into_values().next().unwrap().data
+        })
+        .collect()
+}
+
+fn recording(updates: &[SubscribeUpdate]) -> Vec<u8> {
+    let mut data = RECORDING_MAGIC.to_vec();
+    for (i, update) in updates.iter().enumerate() {
+        let payload = update.encode_to_vec();
+        data.extend((i as u64 * 1_000).to_le_bytes());
+        data.extend((payload.len() as u32).to_le_bytes());
+        data.extend(payload);
+    }
+    data
+}
+
+fn updates(rng: &mut Rng) -> Vec<SubscribeUpdate> {
+    let mut updates = Vec::new();
+    for (data, (_, dex, _)) in fixtures().into_iter().zip(LAYOUTS) {
+        let owner = dex.map_or_else(spl_token::id, |dex| dex.program_id());
+        updates.push(account_update(&Pubkey::new_unique(), &owner, rng.next() % 1_000, 2_039_280, data));
+    }
+    let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    updates.push(transaction_update(7, [3; 64], &keys, false));
+    updates.push(transaction_update(8, [4; 64], &keys, true));
+    updates.push(slot_update(9, Some(8), SlotStatus::Confirmed));
+    updates
+}
+
+#[test]
+fn account_layouts_survive_mutated_fixtures() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let seeds = fixtures();
+    for seed in &seeds {
+        fuzz::account_layouts(seed);
+        for len in 0..seed.len().min(64) {
+            fuzz::account_layouts(&seed[..len]);
+        }
+    }
+    for _ in 0..2_000 {
+        let seed = &seeds[rng.below(seeds.len())];
+        fuzz::account_layouts(&rng.mutate(seed));
+    }
+    for _ in 0..200 {
+        let len = rng.below(17_000);
+        fuzz::account_layouts(&rng.bytes(len));
+    }
+}
+
+#[test]
+fn every_instruction_tag_with_random_payloads() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+    for tag in 0..=u8::MAX {
+        for _ in 0..64 {
+            let len = rng.below(80);
+            let mut data = vec![tag];
+            data.extend(rng.bytes(len));
+            fuzz::amm_instruction(&data);
+        }
+    }
+}
+
+#[test]
+fn short_config_owner_is_rejected() {
+    for param in [0, 1] {
+        for len in 0..32 {
+            let mut data = vec![15, param];
+            data.extend(vec![7; len]);
+            assert!(AmmInstruction::unpack(&data).is_err(), "{} owner bytes accepted", len);
+        }
+        let mut data = vec![15, param];
+        data.extend([7; 32]);
+        assert!(AmmInstruction::unpack(&data).is_ok());
+    }
+}
+
+#[test]
+fn subscribe_updates_survive_mutation() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let seeds: Vec<Vec<u8>> = updates(&mut rng).iter().map(Message::encode_to_vec).collect();
+    for seed in &seeds {
+        fuzz::subscribe_update(seed);
+    }
+    for _ in 0..2_000 {
+        let seed = &seeds[rng.below(seeds.len())];
+        fuzz::subscribe_update(&rng.mutate(seed));
+    }
+}
+
+#[test]
+fn recordings_survive_mutation() {
+    let mut rng = Rng(0x6a09_e667_f3bc_c908);
+    let seed = recording(&updates(&mut rng));
+    fuzz::recording(&seed);
+    for _ in 0..500 {
+        fuzz::recording(&rng.mutate(&seed));
+    }
+
+    // a length header far past the end of the file must not be allocated up front
+    let mut huge = RECORDING_MAGIC.to_vec();
+    huge.extend(0u64.to_le_bytes());
+    huge.extend(u32::MAX.to_le_bytes());
+    huge.extend([0; 16]);
+    fuzz::recording(&huge);
+}
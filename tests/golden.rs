@@ -0,0 +1,246 @@
+//! Golden fixtures: raw account bytes under `tests/fixtures/layouts/<layout>.json` (in the
+//! `solana account --output json` format) decoded and compared field by field with
+//! `<layout>.expected.json`. The same fixtures, with a byte or a field changed, cover the
+//! discriminator and sanity checks.
+//!
+//! None of the fixtures are mainnet dumps. `meteora_pools` and `meteora_dlmm_bin_array` are
+//! rebuilt from the Anchor JSON in `src/config`: the field values come from there, the lamports
+//! are placeholders. Their pubkeys are derived rather than copied: the Meteora pool is the
+//! address the recorded `lp_mint`, `a_vault_lp` and `b_vault_lp` are PDAs of, and the bin array
+//! is the PDA of the recorded `lb_pair` and index. The other fixtures are synthetic: accounts
+//! built byte by byte from the published layouts (Anchor IDLs, the Raydium and Serum sources),
+//! with the expected values written from those layouts rather than from the parsers. Either way
+//! they catch offset drift between the parsers and the layouts, not between the layouts and the
+//! chain. `solfi.expected.json` has no published layout behind it and only pins the current
+//! parser output. Replace a fixture with a real `solana account` dump, noting the slot, when one
+//! is at hand; the expected file then has to be checked against an explorer.
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use mevbot_ws_rust::{
+    dex::account_discriminator,
+    dex_processor::{
+        decode::{layout_discriminator, DecodeError, LAYOUTS},
+        DecodedAccount,
+    },
+    testing::Snapshot,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layouts").join(name)
+}
+
+fn load(layout: &str) -> (solana_sdk::account::Account, Value) {
+    let snapshot = Snapshot::load(fixture(&format!("{}.json", layout))).unwrap();
+    assert_eq!(snapshot.accounts.len(), 1, "{}: one account per fixture", layout);
+    let account = snapshot.accounts.into_values().next().unwrap();
+    let expected = std::fs::read_to_string(fixture(&format!("{}.expected.json", layout))).unwrap();
+    (account, serde_json::from_str(&expected).unwrap())
+}
+
+/// Appends one line per differing leaf, missing field or unexpected field
+fn diff(path: &str, expected: &Value, actual: &Value, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (name, want) in expected {
+                let field = format!("{}.{}", path, name);
+                match actual.get(name) {
+                    Some(got) => diff(&field, want, got, out),
+                    None => out.push(format!("{}: missing from decoded output", field)),
+                }
+            }
+            for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+                out.push(format!("{}.{}: decoded but not in the fixture", path, name));
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (i, (want, got)) in expected.iter().zip(actual).enumerate() {
+                diff(&format!("{}[{}]", path, i), want, got, out);
+            }
+        }
+        _ if expected != actual => out.push(format!("{}: expected {}, got {}", path, expected, actual)),
+        _ => {}
+    }
+}
+
+#[test]
+fn every_layout_has_a_fixture() {
+    for (layout, ..) in LAYOUTS {
+        for file in [format!("{}.json", layout), format!("{}.expected.json", layout)] {
+            assert!(fixture(&file).exists(), "missing fixture {}", file);
+        }
+    }
+}
+
+#[test]
+fn fixtures_decode_to_their_expected_values() {
+    let mut drift = Vec::new();
+    for (layout, _, size) in LAYOUTS {
+        let (account, expected) = load(layout);
+        assert_eq!(account.data.len(), size, "{}: fixture size", layout);
+
+        let Some(decoded) = DecodedAccount::decode(&account.owner, &account.data) else {
+            drift.push(format!("{}: does not decode", layout));
+            continue;
+        };
+        assert_eq!(decoded.layout_name(), layout);
+        let actual = serde_json::to_value(&decoded).unwrap();
+        diff(layout, &expected["data"], &actual["data"], &mut drift);
+    }
+    assert!(drift.is_empty(), "layout drift in {} fields:\n{}", drift.len(), drift.join("\n"));
+}
+
+#[test]
+fn truncated_accounts_are_rejected() {
+    for (layout, ..) in LAYOUTS {
+        let (account, _) = load(layout);
+        let short = &account.data[..account.data.len() - 1];
+        assert!(DecodedAccount::decode_layout(layout, short).is_none(), "{} accepted {} bytes", layout, short.len());
+    }
+}
+
+/// Anchor's decoded-account JSON (as shown by explorers) in the decoders' shape: `{type, data}`
+/// wrappers dropped, camelCase keys snake_cased, unit enums turned into their variant index and
+/// every number written as a string, since Anchor prints u64 as strings
+fn from_anchor(value: &Value) -> Value {
+    const ENUMS: [(&str, &[&str]); 2] = [
+        ("poolType", &["permissioned", "permissionless"]),
+        ("curveType", &["constantProduct", "stable"]),
+    ];
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, field)| {
+                    let data = field.get("data").unwrap_or(field);
+                    let value = match ENUMS.iter().find(|(enum_name, _)| enum_name == name) {
+                        Some((_, variants)) => {
+                            let variant = data.as_object().and_then(|v| v.keys().next()).unwrap();
+                            let index = variants.iter().position(|v| v == variant).unwrap();
+                            Value::String(index.to_string())
+                        }
+                        None => from_anchor(data),
+                    };
+                    (snake_case(name), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(from_anchor).collect()),
+        Value::Number(n) => Value::String(n.to_string()),
+        other => other.clone(),
+    }
+}
+
+fn numbers_as_strings(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(fields.iter().map(|(k, v)| (k.clone(), numbers_as_strings(v))).collect()),
+        Value::Array(items) => Value::Array(items.iter().map(numbers_as_strings).collect()),
+        Value::Number(n) => Value::String(n.to_string()),
+        other => other.clone(),
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+#[test]
+fn anchor_dumps_in_config_match_their_fixtures() {
+    for (layout, config) in [
+        ("meteora_pools", "meteora_pools.json"),
+        ("meteora_dlmm_bin_array", "meteora_bin_array.json"),
+    ] {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/config").join(config);
+        let anchor: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let (account, _) = load(layout);
+        let decoded = DecodedAccount::decode(&account.owner, &account.data).unwrap();
+
+        let mut drift = Vec::new();
+        let actual = numbers_as_strings(&serde_json::to_value(&decoded).unwrap()["data"]);
+        diff(layout, &from_anchor(&anchor), &actual, &mut drift);
+        assert!(drift.is_empty(), "{} differs from src/config/{}:\n{}", layout, config, drift.join("\n"));
+    }
+}
+
+#[test]
+fn discriminators_are_the_anchor_account_hashes() {
+    for (layout, name) in [
+        ("raydium_cpmm", "PoolState"),
+        ("raydium_clmm", "PoolState"),
+        ("meteora_dlmm_lb_pair", "LbPair"),
+        ("meteora_dlmm_bin_array", "BinArray"),
+        ("meteora_dlmm_oracle", "Oracle"),
+        ("meteora_pools", "Pool"),
+    ] {
+        assert_eq!(layout_discriminator(layout), Some(account_discriminator(name)), "{}", layout);
+        let (account, _) = load(layout);
+        assert_eq!(account.data[..8], account_discriminator(name), "{} fixture", layout);
+    }
+}
+
+#[test]
+fn wrong_discriminators_are_rejected() {
+    for (layout, ..) in LAYOUTS.iter().filter(|(layout, ..)| layout_discriminator(layout).is_some()) {
+        let (mut account, _) = load(layout);
+        account.data[3] ^= 0xff;
+        assert!(DecodedAccount::decode_layout(layout, &account.data).is_none(), "{}", layout);
+        let err = DecodedAccount::try_decode(&account.owner, &account.data).unwrap_err();
+        assert!(matches!(err, DecodeError::Discriminator { .. }) && err.is_drift(), "{}: {}", layout, err);
+    }
+}
+
+#[test]
+fn implausible_values_are_reported_as_drift() {
+    let drift = |layout: &str, edit: &dyn Fn(&mut DecodedAccount)| {
+        let (account, _) = load(layout);
+        let mut decoded = DecodedAccount::decode(&account.owner, &account.data).unwrap();
+        edit(&mut decoded);
+        let data = decoded.encode();
+        // still parses, the values are what gives it away
+        assert!(DecodedAccount::decode_layout(layout, &data).is_some());
+        match DecodedAccount::try_decode(&account.owner, &data) {
+            Err(DecodeError::Drift { field, .. }) => field,
+            other => panic!("{}: expected drift, got {:?}", layout, other),
+        }
+    };
+
+    let field = drift("raydium_cpmm", &|d| {
+        if let DecodedAccount::RaydiumCpmm(pool) = d {
+            pool.mint1Decimals = 64;
+        }
+    });
+    assert_eq!(field, "mint1Decimals");
+    let field = drift("raydium_clmm", &|d| {
+        if let DecodedAccount::RaydiumClmm(pool) = d {
+            pool.status = 0x80;
+        }
+    });
+    assert_eq!(field, "status");
+    let field = drift("raydium_amm_v4", &|d| {
+        if let DecodedAccount::RaydiumAmmV4(pool) = d {
+            pool.status = 42;
+        }
+    });
+    assert_eq!(field, "status");
+    let field = drift("meteora_dlmm_lb_pair", &|d| {
+        if let DecodedAccount::MeteoraDlmm(pair) = d {
+            pair.token_y_mint = Default::default();
+        }
+    });
+    assert_eq!(field, "token_y_mint");
+    let field = drift("spl_token_account", &|d| {
+        if let DecodedAccount::SplToken(account) = d {
+            account.state = 3;
+        }
+    });
+    assert_eq!(field, "state");
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use solana_program::pubkey::Pubkey;
+use tonic::Code;
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::PoolTokens,
+    server::{
+        grpc::{
+            self,
+            proto::{pool_event::Event, pool_events_client::PoolEventsClient, SubscribeEventsRequest, Topic},
+        },
+        BotEvent, EventBus,
+    },
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn client(bus: &EventBus) -> PoolEventsClient<tonic::transport::Channel> {
+    let (addr, _) = grpc::serve("127.0.0.1:0".parse().unwrap(), bus.clone()).await.unwrap();
+    PoolEventsClient::connect(format!("http://{}", addr)).await.unwrap()
+}
+
+/// The bus has a receiver once the server accepted the subscription
+async fn wait_for_subscriber(bus: &EventBus) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !bus.has_subscribers() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn streams_normalized_events_matching_the_request() {
+    let bus = EventBus::default();
+    let mut client = client(&bus).await;
+    let mint = Pubkey::new_unique();
+    let mut stream = client
+        .subscribe(SubscribeEventsRequest {
+            topics: vec![Topic::PoolState as i32, Topic::Swaps as i32],
+            dexes: vec!["raydium_cpmm".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    wait_for_subscriber(&bus).await;
+
+    let tokens = PoolTokens {
+        base_mint: mint,
+        quote_mint: Pubkey::new_unique(),
+        base_vault: Pubkey::new_unique(),
+        quote_vault: Pubkey::new_unique(),
+    };
+    let pool = Pubkey::new_unique();
+    // wrong topic, then wrong dex, then one of each wanted topic
+    bus.publish(BotEvent::NewPools {
+        dex: Dex::RaydiumCpmm,
+        pool,
+        slot: 1,
+        tokens: Some(tokens),
+    });
+    bus.publish(BotEvent::PoolState {
+        dex: Dex::RaydiumClmm,
+        pool,
+        slot: 2,
+        tokens: Some(tokens),
+        state: serde_json::json!({}),
+    });
+    bus.publish(BotEvent::PoolState {
+        dex: Dex::RaydiumCpmm,
+        pool,
+        slot: 3,
+        tokens: Some(tokens),
+        state: serde_json::json!({"layout": "raydium_cpmm"}),
+    });
+    bus.publish(BotEvent::Swaps {
+        dex: Dex::RaydiumCpmm,
+        signature: "sig".into(),
+        slot: 4,
+        accounts: vec![mint],
+    });
+
+    let next = tokio::time::timeout(TIMEOUT, stream.message()).await.unwrap().unwrap().unwrap();
+    let Some(Event::PoolState(state)) = next.event else {
+        panic!("unexpected {:?}", next);
+    };
+    assert_eq!(state.slot, 3);
+    assert_eq!(state.dex, "raydium_cpmm");
+    assert_eq!(state.pool, pool.to_string());
+    assert_eq!(state.tokens.unwrap().base_mint, mint.to_string());
+    assert_eq!(state.layout_json, r#"{"layout":"raydium_cpmm"}"#);
+
+    let next = tokio::time::timeout(TIMEOUT, stream.message()).await.unwrap().unwrap().unwrap();
+    let Some(Event::Swap(swap)) = next.event else {
+        panic!("unexpected {:?}", next);
+    };
+    assert_eq!((swap.slot, swap.signature.as_str()), (4, "sig"));
+    assert_eq!(swap.accounts, vec![mint.to_string()]);
+}
+
+#[tokio::test]
+async fn rejects_invalid_requests() {
+    let bus = EventBus::default();
+    let mut client = client(&bus).await;
+
+    let status = client
+        .subscribe(SubscribeEventsRequest {
+            dexes: vec!["uniswap".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let status = client
+        .subscribe(SubscribeEventsRequest {
+            mints: vec!["not a pubkey".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
@@ -0,0 +1,194 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use solana_program::pubkey::Pubkey;
+use tokio::time::Instant;
+use yellowstone_grpc_proto::prelude::{SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterSlots};
+
+use mevbot_ws_rust::{
+    stream::{
+        HealthConfig, HealthMonitor, HealthStatus, ReconnectingSource, SlotStatus, SlotUpdate, SourceUpdate,
+        SubscriptionController, UpdateSource,
+    },
+    testing::{account_update, slot_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn slot(slot: u64) -> SourceUpdate {
+    SourceUpdate::Slot(SlotUpdate {
+        slot,
+        parent: slot.checked_sub(1),
+        status: SlotStatus::Processed,
+    })
+}
+
+#[test]
+fn quiet_stream_with_pongs_is_healthy() {
+    let start = Instant::now();
+    let mut monitor = HealthMonitor::new(HealthConfig::default(), start);
+    assert_eq!(monitor.snapshot(start).status, HealthStatus::Connecting);
+
+    // no updates for a while, but pings keep getting answered
+    for i in 1..=5 {
+        let sent = start + Duration::from_secs(3 * i as u64);
+        monitor.on_ping_sent(i, sent);
+        monitor.on_message(sent + Duration::from_millis(40));
+        monitor.on_pong(i, sent + Duration::from_millis(40));
+    }
+    let snapshot = monitor.snapshot(start + Duration::from_secs(16));
+    assert_eq!(snapshot.status, HealthStatus::Healthy);
+    assert_eq!(snapshot.pong_rtt, Some(Duration::from_millis(40)));
+    assert_eq!(snapshot.since_last_update, None);
+}
+
+#[test]
+fn missing_pong_or_silence_is_a_stall() {
+    let start = Instant::now();
+    let config = HealthConfig::default();
+
+    let mut monitor = HealthMonitor::new(config, start);
+    monitor.on_message(start);
+    monitor.on_ping_sent(1, start + Duration::from_secs(1));
+    // a late ping does not restart the pong timer
+    monitor.on_ping_sent(2, start + Duration::from_secs(4));
+    monitor.on_message(start + Duration::from_secs(5));
+    let status = monitor.snapshot(start + Duration::from_secs(8)).status;
+    assert!(matches!(status, HealthStatus::Stalled(ref reason) if reason.contains("pong")), "{}", status);
+
+    let monitor = HealthMonitor::new(config, start);
+    let status = monitor.snapshot(start + config.stall_timeout + Duration::from_secs(1)).status;
+    assert!(matches!(status, HealthStatus::Stalled(ref reason) if reason.contains("no message")), "{}", status);
+}
+
+#[test]
+fn slow_pong_and_lagging_slots_degrade() {
+    let start = Instant::now();
+    let mut monitor = HealthMonitor::new(HealthConfig::default(), start);
+    monitor.on_ping_sent(1, start);
+    monitor.on_message(start + Duration::from_secs(2));
+    monitor.on_pong(1, start + Duration::from_secs(2));
+    assert!(matches!(monitor.snapshot(start + Duration::from_secs(2)).status, HealthStatus::Degraded(_)));
+
+    let mut monitor = HealthMonitor::new(HealthConfig::default(), start);
+    // 20 s of wall clock is 50 slots, the stream only advanced 10
+    for i in 0..=10 {
+        let at = start + Duration::from_secs(2 * i);
+        monitor.on_message(at);
+        monitor.on_update(&slot(100 + i), at);
+    }
+    let snapshot = monitor.snapshot(start + Duration::from_secs(20));
+    assert_eq!(snapshot.slot, Some(110));
+    assert_eq!(snapshot.slot_drift, Some(40));
+    assert!(matches!(snapshot.status, HealthStatus::Degraded(ref reason) if reason.contains("slot")));
+
+    // keeping pace with the clock is healthy
+    let mut monitor = HealthMonitor::new(HealthConfig::default(), start);
+    for i in 0..=50 {
+        let at = start + Duration::from_millis(400 * i);
+        monitor.on_message(at);
+        monitor.on_update(&slot(100 + i), at);
+    }
+    let snapshot = monitor.snapshot(start + Duration::from_secs(20));
+    assert_eq!(snapshot.slot_drift, Some(0));
+    assert_eq!(snapshot.status, HealthStatus::Healthy);
+}
+
+#[tokio::test]
+async fn reconnects_after_server_restart() {
+    let owner = Pubkey::new_unique();
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "owner".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        slots: HashMap::from([("slots".to_string(), SubscribeRequestFilterSlots::default())]),
+        ..Default::default()
+    };
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let addr = server.addr();
+    let mut source = ReconnectingSource::new(server.endpoint(), None, request, HealthConfig::default());
+    let mut health = source.health();
+
+    let (tx, mut updates) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        while let Ok(Some(update)) = source.next_update().await {
+            if tx.send(update).await.is_err() {
+                break;
+            }
+        }
+    });
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+    server.push(slot_update(7, Some(6), SlotStatus::Processed));
+    let first = tokio::time::timeout(TIMEOUT, updates.recv()).await.unwrap();
+    assert!(matches!(first, Some(SourceUpdate::Slot(update)) if update.slot == 7));
+
+    server.shutdown();
+    let stalled = tokio::time::timeout(TIMEOUT, health.wait_for(|s| s.reconnects > 0)).await.unwrap().unwrap().clone();
+    assert!(matches!(stalled.status, HealthStatus::Stalled(_)), "{}", stalled.status);
+
+    let mut server = loop {
+        match MockGeyserServer::start_at(addr).await {
+            Ok(server) => break server,
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    };
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+    let pool = Pubkey::new_unique();
+    server.push(account_update(&pool, &owner, 8, 1, vec![]));
+
+    let second = tokio::time::timeout(TIMEOUT, updates.recv()).await.unwrap();
+    assert!(matches!(second, Some(SourceUpdate::Account(account)) if account.pubkey == pool));
+    assert_eq!(health.borrow().reconnects, 1);
+}
+
+#[tokio::test]
+async fn subscription_changes_survive_health_checks() {
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let controller = SubscriptionController::new(SubscribeRequest::default());
+    controller.watch("wallets", [Pubkey::new_unique()]);
+    let mut source = ReconnectingSource::new(server.endpoint(), None, controller.request(), HealthConfig::default())
+        .with_subscription(controller.subscribe());
+    let health = source.health();
+
+    let (tx, mut updates) = tokio::sync::mpsc::channel(1024);
+    tokio::spawn(async move {
+        while let Ok(Some(update)) = source.next_update().await {
+            if tx.send(update).await.is_err() {
+                break;
+            }
+        }
+    });
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    // keep changing the subscription across a few health check ticks (one per second)
+    let mut wallets = Vec::new();
+    let started = Instant::now();
+    while started.elapsed() < Duration::from_millis(2_200) {
+        let wallet = Pubkey::new_unique();
+        controller.watch("wallets", [wallet]);
+        wallets.push(wallet);
+        tokio::time::sleep(Duration::from_millis(7)).await;
+    }
+
+    // the server applies the latest request eventually, every wallet must stream
+    let mut missing: HashSet<_> = wallets.iter().copied().collect();
+    let deadline = Instant::now() + TIMEOUT;
+    while !missing.is_empty() && Instant::now() < deadline {
+        for wallet in &missing {
+            server.push(account_update(wallet, &Pubkey::new_unique(), 9, 1, vec![]));
+        }
+        while let Ok(Some(update)) = tokio::time::timeout(Duration::from_millis(100), updates.recv()).await {
+            if let SourceUpdate::Account(account) = update {
+                missing.remove(&account.pubkey);
+            }
+        }
+    }
+    assert!(missing.is_empty(), "{} of {} wallets never streamed", missing.len(), wallets.len());
+    assert_eq!(health.borrow().reconnects, 0);
+}
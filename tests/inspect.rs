@@ -0,0 +1,148 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::{
+    dex::{
+        raydium_cpmm::{RAYDIUM_CP_POOL_DISCRIMINATOR, RAYDIUM_CP_POOL_SIZE},
+        raydium_lp_v4::OPEN_ORDERS_ACCOUNT_SIZE,
+        spl_token::SPL_TOKEN_ACCOUNT_SIZE,
+        Dex,
+    },
+    dex_processor::inspect::{inspect, render_table, AccountInput, InputEncoding},
+};
+
+fn cpmm_pool() -> Vec<u8> {
+    let mut data = vec![0; RAYDIUM_CP_POOL_SIZE];
+    data[..8].copy_from_slice(&RAYDIUM_CP_POOL_DISCRIMINATOR);
+    data
+}
+
+fn cli_dump(pubkey: &Pubkey, owner: &Pubkey, data: &[u8]) -> String {
+    serde_json::json!({
+        "pubkey": pubkey.to_string(),
+        "account": {
+            "lamports": 1_000_000,
+            "data": [BASE64.encode(data), "base64"],
+            "owner": owner.to_string(),
+            "executable": false,
+            "rentEpoch": 18446744073709551615u64,
+            "space": data.len(),
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn parses_every_input_encoding() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    let base64 = AccountInput::parse(BASE64.encode(&data).as_bytes(), InputEncoding::Auto).unwrap();
+    assert_eq!(base64.data, data);
+    // a trailing newline from `pbpaste` or `echo` is fine
+    let base58 = format!("{}\n", bs58::encode(&data).into_string());
+    assert_eq!(AccountInput::parse(base58.as_bytes(), InputEncoding::Base58).unwrap().data, data);
+    // not valid UTF-8, taken as raw bytes
+    assert_eq!(AccountInput::parse(&data, InputEncoding::Auto).unwrap().data, data);
+
+    let pubkey = Pubkey::new_unique();
+    let owner = Dex::RaydiumCpmm.program_id();
+    let dump = AccountInput::parse(cli_dump(&pubkey, &owner, &data).as_bytes(), InputEncoding::Auto).unwrap();
+    assert_eq!(
+        dump,
+        AccountInput {
+            pubkey: Some(pubkey),
+            owner: Some(owner),
+            data,
+        }
+    );
+
+    assert!(AccountInput::parse(b"not base64!", InputEncoding::Base64).is_err());
+    assert!(AccountInput::parse(b"{}", InputEncoding::Json).is_err());
+}
+
+#[test]
+fn auto_prefers_base58_unless_only_base64_fits_a_layout() {
+    // a mint (supply, decimals, initialized), an OpenOrders account with some coin free and a
+    // program id, whose 44 base58 characters are valid base64 as well
+    let mut mint = vec![0; 82];
+    mint[36..44].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+    mint[44..46].copy_from_slice(&[6, 1]);
+    let mut open_orders = vec![0; OPEN_ORDERS_ACCOUNT_SIZE];
+    open_orders[..5].copy_from_slice(b"serum");
+    open_orders[5 + 8 + 32 + 32..][..8].copy_from_slice(&42u64.to_le_bytes());
+    let program = Dex::RaydiumCpmm.program_id().to_bytes().to_vec();
+    assert!(BASE64.decode(bs58::encode(&program).into_string()).is_ok());
+    for data in [mint, open_orders, program] {
+        let text = bs58::encode(&data).into_string();
+        assert_eq!(AccountInput::parse(text.as_bytes(), InputEncoding::Auto).unwrap().data, data);
+    }
+
+    // only base58 characters, but 165 bytes as base64 is a token account
+    let text = "A".repeat(220);
+    let token = AccountInput::parse(text.as_bytes(), InputEncoding::Auto).unwrap();
+    assert_eq!(token.data, vec![0; SPL_TOKEN_ACCOUNT_SIZE]);
+}
+
+#[test]
+fn detects_the_layout_from_owner_or_size() {
+    let pool = AccountInput {
+        pubkey: None,
+        owner: Some(Dex::RaydiumCpmm.program_id()),
+        data: cpmm_pool(),
+    };
+    let inspection = inspect(&pool, None).unwrap();
+    assert_eq!(inspection.decoded.layout_name(), "raydium_cpmm");
+    assert!(inspection.alternatives.is_empty());
+    // all-zero mints decode, flagged instead of rejected
+    assert!(inspection.drift.unwrap().to_string().contains("token0Mint"));
+
+    let unknown_owner = AccountInput {
+        owner: None,
+        data: vec![0; SPL_TOKEN_ACCOUNT_SIZE],
+        ..pool.clone()
+    };
+    assert_eq!(inspect(&unknown_owner, None).unwrap().decoded.layout_name(), "spl_token_account");
+
+    // the owner wins over size, an explicit layout wins over both
+    let wrong_owner = AccountInput {
+        owner: Some(Pubkey::new_unique()),
+        ..pool.clone()
+    };
+    assert!(inspect(&wrong_owner, None).is_err());
+    assert_eq!(inspect(&wrong_owner, Some("raydium_cpmm")).unwrap().decoded.layout_name(), "raydium_cpmm");
+    assert!(inspect(&pool, Some("solfi")).is_err());
+    let err = inspect(&pool, Some("uniswap")).unwrap_err().to_string();
+    assert!(err.contains("unknown layout uniswap"), "{}", err);
+
+    let odd_size = AccountInput {
+        owner: None,
+        data: vec![0; 7],
+        ..pool
+    };
+    assert!(inspect(&odd_size, None).is_err());
+
+    let no_discriminator = AccountInput {
+        pubkey: None,
+        owner: Some(Dex::RaydiumCpmm.program_id()),
+        data: vec![0; RAYDIUM_CP_POOL_SIZE],
+    };
+    assert!(inspect(&no_discriminator, None).is_err());
+}
+
+#[test]
+fn table_lists_fields_in_declaration_order() {
+    let mut data = cpmm_pool();
+    let amm_config = Pubkey::new_unique();
+    data[8..40].copy_from_slice(amm_config.as_ref());
+    let input = AccountInput {
+        pubkey: None,
+        owner: Some(Dex::RaydiumCpmm.program_id()),
+        data,
+    };
+    let table = render_table(&inspect(&input, None).unwrap().decoded).unwrap();
+    let rows: Vec<Vec<&str>> = table.lines().map(|line| line.split_whitespace().collect()).collect();
+    assert_eq!(rows[0], ["layout", "raydium_cpmm"]);
+    let discriminator = u64::from_le_bytes(RAYDIUM_CP_POOL_DISCRIMINATOR).to_string();
+    assert_eq!(rows[1], ["discriminator", discriminator.as_str()]);
+    assert_eq!(rows[2], ["ammConfig", amm_config.to_string().as_str()]);
+}
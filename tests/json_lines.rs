@@ -0,0 +1,172 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{
+        json_lines::JsonRecord, AccountDecoder, AccountUpdate, DecodedAccount, JsonLinesSink, NewPoolFilter,
+        Pipeline, PipelineEvent, RetractReason, Retraction,
+    },
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn take_lines(&self) -> Vec<String> {
+        let bytes = std::mem::take(&mut *self.0.lock().unwrap());
+        let text = String::from_utf8(bytes).unwrap();
+        assert!(text.is_empty() || text.ends_with('\n'), "unterminated line: {:?}", text);
+        text.lines().map(str::to_string).collect()
+    }
+}
+
+/// A v4 pool that has never been swapped against, so [`NewPoolFilter`] raises an event for it
+fn new_v4_pool() -> (Pubkey, LIQUIDITY_STATE_LAYOUT_V4) {
+    let state = LIQUIDITY_STATE_LAYOUT_V4 {
+        status: 6,
+        baseDecimal: 9,
+        quoteDecimal: 6,
+        baseMint: Pubkey::new_unique(),
+        quoteMint: Pubkey::new_unique(),
+        ..Default::default()
+    };
+    (Pubkey::new_unique(), state)
+}
+
+fn pipeline(buffer: &SharedBuffer) -> Pipeline {
+    Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(NewPoolFilter)
+        .with_handler(JsonLinesSink::new(buffer.clone()))
+}
+
+#[test]
+fn writes_one_line_per_account_and_event() {
+    let buffer = SharedBuffer::default();
+    let mut pipeline = pipeline(&buffer);
+    let (pool, state) = new_v4_pool();
+    let owner = Dex::RaydiumAmmV4.program_id();
+    pipeline
+        .process(AccountUpdate {
+            pubkey: pool,
+            owner,
+            slot: 77,
+            data: bytemuck::bytes_of(&state).to_vec(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let lines = buffer.take_lines();
+    assert_eq!(lines.len(), 2, "{:#?}", lines);
+
+    match serde_json::from_str::<JsonRecord>(&lines[0]).unwrap() {
+        JsonRecord::Account {
+            slot,
+            pubkey,
+            owner: record_owner,
+            dex,
+            account,
+        } => {
+            assert_eq!((slot, pubkey, record_owner), (77, pool, owner));
+            assert_eq!(dex, Some(Dex::RaydiumAmmV4));
+            let DecodedAccount::RaydiumAmmV4(decoded) = account else {
+                panic!("expected a v4 pool, got {}", account.layout_name());
+            };
+            assert_eq!(*decoded, state);
+        }
+        other => panic!("expected an account record, got {:?}", other),
+    }
+    match serde_json::from_str::<JsonRecord>(&lines[1]).unwrap() {
+        JsonRecord::Event { slot, pubkey, event, .. } => {
+            assert_eq!((slot, pubkey), (77, pool));
+            assert_eq!(
+                event,
+                PipelineEvent::NewPool {
+                    dex: Dex::RaydiumAmmV4,
+                    pool
+                }
+            );
+        }
+        other => panic!("expected an event record, got {:?}", other),
+    }
+
+    // flat objects keyed by `kind`, pubkeys as base58 strings
+    let account: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(account["kind"], "account");
+    assert_eq!(account["layout"], "raydium_amm_v4");
+    assert_eq!(account["pubkey"], pool.to_string());
+    assert!(account["data"].is_object());
+    let event: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+    assert_eq!(event["kind"], "event");
+    assert_eq!(event["event"], "new_pool");
+    assert_eq!(event["pool"], pool.to_string());
+}
+
+#[test]
+fn undecoded_accounts_write_nothing() {
+    let buffer = SharedBuffer::default();
+    let mut pipeline = pipeline(&buffer);
+    pipeline
+        .process(AccountUpdate {
+            pubkey: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            data: vec![1, 2, 3],
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(buffer.take_lines().is_empty());
+}
+
+#[test]
+fn retractions_name_the_restored_slot() {
+    let buffer = SharedBuffer::default();
+    let mut pipeline = pipeline(&buffer);
+    let pubkey = Pubkey::new_unique();
+    let write = |slot| AccountUpdate {
+        pubkey,
+        slot,
+        ..Default::default()
+    };
+    pipeline
+        .retract(&Retraction {
+            update: write(12),
+            reason: RetractReason::Skipped,
+            restored: Some(write(10)),
+        })
+        .unwrap();
+    pipeline
+        .retract(&Retraction {
+            update: write(13),
+            reason: RetractReason::Dead,
+            restored: None,
+        })
+        .unwrap();
+
+    let lines = buffer.take_lines();
+    assert_eq!(lines.len(), 2);
+    let skipped: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(skipped["kind"], "retraction");
+    assert_eq!(skipped["slot"], 12);
+    assert_eq!(skipped["pubkey"], pubkey.to_string());
+    assert_eq!(skipped["reason"], "skipped");
+    assert_eq!(skipped["restored_slot"], 10);
+    let dead: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+    assert_eq!(dead["reason"], "dead");
+    assert!(dead["restored_slot"].is_null());
+}
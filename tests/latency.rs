@@ -0,0 +1,128 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime},
+};
+
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_proto::{
+    prelude::{SubscribeRequest, SubscribeRequestFilterAccounts},
+    prost_types::Timestamp,
+};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{AccountDecoder, AccountUpdate, NewPoolFilter, Pipeline},
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+    metrics::{Histogram, LatencyStage, Metrics, Scope, Timings},
+    stream::{GrpcSource, SourceUpdate, UpdateSource},
+    testing::{account_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn histogram_quantiles_resolve_to_bucket_bounds() {
+    let mut histogram = Histogram::default();
+    assert_eq!(histogram.quantile(0.5), Duration::ZERO);
+    for ms in 1..=100 {
+        histogram.record(Duration::from_millis(ms));
+    }
+    assert_eq!(histogram.count, 100);
+    assert_eq!(histogram.min_us, 1_000);
+    assert_eq!(histogram.max_us, 100_000);
+    assert_eq!(histogram.mean(), Duration::from_micros(50_500));
+    assert_eq!(histogram.quantile(0.01), Duration::from_millis(1));
+    assert_eq!(histogram.quantile(0.5), Duration::from_millis(50));
+    // the 100 ms bucket, capped at the maximum seen
+    assert_eq!(histogram.quantile(0.99), Duration::from_millis(100));
+
+    histogram.record(Duration::from_secs(60));
+    assert_eq!(histogram.buckets.last(), Some(&1));
+    assert_eq!(histogram.quantile(1.0), Duration::from_secs(60));
+}
+
+#[test]
+fn pipeline_stamps_stages_per_dex() {
+    let metrics = Metrics::new();
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(AccountDecoder::default())
+        .with_handler(NewPoolFilter);
+    let mut timings = Timings {
+        created_at: Some(SystemTime::now() - Duration::from_millis(30)),
+        ..Default::default()
+    };
+    timings.receive();
+    let update = AccountUpdate {
+        pubkey: Pubkey::new_unique(),
+        owner: Dex::RaydiumAmmV4.program_id(),
+        data: LIQUIDITY_STATE_LAYOUT_V4 {
+            baseMint: Pubkey::new_unique(),
+            quoteMint: Pubkey::new_unique(),
+            ..Default::default()
+        }
+        .to_bytes(),
+        timings,
+        ..Default::default()
+    };
+    let before = Instant::now();
+    let account = match pipeline.process(update).unwrap() {
+        mevbot_ws_rust::dex_processor::ProcessOutcome::Completed(account) => account,
+        other => panic!("expected completion, got {:?}", other),
+    };
+    let timings = account.update.timings;
+    assert!(timings.decoded.unwrap() <= timings.decided.unwrap());
+    assert!(timings.decided.unwrap() >= before);
+    assert!(timings.acted.is_none(), "no act-stage handler");
+    assert!(timings.total().unwrap() >= Duration::from_millis(30));
+
+    let series = metrics.latency.snapshot();
+    let stages: Vec<_> = series.iter().map(|s| (s.scope, s.label.as_str(), s.stage)).collect();
+    assert_eq!(
+        stages,
+        vec![
+            (Scope::Dex, "raydium_amm_v4", LatencyStage::Decode),
+            (Scope::Dex, "raydium_amm_v4", LatencyStage::Decide),
+            (Scope::Dex, "raydium_amm_v4", LatencyStage::Total),
+        ]
+    );
+    assert!(series.iter().all(|s| s.histogram.count == 1));
+}
+
+#[tokio::test]
+async fn grpc_source_records_network_latency_per_endpoint() {
+    let owner = Pubkey::new_unique();
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "owner".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let metrics = Metrics::new();
+    let mut source = GrpcSource::connect(server.endpoint(), None, request)
+        .await
+        .unwrap()
+        .with_metrics(metrics.clone());
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    let mut update = account_update(&Pubkey::new_unique(), &owner, 1, 1, vec![]);
+    update.created_at = Some(Timestamp::from(SystemTime::now() - Duration::from_millis(20)));
+    server.push(update);
+    let account = match tokio::time::timeout(TIMEOUT, source.next_update()).await.unwrap().unwrap() {
+        Some(SourceUpdate::Account(account)) => account,
+        other => panic!("expected account, got {:?}", other),
+    };
+    assert!(account.timings.network().unwrap() >= Duration::from_millis(20));
+    assert!(account.timings.received.is_some());
+
+    let series = metrics.latency.snapshot();
+    assert_eq!(series.len(), 1);
+    assert_eq!((series[0].scope, series[0].label.as_str()), (Scope::Source, server.endpoint().as_str()));
+    assert_eq!(series[0].stage, LatencyStage::Network);
+    assert!(series[0].histogram.min_us >= 20_000);
+}
@@ -0,0 +1,215 @@
+use std::{collections::HashMap, mem::offset_of, str::FromStr, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::{
+    geyser::{
+        subscribe_request_filter_accounts_filter::Filter as AccountFilterKind,
+        subscribe_request_filter_accounts_filter_memcmp::Data as MemcmpData,
+        subscribe_update::UpdateOneof, SubscribeRequestFilterAccountsFilter,
+        SubscribeRequestFilterAccountsFilterMemcmp,
+    },
+    prelude::{
+        SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions,
+        SubscribeRequestPing, SubscribeUpdate,
+    },
+    tonic::Status,
+};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{
+        AccountDecoder, AccountUpdate, NewPoolFilter, Pipeline, PipelineEvent, ProcessOutcome,
+    },
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+    testing::{account_update, transaction_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn wsol() -> Pubkey {
+    Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap()
+}
+
+fn v4_pool(quote_mint: Pubkey, swapped: bool) -> Vec<u8> {
+    let mut state = LIQUIDITY_STATE_LAYOUT_V4 {
+        baseMint: Pubkey::new_unique(),
+        quoteMint: quote_mint,
+        marketProgramId: Dex::OpenBook.program_id(),
+        ..Default::default()
+    };
+    if swapped {
+        state.swapQuoteInAmount = 1;
+    }
+    bytemuck::bytes_of(&state).to_vec()
+}
+
+fn memcmp(offset: usize, data: MemcmpData) -> SubscribeRequestFilterAccountsFilter {
+    SubscribeRequestFilterAccountsFilter {
+        filter: Some(AccountFilterKind::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+            offset: offset as u64,
+            data: Some(data),
+        })),
+    }
+}
+
+/// The same account filter `main.rs` subscribes with
+fn new_pool_request() -> SubscribeRequest {
+    SubscribeRequest {
+        accounts: HashMap::from([(
+            "new_lp_v4".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![Dex::RaydiumAmmV4.program_id().to_string()],
+                filters: vec![
+                    memcmp(
+                        offset_of!(LIQUIDITY_STATE_LAYOUT_V4, quoteMint),
+                        MemcmpData::Base58(wsol().to_string()),
+                    ),
+                    memcmp(
+                        offset_of!(LIQUIDITY_STATE_LAYOUT_V4, marketProgramId),
+                        MemcmpData::Base58(Dex::OpenBook.program_id().to_string()),
+                    ),
+                    memcmp(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, swapQuoteInAmount), MemcmpData::Bytes(vec![0])),
+                    memcmp(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, swapBaseOutAmount), MemcmpData::Bytes(vec![0])),
+                ],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+async fn next_update(
+    stream: &mut (impl futures::Stream<Item = Result<SubscribeUpdate, Status>> + Unpin),
+) -> SubscribeUpdate {
+    tokio::time::timeout(TIMEOUT, stream.next())
+        .await
+        .expect("timed out waiting for an update")
+        .expect("stream closed")
+        .expect("stream error")
+}
+
+#[tokio::test]
+async fn new_pool_flows_from_subscription_to_pipeline_event() {
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let mut client = GeyserGrpcClient::build_from_shared(server.endpoint())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let (mut tx, mut stream) = client.subscribe().await.unwrap();
+    tx.send(new_pool_request()).await.unwrap();
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    let owner = Dex::RaydiumAmmV4.program_id();
+    let (new_pool, usdc_pool, traded_pool) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    // rejected by the server: wrong quote mint, already swapped, wrong owner
+    server.push(account_update(&usdc_pool, &owner, 1, 1, v4_pool(Pubkey::new_unique(), false)));
+    server.push(account_update(&traded_pool, &owner, 2, 1, v4_pool(wsol(), true)));
+    server.push(account_update(&new_pool, &Pubkey::new_unique(), 3, 1, v4_pool(wsol(), false)));
+    server.push(account_update(&new_pool, &owner, 4, 1, v4_pool(wsol(), false)));
+
+    let update = next_update(&mut stream).await;
+    assert_eq!(update.filters, vec!["new_lp_v4".to_string()]);
+
+    let mut pipeline = Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(NewPoolFilter);
+    let account = AccountUpdate::from_subscribe_update(update).unwrap().unwrap();
+    assert_eq!((account.pubkey, account.slot), (new_pool, 4));
+    let ProcessOutcome::Completed(processed) = pipeline.process(account).unwrap() else {
+        panic!("new pool was skipped");
+    };
+    assert_eq!(
+        processed.events,
+        vec![PipelineEvent::NewPool {
+            dex: Dex::RaydiumAmmV4,
+            pool: new_pool
+        }]
+    );
+
+    // the stream ends cleanly once the server goes away
+    server.shutdown();
+    let end = tokio::time::timeout(TIMEOUT, stream.next()).await.unwrap();
+    assert!(end.is_none(), "expected end of stream, got {:?}", end);
+}
+
+#[tokio::test]
+async fn ping_and_transactions() {
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let mut client = GeyserGrpcClient::build_from_shared(server.endpoint())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let (mut tx, mut stream) = client.subscribe().await.unwrap();
+
+    let pool = Pubkey::new_unique();
+    tx.send(SubscribeRequest {
+        transactions: HashMap::from([(
+            "pool_txs".to_string(),
+            SubscribeRequestFilterTransactions {
+                failed: Some(false),
+                account_include: vec![pool.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    tx.send(SubscribeRequest {
+        ping: Some(SubscribeRequestPing { id: 7 }),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    match next_update(&mut stream).await.update_oneof {
+        Some(UpdateOneof::Pong(pong)) => assert_eq!(pong.id, 7),
+        other => panic!("expected pong, got {:?}", other),
+    }
+
+    server.push(transaction_update(10, [1; 64], &[Pubkey::new_unique()], false));
+    server.push(transaction_update(11, [2; 64], &[pool], true));
+    server.push(transaction_update(12, [3; 64], &[Pubkey::new_unique(), pool], false));
+    match next_update(&mut stream).await.update_oneof {
+        Some(UpdateOneof::Transaction(tx)) => {
+            assert_eq!(tx.slot, 12);
+            assert_eq!(tx.transaction.unwrap().signature, vec![3; 64]);
+        }
+        other => panic!("expected transaction, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn invalid_filter_is_rejected() {
+    let server = MockGeyserServer::start().await.unwrap();
+    let mut client = GeyserGrpcClient::build_from_shared(server.endpoint())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let (mut tx, mut stream) = client.subscribe().await.unwrap();
+    tx.send(SubscribeRequest {
+        accounts: HashMap::from([(
+            "bad".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec!["not a pubkey".to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+
+    let status = tokio::time::timeout(TIMEOUT, stream.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(status.code(), yellowstone_grpc_proto::tonic::Code::InvalidArgument);
+}
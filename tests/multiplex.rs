@@ -0,0 +1,113 @@
+use std::{collections::HashMap, time::Duration};
+
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{
+    SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions,
+};
+
+use mevbot_ws_rust::{
+    stream::{GrpcSource, Multiplexer, SourceUpdate, UpdateSource},
+    testing::{account_update, transaction_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn next(source: &mut dyn UpdateSource) -> Option<SourceUpdate> {
+    tokio::time::timeout(TIMEOUT, source.next_update())
+        .await
+        .expect("timed out waiting for an update")
+        .unwrap()
+}
+
+async fn servers(owner: &Pubkey, watched: &Pubkey) -> (Vec<MockGeyserServer>, Multiplexer) {
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "owner".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        transactions: HashMap::from([(
+            "watched".to_string(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![watched.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let mut servers = Vec::new();
+    let mut sources: Vec<Box<dyn UpdateSource>> = Vec::new();
+    for _ in 0..2 {
+        let mut server = MockGeyserServer::start().await.unwrap();
+        sources.push(Box::new(
+            GrpcSource::connect(server.endpoint(), None, request.clone()).await.unwrap(),
+        ));
+        server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+        servers.push(server);
+    }
+    (servers, Multiplexer::new(sources))
+}
+
+#[tokio::test]
+async fn forwards_first_copy_only_and_tracks_winners() {
+    let (owner, watched) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (servers, mut mux) = servers(&owner, &watched).await;
+    let stats = mux.stats();
+    let (fast, slow) = (&servers[0], &servers[1]);
+
+    let pool = Pubkey::new_unique();
+    let update = account_update(&pool, &owner, 10, 1, vec![1]);
+    fast.push(update.clone());
+    match next(&mut mux).await {
+        Some(SourceUpdate::Account(account)) => assert_eq!((account.pubkey, account.slot), (pool, 10)),
+        other => panic!("expected account, got {:?}", other),
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    slow.push(update);
+
+    // same signature from both, the slow endpoint wins this one
+    let tx = transaction_update(10, [4; 64], &[watched], false);
+    slow.push(tx.clone());
+    match next(&mut mux).await {
+        Some(SourceUpdate::Transaction(tx)) => assert_eq!(tx.signature.as_ref(), [4; 64]),
+        other => panic!("expected transaction, got {:?}", other),
+    }
+    fast.push(tx);
+
+    // a new write of the same account in a later slot is not a duplicate
+    fast.push(account_update(&pool, &owner, 11, 1, vec![2]));
+    match next(&mut mux).await {
+        Some(SourceUpdate::Account(account)) => assert_eq!(account.slot, 11),
+        other => panic!("expected account, got {:?}", other),
+    }
+
+    // nothing else is forwarded, but draining counts the late copies
+    assert!(tokio::time::timeout(Duration::from_millis(200), mux.next_update()).await.is_err());
+    let stats = stats.snapshot();
+    assert_eq!((stats[0].received, stats[0].wins, stats[0].late), (3, 2, 1));
+    assert_eq!((stats[1].received, stats[1].wins, stats[1].late), (2, 1, 1));
+    assert!(stats[1].max_lag >= Duration::from_millis(20), "{:?}", stats[1].max_lag);
+    assert!((stats[0].win_rate() - 2.0 / 3.0).abs() < 1e-9);
+    assert!(stats.iter().all(|s| s.connected));
+}
+
+#[tokio::test]
+async fn survives_an_endpoint_going_away() {
+    let (owner, watched) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (mut servers, mut mux) = servers(&owner, &watched).await;
+    let stats = mux.stats();
+
+    servers.remove(0).shutdown();
+    let pool = Pubkey::new_unique();
+    servers[0].push(account_update(&pool, &owner, 3, 1, vec![]));
+    match next(&mut mux).await {
+        Some(SourceUpdate::Account(account)) => assert_eq!(account.pubkey, pool),
+        other => panic!("expected account, got {:?}", other),
+    }
+
+    servers.remove(0).shutdown();
+    assert!(next(&mut mux).await.is_none());
+    assert!(stats.snapshot().iter().all(|s| !s.connected));
+}
@@ -0,0 +1,278 @@
+use std::path::PathBuf;
+
+use solana_program::{pubkey, pubkey::Pubkey};
+
+use mevbot_ws_rust::{
+    dex::{
+        pda::{meteora_dlmm, meteora_pools, raydium_clmm, raydium_cpmm},
+        Dex,
+    },
+    dex_processor::{
+        verify::verify, AccountDecoder, AccountUpdate, DecodedAccount, PdaMismatch, Pipeline, ProcessOutcome,
+        VerifyPdas,
+    },
+    testing::Snapshot,
+};
+
+const WSOL: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+const USDC: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+
+fn fixture(layout: &str) -> (Pubkey, DecodedAccount) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/layouts/{}.json", layout));
+    let (pubkey, account) = Snapshot::load(path).unwrap().accounts.into_iter().next().unwrap();
+    (pubkey, DecodedAccount::decode_layout(layout, &account.data).unwrap())
+}
+
+#[test]
+fn derives_well_known_addresses() {
+    assert_eq!(raydium_cpmm::authority().0, pubkey!("GpMZbSM2GgvTKHJirzeGfMFoaZ8UR2X7F4v8vHTvxFbL"));
+    assert_eq!(meteora_pools::vault(&WSOL).0, pubkey!("FERjPVNEa7Udq8CEv68h6tPL46Tq7ieE49HrE2wea3XT"));
+    assert_eq!(meteora_pools::vault(&USDC).0, pubkey!("3ESUFCnRNgZ7Mn2mPPUMmXYaKU8jpnV9VtA17M7t2mHQ"));
+    assert_eq!(
+        meteora_pools::vault_token(&meteora_pools::vault(&WSOL).0).0,
+        pubkey!("HZeLxbZ9uHtSpwZC3LBr4Nubd14iHwz7bRSghRZf5VCG")
+    );
+}
+
+/// SOL/USDC pools on mainnet and the accounts they reference
+#[test]
+fn derives_mainnet_pool_accounts() {
+    let config = pubkey!("D4FPEruKEHrG5TenZ2mpDGEfu1iUvTiqBxvpU8HLBvC2");
+    let pool = pubkey!("7JuwJuNU88gurFnyWeiyGKbFmExMWcmRZntn9imEzdny");
+    assert_eq!(raydium_cpmm::pool(&config, &WSOL, &USDC).0, pool);
+    assert_eq!(raydium_cpmm::vault(&pool, &WSOL).0, pubkey!("7VLUXrnSSDo9BfCa4NWaQs68g7ddDY1sdXBKW6Xswj9Y"));
+    assert_eq!(raydium_cpmm::vault(&pool, &USDC).0, pubkey!("3rzbbW5Q8MA7sCaowf28hNgACNPecdS2zceWy7Ptzua9"));
+    assert_eq!(raydium_cpmm::observation(&pool).0, pubkey!("4MYrPgjgFceyhtwhG1ZX8UVb4wn1aQB5wzMimtFqg7U8"));
+
+    for (config, pool) in [
+        (pubkey!("9iFER3bpjf1PTTCQCfTRu17EJgvsxo9pVyA9QWwEuX4x"), pubkey!("8sLbNZoA1cfnvMJLPfp98ZLAnFSYCFApfJKMbiXNLwxj")),
+        (pubkey!("HfERMT5DRA6C1TAqecrJQFpmkf3wsWTMncqnj3RDg5aw"), pubkey!("2QdhepnKRTLjjSqPL1PtKNwqrUkoLee5Gqs8bvZhRdMv")),
+        (pubkey!("3h2e43PunVA5K34vwKCLHWhZF4aZpyaC9RmxvshGAQpL"), pubkey!("3ucNos4NbumPLZNWztqGHNFFgkHeRMBQAVemeeomsUxv")),
+    ] {
+        assert_eq!(raydium_clmm::pool(&config, &WSOL, &USDC).0, pool);
+    }
+    let pool = pubkey!("3ucNos4NbumPLZNWztqGHNFFgkHeRMBQAVemeeomsUxv");
+    assert_eq!(raydium_clmm::vault(&pool, &WSOL).0, pubkey!("4ct7br2vTPzfdmY3S5HLtTxcGSBfn6pnw98hsS6v359A"));
+    assert_eq!(raydium_clmm::vault(&pool, &USDC).0, pubkey!("5it83u57VRrVgc51oNV19TTmAJuffPx5GtGwQr7gQNUo"));
+    assert_eq!(
+        raydium_clmm::tick_array_bitmap_extension(&pool).0,
+        pubkey!("4NFvUKqknMpoe6CWTzK758B8ojVLzURL5pC6MtiaJ8TQ")
+    );
+
+    assert_eq!(meteora_dlmm::lb_pair(&WSOL, &USDC, 20).0, pubkey!("BVRbyLjjfSBcoyiYFuxbgKYnWuiFaF9CSXEa5vdSZ9Hh"));
+    let pool = pubkey!("5rCf1DM8LjKTw4YqhnoLcngyZYeNnQqztScTogYHAS6");
+    assert_eq!(meteora_dlmm::lb_pair(&WSOL, &USDC, 4).0, pool);
+    assert_eq!(meteora_dlmm::reserve(&pool, &WSOL).0, pubkey!("EYj9xKw6ZszwpyNibHY7JD5o3QgTVrSdcBp1fMJhrR9o"));
+    assert_eq!(meteora_dlmm::reserve(&pool, &USDC).0, pubkey!("CoaxzEh8p5YyGLcj36Eo3cUThVJxeKCs7qvLAGDYwBcz"));
+    assert_eq!(meteora_dlmm::oracle(&pool).0, pubkey!("59YuGWPunbchD2mbi9U7qvjWQKQReGeepn4ZSr9zz9Li"));
+}
+
+/// The 1% constant product pool recorded in `src/config/meteora_pools.json`: its address is
+/// pinned here and checked against the mints, LP mint and vault LPs recorded there, then the
+/// `meteora_pools` fixture built from the same record must verify without rewriting any field
+#[test]
+fn recorded_meteora_pool_passes_verification() {
+    let pool = pubkey!("6pqDh4MLY5NNsgXa2Phjzpv1pXtPmiRGcR4kYzmi2cMm");
+    let token_a = pubkey!("7GCihgDB8fe6KNjn2MYtkzZcRjQy3t9GHdC8uHYmW2hr");
+    let token_b = pubkey!("63LfDmNb3MQ8mw9MtZ2To9bEA2M71kZUUGq5tiJxcqj9");
+    assert_eq!(meteora_pools::permissionless_pool_with_fee_tier(0, &token_a, &token_b, 100).0, pool);
+    assert_eq!(meteora_pools::lp_mint(&pool).0, pubkey!("EZ8YuEa262shBR8x9VRqiYS8bktKwGbsN2KhX46KbUh8"));
+    assert_eq!(
+        meteora_pools::vault_lp(&pubkey!("Bi9JVJMSghv1Eg7KS1TWvnpUKtiX5afazJVCzDJgRCe2"), &pool).0,
+        pubkey!("HHUpWq6jsX4jaKCqPcgZErfSR2tBXfHTMBkMdzREdG66")
+    );
+    assert_eq!(
+        meteora_pools::vault_lp(&pubkey!("HqziphMUJWawvfZhZrMZs2yKL2oD5bM5QTzqLJPUQ463"), &pool).0,
+        pubkey!("FVt24YPLopV8ebda3QqLYJMwSyyPwfK5HMddoUyfEGUC")
+    );
+
+    let (pubkey, decoded) = fixture("meteora_pools");
+    assert_eq!(pubkey, pool);
+    assert_eq!(verify(&pool, &decoded), vec![]);
+}
+
+#[test]
+fn mint_order_matters_only_where_the_program_sorts() {
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(meteora_dlmm::lb_pair(&a, &b, 25), meteora_dlmm::lb_pair(&b, &a, 25));
+    assert_ne!(meteora_dlmm::lb_pair(&a, &b, 25), meteora_dlmm::lb_pair(&a, &b, 10));
+    assert_ne!(meteora_dlmm::lb_pair(&a, &b, 25), meteora_dlmm::lb_pair_with_base_factor(&a, &b, 25, 10_000));
+    assert_eq!(
+        meteora_pools::permissionless_pool(0, &a, &b),
+        meteora_pools::permissionless_pool(0, &b, &a)
+    );
+    assert_ne!(
+        meteora_pools::permissionless_pool(0, &a, &b),
+        meteora_pools::permissionless_pool(1, &a, &b)
+    );
+
+    let config = Pubkey::new_unique();
+    assert_ne!(raydium_cpmm::pool(&config, &a, &b), raydium_cpmm::pool(&config, &b, &a));
+    assert_ne!(raydium_clmm::pool(&config, &a, &b), raydium_clmm::pool(&config, &b, &a));
+}
+
+#[test]
+fn array_indexes_round_towards_negative_infinity() {
+    for (tick, spacing, start) in [(0, 1, 0), (59, 1, 0), (60, 1, 60), (-1, 1, -60), (-60, 1, -60), (-61, 1, -120), (-1, 10, -600), (1_234, 10, 1_200)] {
+        assert_eq!(raydium_clmm::tick_array_start_index(tick, spacing), start, "tick {} spacing {}", tick, spacing);
+    }
+    for (bin, index) in [(0, 0), (69, 0), (70, 1), (-1, -1), (-70, -1), (-71, -2)] {
+        assert_eq!(meteora_dlmm::bin_array_index(bin), index, "bin {}", bin);
+    }
+
+    let pool = Pubkey::new_unique();
+    assert_ne!(raydium_clmm::tick_array(&pool, -60), raydium_clmm::tick_array(&pool, 60));
+    assert_ne!(meteora_dlmm::bin_array(&pool, -1), meteora_dlmm::bin_array(&pool, 1));
+    assert_ne!(
+        raydium_clmm::tick_array_bitmap_extension(&pool),
+        meteora_dlmm::bin_array_bitmap_extension(&pool)
+    );
+}
+
+/// Fixture pools with every referenced account replaced by its PDA. Only the mismatch reporting
+/// is tested with these, [`derives_mainnet_pool_accounts`] pins the derivations themselves.
+fn derived_pools() -> Vec<(Pubkey, DecodedAccount)> {
+    let mut pools = Vec::new();
+
+    let (pool, mut decoded) = fixture("raydium_cpmm");
+    if let DecodedAccount::RaydiumCpmm(state) = &mut decoded {
+        state.token0Vault = raydium_cpmm::vault(&pool, &state.token0Mint).0;
+        state.token1Vault = raydium_cpmm::vault(&pool, &state.token1Mint).0;
+        state.lpMint = raydium_cpmm::lp_mint(&pool).0;
+        state.observationKey = raydium_cpmm::observation(&pool).0;
+    }
+    pools.push((pool, decoded));
+
+    let (_, mut decoded) = fixture("raydium_clmm");
+    let DecodedAccount::RaydiumClmm(state) = &mut decoded else { unreachable!() };
+    let pool = raydium_clmm::pool(&state.amm_config, &state.token_mint0, &state.token_mint1).0;
+    state.token_vault0 = raydium_clmm::vault(&pool, &state.token_mint0).0;
+    state.token_vault1 = raydium_clmm::vault(&pool, &state.token_mint1).0;
+    pools.push((pool, decoded));
+
+    let (pool, mut decoded) = fixture("meteora_dlmm_lb_pair");
+    if let DecodedAccount::MeteoraDlmm(state) = &mut decoded {
+        state.reserve_x = meteora_dlmm::reserve(&pool, &state.token_x_mint).0;
+        state.reserve_y = meteora_dlmm::reserve(&pool, &state.token_y_mint).0;
+        state.oracle = meteora_dlmm::oracle(&pool).0;
+    }
+    pools.push((pool, decoded));
+
+    // rebuilt from src/config, already consistent
+    pools.push(fixture("meteora_pools"));
+
+    pools
+}
+
+#[test]
+fn derived_accounts_pass_verification() {
+    for (pool, decoded) in derived_pools() {
+        assert_eq!(verify(&pool, &decoded), vec![], "{}", decoded.layout_name());
+    }
+}
+
+#[test]
+fn spoofed_accounts_are_reported() {
+    let fake = Pubkey::new_unique();
+    for (pool, mut decoded) in derived_pools() {
+        let (field, expected) = match &mut decoded {
+            DecodedAccount::RaydiumCpmm(state) => ("token1Vault", std::mem::replace(&mut state.token1Vault, fake)),
+            DecodedAccount::RaydiumClmm(state) => ("token_vault0", std::mem::replace(&mut state.token_vault0, fake)),
+            DecodedAccount::MeteoraDlmm(state) => ("oracle", std::mem::replace(&mut state.oracle, fake)),
+            DecodedAccount::MeteoraPools(state) => ("b_vault_lp", std::mem::replace(&mut state.b_vault_lp, fake)),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            verify(&pool, &decoded),
+            vec![PdaMismatch { field, expected, found: fake }],
+            "{}",
+            decoded.layout_name()
+        );
+    }
+
+    // a CLMM pool at an address its seeds do not derive
+    let (pool, decoded) = derived_pools().remove(1);
+    let mismatches = verify(&fake, &decoded);
+    assert_eq!(mismatches[0], PdaMismatch { field: "pool", expected: pool, found: fake });
+}
+
+#[test]
+fn pipeline_skips_pools_with_spoofed_accounts() {
+    let mut pipeline = Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(VerifyPdas::default());
+    let (pool, mut decoded) = derived_pools().remove(0);
+    let update = |decoded: &DecodedAccount| AccountUpdate {
+        pubkey: pool,
+        owner: Dex::RaydiumCpmm.program_id(),
+        data: decoded.encode(),
+        ..Default::default()
+    };
+
+    assert!(matches!(pipeline.process(update(&decoded)).unwrap(), ProcessOutcome::Completed(_)));
+
+    let mut other = decoded.clone();
+    if let DecodedAccount::RaydiumCpmm(state) = &mut other {
+        state.lpMint = Pubkey::new_unique();
+    }
+    let spoofed = AccountUpdate {
+        pubkey: Pubkey::new_unique(),
+        ..update(&other)
+    };
+    let first = match pipeline.process(spoofed.clone()).unwrap() {
+        ProcessOutcome::Skipped { handler, reason, .. } => {
+            assert_eq!(handler, "verify_pdas");
+            assert!(reason.starts_with("spoofed accounts: token0Vault is "), "{}", reason);
+            reason
+        }
+        other => panic!("expected a skip, got {:?}", other),
+    };
+
+    // rejected pools stay rejected, with the reason from the first check
+    if let DecodedAccount::RaydiumCpmm(state) = &mut other {
+        state.lpSupply += 1;
+    }
+    match pipeline.process(AccountUpdate { data: other.encode(), ..spoofed }).unwrap() {
+        ProcessOutcome::Skipped { reason, .. } => assert_eq!(reason, first),
+        other => panic!("expected a skip, got {:?}", other),
+    }
+
+    // verified pools are not derived again
+    if let DecodedAccount::RaydiumCpmm(state) = &mut decoded {
+        state.lpSupply += 1;
+    }
+    assert!(matches!(pipeline.process(update(&decoded)).unwrap(), ProcessOutcome::Completed(_)));
+}
+
+#[test]
+fn verdicts_expire_for_pools_not_seen_for_a_while() {
+    let mut pipeline = Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(VerifyPdas::default().with_expiry(10));
+    let (pool, decoded) = derived_pools().remove(0);
+    let mut spoofed = decoded.clone();
+    if let DecodedAccount::RaydiumCpmm(state) = &mut spoofed {
+        state.lpMint = Pubkey::new_unique();
+    }
+    let update = |slot: u64, decoded: &DecodedAccount| AccountUpdate {
+        pubkey: pool,
+        owner: Dex::RaydiumCpmm.program_id(),
+        slot,
+        data: decoded.encode(),
+        ..Default::default()
+    };
+    let skipped = |outcome: ProcessOutcome| matches!(outcome, ProcessOutcome::Skipped { .. });
+
+    assert!(!skipped(pipeline.process(update(1, &decoded)).unwrap()));
+    // the verdict is kept while the pool keeps updating, even past the expiry
+    assert!(!skipped(pipeline.process(update(8, &spoofed)).unwrap()));
+    assert!(!skipped(pipeline.process(update(15, &spoofed)).unwrap()));
+
+    // another pool moves the slot on, the first one has not been seen for more than 10 slots
+    let other = AccountUpdate {
+        pubkey: Pubkey::new_unique(),
+        ..update(30, &decoded)
+    };
+    assert!(skipped(pipeline.process(other).unwrap()));
+    assert!(skipped(pipeline.process(update(30, &spoofed)).unwrap()));
+}
@@ -0,0 +1,182 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{
+        AccountDecoder, AccountUpdate, DecodedAccount, Flow, Handler, LoggingHandler, Pipeline, ProcessOutcome,
+        ProcessedAccount, RetractReason, Retraction, Stage,
+    },
+    testing::Snapshot,
+};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records every call into a shared log, skips or fails updates whose lamports match
+struct Probe {
+    name: &'static str,
+    stage: Stage,
+    log: Log,
+    skip_on: Option<u64>,
+    fail_on: Option<u64>,
+}
+
+impl Probe {
+    fn new(name: &'static str, stage: Stage, log: &Log) -> Self {
+        Self {
+            name,
+            stage,
+            log: log.clone(),
+            skip_on: None,
+            fail_on: None,
+        }
+    }
+}
+
+impl Handler for Probe {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    fn handle(&mut self, account: &mut ProcessedAccount) -> anyhow::Result<Flow> {
+        self.log.lock().unwrap().push(self.name.to_string());
+        let lamports = account.update.lamports;
+        if self.fail_on == Some(lamports) {
+            anyhow::bail!("boom");
+        }
+        if self.skip_on == Some(lamports) {
+            return Ok(Flow::Skip(format!("{} skipped", self.name)));
+        }
+        Ok(Flow::Continue)
+    }
+
+    fn retract(&mut self, retraction: &Retraction) -> anyhow::Result<()> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} retract {}", self.name, retraction.update.slot));
+        Ok(())
+    }
+}
+
+fn update(lamports: u64) -> AccountUpdate {
+    AccountUpdate {
+        pubkey: Pubkey::new_unique(),
+        slot: 9,
+        lamports,
+        ..Default::default()
+    }
+}
+
+fn take(log: &Log) -> Vec<String> {
+    std::mem::take(&mut *log.lock().unwrap())
+}
+
+#[test]
+fn handlers_run_in_stage_order_then_insertion_order() {
+    let log = Log::default();
+    let mut pipeline = Pipeline::new()
+        .with_handler(Probe::new("act", Stage::Act, &log))
+        .with_handler(Probe::new("filter", Stage::Filter, &log))
+        .with_handler(Probe::new("decode", Stage::Decode, &log))
+        .with_handler(Probe::new("act_2", Stage::Act, &log))
+        .with_handler(Probe::new("enrich", Stage::Enrich, &log))
+        .with_handler(Probe::new("decode_2", Stage::Decode, &log));
+    assert_eq!(pipeline.handler_names(), ["decode", "decode_2", "enrich", "filter", "act", "act_2"]);
+
+    assert!(matches!(pipeline.process(update(1)).unwrap(), ProcessOutcome::Completed(_)));
+    assert_eq!(take(&log), ["decode", "decode_2", "enrich", "filter", "act", "act_2"]);
+}
+
+#[test]
+fn skip_stops_later_handlers_and_names_the_skipper() {
+    let log = Log::default();
+    let mut filter = Probe::new("filter", Stage::Filter, &log);
+    filter.skip_on = Some(2);
+    let mut pipeline = Pipeline::new()
+        .with_handler(Probe::new("decode", Stage::Decode, &log))
+        .with_handler(filter)
+        .with_handler(Probe::new("act", Stage::Act, &log));
+
+    let input = update(2);
+    let pubkey = input.pubkey;
+    match pipeline.process(input).unwrap() {
+        ProcessOutcome::Skipped { handler, reason, account } => {
+            assert_eq!(handler, "filter");
+            assert_eq!(reason, "filter skipped");
+            assert_eq!(account.update.pubkey, pubkey);
+        }
+        other => panic!("expected a skip, got {:?}", other),
+    }
+    assert_eq!(take(&log), ["decode", "filter"]);
+
+    // the next update runs through again
+    assert!(matches!(pipeline.process(update(3)).unwrap(), ProcessOutcome::Completed(_)));
+    assert_eq!(take(&log), ["decode", "filter", "act"]);
+}
+
+#[test]
+fn errors_carry_the_handler_and_account() {
+    let log = Log::default();
+    let mut enrich = Probe::new("enrich", Stage::Enrich, &log);
+    enrich.fail_on = Some(4);
+    let mut pipeline = Pipeline::new()
+        .with_handler(enrich)
+        .with_handler(Probe::new("act", Stage::Act, &log));
+
+    let input = update(4);
+    let pubkey = input.pubkey;
+    let err = pipeline.process(input).unwrap_err();
+    assert_eq!(format!("{}", err), format!("handler enrich failed for {}", pubkey));
+    assert_eq!(format!("{:#}", err), format!("handler enrich failed for {}: boom", pubkey));
+    assert_eq!(take(&log), ["enrich"]);
+}
+
+#[test]
+fn retractions_reach_every_handler_in_stage_order() {
+    let log = Log::default();
+    let mut pipeline = Pipeline::new()
+        .with_handler(Probe::new("act", Stage::Act, &log))
+        .with_handler(Probe::new("decode", Stage::Decode, &log));
+
+    pipeline
+        .retract(&Retraction {
+            update: update(5),
+            reason: RetractReason::Dead,
+            restored: None,
+        })
+        .unwrap();
+    assert_eq!(take(&log), ["decode retract 9", "act retract 9"]);
+}
+
+#[test]
+fn serum_markets_are_logged_not_fatal() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layouts/serum_market.json");
+    let (pubkey, account) = Snapshot::load(path).unwrap().accounts.into_iter().next().unwrap();
+    let mut pipeline = Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(LoggingHandler);
+    let update = || AccountUpdate {
+        pubkey,
+        owner: Dex::OpenBook.program_id(),
+        data: account.data.clone(),
+        ..Default::default()
+    };
+    // used to print the market and exit the process
+    for _ in 0..2 {
+        match pipeline.process(update()).unwrap() {
+            ProcessOutcome::Completed(account) => {
+                assert!(matches!(account.decoded, Some(DecodedAccount::SerumMarket(_))));
+            }
+            other => panic!("expected the market to decode, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::{collections::HashMap, path::PathBuf, time::Duration};
+
+use solana_program::pubkey::Pubkey;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use yellowstone_grpc_proto::prelude::{SubscribeRequest, SubscribeRequestFilterAccounts};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{AccountDecoder, AccountUpdate, NewPoolFilter, Pipeline},
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+    metrics::{prometheus, Metrics, Timings, TxOutcome},
+    stream::{HealthConfig, ReconnectingSource, UpdateSource},
+    testing::{account_update, MockGeyserServer, Snapshot},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn scrape(addr: std::net::SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    response.split_once("\r\n\r\n").unwrap().1.to_string()
+}
+
+#[tokio::test]
+async fn scrape_exposes_counters_health_and_histograms() {
+    let owner = Pubkey::new_unique();
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "pools".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let metrics = Metrics::new();
+    let (addr, _server) = prometheus::serve(([127, 0, 0, 1], 0).into(), metrics.clone()).await.unwrap();
+
+    // stream side: one update through a monitored endpoint
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let mut source = ReconnectingSource::new(server.endpoint(), None, request, HealthConfig::default())
+        .with_metrics(metrics.clone());
+    metrics.register_health(&server.endpoint(), source.health());
+    let pending = tokio::spawn(async move { source.next_update().await });
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+    server.push(account_update(&Pubkey::new_unique(), &owner, 1, 1, vec![]));
+    tokio::time::timeout(TIMEOUT, pending).await.unwrap().unwrap().unwrap().unwrap();
+
+    // pipeline side: a new pool and a drifted one
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(AccountDecoder::default())
+        .with_handler(NewPoolFilter);
+    let mut timings = Timings::default();
+    timings.receive();
+    let pool = LIQUIDITY_STATE_LAYOUT_V4 {
+        baseMint: Pubkey::new_unique(),
+        quoteMint: Pubkey::new_unique(),
+        ..Default::default()
+    };
+    let drifted = LIQUIDITY_STATE_LAYOUT_V4 { status: 99, ..pool };
+    for data in [pool.to_bytes(), drifted.to_bytes()] {
+        pipeline
+            .process(AccountUpdate {
+                pubkey: Pubkey::new_unique(),
+                owner: Dex::RaydiumAmmV4.program_id(),
+                data,
+                timings,
+                ..Default::default()
+            })
+            .unwrap();
+    }
+    metrics.record_transaction(Some(Dex::RaydiumAmmV4), TxOutcome::Sent);
+
+    let body = scrape(addr).await;
+    let endpoint = server.endpoint();
+    for line in [
+        format!("mevbot_updates_received_total{{source=\"{endpoint}\",filter=\"pools\"}} 1"),
+        "mevbot_decode_failures_total{dex=\"raydium_amm_v4\",layout=\"raydium_amm_v4\",kind=\"drift\"} 1".to_string(),
+        "mevbot_new_pools_total{dex=\"raydium_amm_v4\"} 1".to_string(),
+        "mevbot_transactions_total{dex=\"raydium_amm_v4\",outcome=\"sent\"} 1".to_string(),
+        format!("mevbot_stream_reconnects_total{{endpoint=\"{endpoint}\"}} 0"),
+        "# TYPE mevbot_latency_seconds histogram".to_string(),
+        "mevbot_latency_seconds_count{scope=\"dex\",label=\"raydium_amm_v4\",stage=\"decode\"} 2".to_string(),
+        "mevbot_latency_seconds_bucket{scope=\"dex\",label=\"raydium_amm_v4\",stage=\"decode\",le=\"+Inf\"} 2".to_string(),
+    ] {
+        assert!(body.lines().any(|l| l == line), "missing {:?} in\n{}", line, body);
+    }
+    assert_eq!(metrics.counter("new_pools_total", &["raydium_amm_v4"]), 1);
+}
+
+#[test]
+fn decode_failures_are_labelled_by_layout_and_kind() {
+    let metrics = Metrics::new();
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(AccountDecoder::default());
+    let process = |pipeline: &mut Pipeline, dex: Dex, data: Vec<u8>| {
+        pipeline
+            .process(AccountUpdate {
+                pubkey: Pubkey::new_unique(),
+                owner: dex.program_id(),
+                data,
+                ..Default::default()
+            })
+            .unwrap();
+    };
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layouts/raydium_cpmm.json");
+    let mut cpmm = Snapshot::load(path).unwrap().accounts.into_values().next().unwrap().data;
+    cpmm[0] ^= 1;
+    process(&mut pipeline, Dex::RaydiumCpmm, cpmm);
+    let drifted = LIQUIDITY_STATE_LAYOUT_V4 {
+        status: 99,
+        ..Default::default()
+    };
+    process(&mut pipeline, Dex::RaydiumAmmV4, bytemuck::bytes_of(&drifted).to_vec());
+    // other accounts of a DEX program and accounts of unknown programs fit no layout
+    process(&mut pipeline, Dex::RaydiumAmmV4, vec![0; 3]);
+    pipeline
+        .process(AccountUpdate {
+            pubkey: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            data: vec![0; 165],
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(
+        metrics.counter("decode_failures_total", &["raydium_cpmm", "raydium_cpmm", "discriminator"]),
+        1
+    );
+    assert_eq!(
+        metrics.counter("decode_failures_total", &["raydium_amm_v4", "raydium_amm_v4", "drift"]),
+        1
+    );
+    let rendered = prometheus::render(&metrics);
+    assert!(rendered.lines().any(|line| line
+        == "mevbot_decode_failures_total{dex=\"raydium_cpmm\",layout=\"raydium_cpmm\",kind=\"discriminator\"} 1"));
+    assert_eq!(rendered.lines().filter(|line| line.starts_with("mevbot_decode_failures_total{")).count(), 2);
+}
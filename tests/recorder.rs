@@ -0,0 +1,241 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use solana_program::pubkey::Pubkey;
+
+use mevbot_ws_rust::stream::{Recorder, RecordingReader, Replay, ReplaySpeed, RECORDING_MAGIC};
+use yellowstone_grpc_proto::{
+    geyser::{
+        subscribe_update::UpdateOneof, SubscribeUpdate, SubscribeUpdateAccount, SubscribeUpdateAccountInfo,
+        SubscribeUpdateTransaction, SubscribeUpdateTransactionInfo,
+    },
+    solana::storage::confirmed_block::{Message, Transaction},
+};
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn account_update(pubkey: &Pubkey, owner: &Pubkey, slot: u64, lamports: u64, data: Vec<u8>) -> SubscribeUpdate {
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+            account: Some(SubscribeUpdateAccountInfo {
+                pubkey: pubkey.to_bytes().to_vec(),
+                lamports,
+                owner: owner.to_bytes().to_vec(),
+                data,
+                write_version: slot,
+                ..Default::default()
+            }),
+            slot,
+            is_startup: false,
+        })),
+        ..Default::default()
+    }
+}
+
+fn transaction_update(slot: u64, signature: [u8; 64], account_keys: &[Pubkey]) -> SubscribeUpdate {
+    SubscribeUpdate {
+        update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: signature.to_vec(),
+                transaction: Some(Transaction {
+                    signatures: vec![signature.to_vec()],
+                    message: Some(Message {
+                        account_keys: account_keys.iter().map(|k| k.to_bytes().to_vec()).collect(),
+                        ..Default::default()
+                    }),
+                }),
+                ..Default::default()
+            }),
+            slot,
+        })),
+        ..Default::default()
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}.rec", name, std::process::id()))
+}
+
+fn at(nanos: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(nanos)
+}
+
+#[test]
+fn records_round_trip_compressed_or_not() {
+    let (pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let updates = [
+        (at(1_700_000_000_000_000_001), account_update(&pool, &owner, 10, 5, vec![1, 2, 3])),
+        (at(1_700_000_000_250_000_000), transaction_update(11, [7; 64], &[pool])),
+        (at(1_700_000_001_000_000_000), account_update(&pool, &owner, 12, 6, vec![0; 4096])),
+    ];
+
+    for compress in [false, true] {
+        let path = temp_path(&format!("recorder-round-trip-{}", compress));
+        {
+            let mut recorder = Recorder::create(&path, compress).unwrap();
+            for (received_at, update) in &updates {
+                recorder.record(update, *received_at).unwrap();
+            }
+            assert_eq!(recorder.records(), 3);
+        }
+
+        let bytes = std::fs::read(&path).unwrap();
+        if compress {
+            assert!(bytes.starts_with(&ZSTD_MAGIC), "compressed recordings are one zstd stream");
+        } else {
+            assert!(bytes.starts_with(RECORDING_MAGIC));
+        }
+
+        let read: Vec<_> = RecordingReader::open(&path).unwrap().map(Result::unwrap).collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.len(), updates.len());
+        for (record, (received_at, update)) in read.iter().zip(&updates) {
+            assert_eq!(record.received_at, *received_at, "compress={}", compress);
+            assert_eq!(&record.update, update, "compress={}", compress);
+        }
+    }
+}
+
+#[test]
+fn frames_are_timestamp_length_payload() {
+    let buffer = SharedBuffer::default();
+    let mut recorder = Recorder::new(Box::new(buffer.clone())).unwrap();
+    let update = account_update(&Pubkey::new_unique(), &Pubkey::new_unique(), 3, 1, vec![9; 10]);
+    recorder.record(&update, at(0x0102_0304_0506_0708)).unwrap();
+    recorder.flush().unwrap();
+
+    let bytes = buffer.0.lock().unwrap().clone();
+    let payload = yellowstone_grpc_proto::prost::Message::encode_to_vec(&update);
+    assert_eq!(&bytes[..8], RECORDING_MAGIC);
+    assert_eq!(&bytes[8..16], &0x0102_0304_0506_0708u64.to_le_bytes());
+    assert_eq!(&bytes[16..20], &(payload.len() as u32).to_le_bytes());
+    assert_eq!(&bytes[20..], payload.as_slice());
+
+    // timestamps before the epoch are stored as zero rather than failing the recording
+    recorder.record(&update, UNIX_EPOCH - Duration::from_secs(1)).unwrap();
+    let bytes = buffer.0.lock().unwrap().clone();
+    let second = 20 + payload.len();
+    assert_eq!(&bytes[second..second + 8], &[0; 8]);
+}
+
+#[test]
+fn damaged_recordings_are_rejected() {
+    let buffer = SharedBuffer::default();
+    let mut recorder = Recorder::new(Box::new(buffer.clone())).unwrap();
+    let update = account_update(&Pubkey::new_unique(), &Pubkey::new_unique(), 3, 1, vec![9; 10]);
+    recorder.record(&update, at(1)).unwrap();
+    recorder.record(&update, at(2)).unwrap();
+    let bytes = buffer.0.lock().unwrap().clone();
+
+    let reader = |bytes: Vec<u8>| RecordingReader::new(Box::new(io::Cursor::new(bytes)));
+
+    let err = reader(b"NOTAREC\x01".to_vec()).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    // a record cut short ends with an error after the complete ones
+    let mut truncated = reader(bytes[..bytes.len() - 3].to_vec()).unwrap();
+    assert_eq!(truncated.next().unwrap().unwrap().received_at, at(1));
+    assert_eq!(truncated.next().unwrap().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+    // a huge declared length does not allocate past the end of the file
+    let mut oversized = bytes[..8].to_vec();
+    oversized.extend(1u64.to_le_bytes());
+    oversized.extend(u32::MAX.to_le_bytes());
+    oversized.extend([0; 16]);
+    let mut oversized = reader(oversized).unwrap();
+    assert_eq!(oversized.next().unwrap().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+    // only the magic: an empty, valid recording
+    assert_eq!(reader(bytes[..8].to_vec()).unwrap().count(), 0);
+}
+
+#[test]
+fn replay_speeds_parse() {
+    assert_eq!("original".parse(), Ok(ReplaySpeed::Original));
+    assert_eq!("1".parse(), Ok(ReplaySpeed::Original));
+    assert_eq!("max".parse(), Ok(ReplaySpeed::Max));
+    assert_eq!("step".parse(), Ok(ReplaySpeed::Stepwise));
+    assert_eq!("10x".parse(), Ok(ReplaySpeed::Accelerated(10.0)));
+    assert_eq!("0.5".parse(), Ok(ReplaySpeed::Accelerated(0.5)));
+    assert!("0".parse::<ReplaySpeed>().is_err());
+    assert!("fast".parse::<ReplaySpeed>().is_err());
+}
+
+#[tokio::test]
+async fn replay_keeps_the_recorded_gaps() {
+    let path = temp_path("replay-pacing");
+    {
+        let mut recorder = Recorder::create(&path, true).unwrap();
+        let (pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for (slot, secs) in [(1, 100), (2, 140), (3, 300)] {
+            recorder
+                .record(&account_update(&pool, &owner, slot, 0, vec![]), at(secs * 1_000_000_000))
+                .unwrap();
+        }
+    }
+
+    // 40s and 200s recorded gaps, played back 1000x faster
+    let mut replay = Replay::open(&path, ReplaySpeed::Accelerated(1_000.0)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let started = tokio::time::Instant::now();
+    let mut elapsed = Vec::new();
+    while let Some(record) = replay.next().await {
+        record.unwrap();
+        elapsed.push(started.elapsed());
+    }
+    assert_eq!(elapsed.len(), 3);
+    let expected = [0, 40, 200].map(Duration::from_millis);
+    for (elapsed, expected) in elapsed.iter().zip(expected) {
+        assert!(*elapsed >= expected && *elapsed < expected + Duration::from_millis(35), "{:?} vs {:?}", elapsed, expected);
+    }
+}
+
+#[tokio::test]
+async fn stepwise_replay_waits_for_each_step() {
+    let buffer = SharedBuffer::default();
+    {
+        let mut recorder = Recorder::new(Box::new(buffer.clone())).unwrap();
+        let (pool, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for slot in 1..=2 {
+            recorder.record(&account_update(&pool, &owner, slot, 0, vec![]), at(slot)).unwrap();
+        }
+    }
+    let bytes = buffer.0.lock().unwrap().clone();
+    let reader = RecordingReader::new(Box::new(io::Cursor::new(bytes))).unwrap();
+    let mut replay = Replay::new(reader, ReplaySpeed::Stepwise);
+    let stepper = replay.stepper();
+
+    let wait = Duration::from_millis(50);
+    {
+        // the same future is polled again after the timeout, so the record it read is not lost
+        let first = replay.next();
+        tokio::pin!(first);
+        assert!(tokio::time::timeout(wait, &mut first).await.is_err());
+        stepper.step();
+        let record = tokio::time::timeout(wait, first).await.unwrap().unwrap().unwrap();
+        assert_eq!(record.received_at, at(1));
+    }
+    stepper.step();
+    stepper.step();
+    let record = tokio::time::timeout(wait, replay.next()).await.unwrap().unwrap().unwrap();
+    assert_eq!(record.received_at, at(2));
+    // out of records, the spare step is never needed
+    assert!(tokio::time::timeout(wait, replay.next()).await.unwrap().is_none());
+}
@@ -0,0 +1,39 @@
+use mevbot_ws_rust::dex::raydium_lp_v4::{
+    SerumMarketLayout, SERUM_FLAG_CRANK_AUTHORITY_REQUIRED, SERUM_FLAG_DISABLED, SERUM_FLAG_INITIALIZED,
+    SERUM_FLAG_MARKET, SERUM_FLAG_PERMISSIONED,
+};
+
+fn market(flags: u64) -> SerumMarketLayout {
+    SerumMarketLayout {
+        market_flags: flags,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn market_flags_match_serum_dex() {
+    let market_flags = SERUM_FLAG_INITIALIZED | SERUM_FLAG_MARKET;
+    let permissioned = market_flags | SERUM_FLAG_PERMISSIONED;
+    for flags in [market_flags, permissioned, permissioned | SERUM_FLAG_CRANK_AUTHORITY_REQUIRED] {
+        assert!(market(flags).check_flags(false), "{:#x}", flags);
+        assert!(market(flags).check_flags(true), "{:#x}", flags);
+        assert!(!market(flags | SERUM_FLAG_DISABLED).check_flags(false), "{:#x} disabled", flags);
+        assert!(market(flags | SERUM_FLAG_DISABLED).check_flags(true), "{:#x} disabled", flags);
+    }
+
+    for flags in [
+        0,
+        SERUM_FLAG_INITIALIZED,
+        SERUM_FLAG_MARKET,
+        // open orders, bids, ... are other account kinds
+        SERUM_FLAG_INITIALIZED | 1 << 2,
+        market_flags | 1 << 5,
+        // closed, unknown bits
+        market_flags | 1 << 8,
+        market_flags | 1 << 11,
+        // crank authority only exists on permissioned markets
+        market_flags | SERUM_FLAG_CRANK_AUTHORITY_REQUIRED,
+    ] {
+        assert!(!market(flags).check_flags(true), "{:#x}", flags);
+    }
+}
@@ -0,0 +1,144 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde_json::json;
+use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use mevbot_ws_rust::{dex::Dex, testing::Snapshot};
+
+fn ui_account(owner: &Pubkey, data: serde_json::Value) -> serde_json::Value {
+    json!({
+        "lamports": 2_039_280,
+        "data": data,
+        "owner": owner.to_string(),
+        "executable": false,
+        "rentEpoch": 18446744073709551615u64,
+        "space": 0,
+    })
+}
+
+fn rpc(result: serde_json::Value) -> String {
+    json!({"jsonrpc": "2.0", "result": result, "id": 1}).to_string()
+}
+
+#[test]
+fn loads_cli_dumps_and_program_accounts() {
+    let pool = Pubkey::new_unique();
+    let owner = Dex::RaydiumCpmm.program_id();
+    let data: Vec<u8> = (0..=255).cycle().take(637).collect();
+
+    let dump = json!({"pubkey": pool.to_string(), "account": ui_account(&owner, json!([BASE64.encode(&data), "base64"]))});
+    let path = std::env::temp_dir().join(format!("snapshot-{}.json", pool));
+    std::fs::write(&path, dump.to_string()).unwrap();
+    let snapshot = Snapshot::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let account = snapshot.get(&pool).unwrap();
+    assert_eq!((account.owner, account.lamports, &account.data), (owner, 2_039_280, &data));
+    assert_eq!(snapshot.slot, None);
+
+    // getProgramAccounts with context, one account zstd-compressed and one base58
+    let other = Pubkey::new_unique();
+    let compressed = zstd::encode_all(data.as_slice(), 3).unwrap();
+    let response = rpc(json!({
+        "context": {"slot": 321},
+        "value": [
+            {"pubkey": pool.to_string(), "account": ui_account(&owner, json!([BASE64.encode(compressed), "base64+zstd"]))},
+            {"pubkey": other.to_string(), "account": ui_account(&owner, json!([bs58::encode(&data[..8]).into_string(), "base58"]))},
+        ],
+    }));
+    let snapshot = Snapshot::from_json(&response).unwrap();
+    assert_eq!(snapshot.slot, Some(321));
+    assert_eq!(snapshot.get(&pool).unwrap().data, data);
+    assert_eq!(snapshot.get(&other).unwrap().data, &data[..8]);
+    assert_eq!(snapshot.owned_by(&owner).count(), 2);
+
+    // values without pubkeys need the keys passed in
+    let info = rpc(json!({"context": {"slot": 1}, "value": ui_account(&owner, json!(["", "base64"]))}));
+    assert!(Snapshot::from_json(&info).is_err());
+    let error = json!({"jsonrpc": "2.0", "error": {"code": -32602, "message": "bad"}, "id": 1}).to_string();
+    assert!(Snapshot::from_json(&error).unwrap_err().to_string().contains("RPC error"));
+}
+
+#[test]
+fn loads_multiple_accounts_with_json_parsed_token_data() {
+    let (vault, mint, missing) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let authority = Pubkey::new_unique();
+    let token = spl_token::id();
+    let response = rpc(json!({
+        "context": {"slot": 99},
+        "value": [
+            ui_account(&token, json!({
+                "program": "spl-token",
+                "parsed": {
+                    "type": "account",
+                    "info": {
+                        "isNative": true,
+                        "mint": mint.to_string(),
+                        "owner": authority.to_string(),
+                        "state": "initialized",
+                        "tokenAmount": {"amount": "5000", "decimals": 9, "uiAmount": 0.000005, "uiAmountString": "0.000005"},
+                        "rentExemptReserve": {"amount": "2039280", "decimals": 9, "uiAmount": 0.00203928, "uiAmountString": "0.00203928"},
+                    },
+                },
+                "space": 165,
+            })),
+            null,
+            ui_account(&token, json!({
+                "program": "spl-token",
+                "parsed": {
+                    "type": "mint",
+                    "info": {
+                        "decimals": 9,
+                        "freezeAuthority": null,
+                        "isInitialized": true,
+                        "mintAuthority": authority.to_string(),
+                        "supply": "1000000000",
+                    },
+                },
+                "space": 82,
+            })),
+        ],
+    }));
+    let snapshot = Snapshot::from_multiple_accounts(&[vault, missing, mint], &response).unwrap();
+    assert_eq!(snapshot.slot, Some(99));
+    assert!(snapshot.get(&missing).is_none());
+
+    let account = TokenAccount::unpack(&snapshot.get(&vault).unwrap().data).unwrap();
+    assert_eq!(
+        account,
+        TokenAccount {
+            mint,
+            owner: authority,
+            amount: 5000,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::Some(2_039_280),
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+    );
+    let decoded = Mint::unpack(&snapshot.get(&mint).unwrap().data).unwrap();
+    assert_eq!((decoded.supply, decoded.decimals), (1_000_000_000, 9));
+    assert_eq!(decoded.mint_authority, COption::Some(authority));
+
+    assert!(Snapshot::from_multiple_accounts(&[vault], &response).is_err());
+    let parsed_elsewhere = rpc(json!({"context": {"slot": 1}, "value": ui_account(&token, json!({
+        "program": "vote", "parsed": {}, "space": 3762,
+    }))}));
+    assert!(Snapshot::from_account_info(vault, &parsed_elsewhere).is_err());
+}
+
+#[test]
+fn merge_prefers_the_newer_snapshot() {
+    let key = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let at = |slot: u64, byte: u8| {
+        let response = rpc(json!({"context": {"slot": slot}, "value": ui_account(&owner, json!([BASE64.encode([byte]), "base64"]))}));
+        Snapshot::from_account_info(key, &response).unwrap()
+    };
+
+    let mut snapshot = at(10, 1);
+    snapshot.merge(at(5, 2));
+    assert_eq!((snapshot.slot, snapshot.get(&key).unwrap().data[0]), (Some(10), 1));
+    snapshot.merge(at(20, 3));
+    assert_eq!((snapshot.slot, snapshot.get(&key).unwrap().data[0]), (Some(20), 3));
+}
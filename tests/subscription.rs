@@ -0,0 +1,116 @@
+use std::{collections::HashMap, time::Duration};
+
+use solana_program::pubkey::Pubkey;
+use yellowstone_grpc_proto::prelude::{SubscribeRequest, SubscribeRequestFilterAccounts};
+
+use mevbot_ws_rust::{
+    dex_processor::{AccountDecoder, AccountUpdate, NewPoolFilter, Pipeline, WatchPoolVaults, POOL_VAULTS_FILTER},
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+    stream::{GrpcSource, SourceUpdate, SubscriptionController, UpdateSource},
+    testing::{account_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn owner_request(owner: &Pubkey) -> SubscribeRequest {
+    SubscribeRequest {
+        accounts: HashMap::from([(
+            "owner".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn merges_watched_accounts_into_the_base_request() {
+    let owner = Pubkey::new_unique();
+    let controller = SubscriptionController::new(owner_request(&owner));
+    let mut requests = controller.subscribe();
+    let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    assert!(controller.watch("dev_wallets", [a, b]));
+    assert!(!controller.watch("dev_wallets", [a]), "nothing new");
+    assert!(requests.has_changed().unwrap());
+    let request = requests.borrow_and_update().clone();
+    assert_eq!(request.accounts["owner"].owner, vec![owner.to_string()]);
+    let mut watched = request.accounts["dev_wallets"].account.clone();
+    watched.sort();
+    let mut expected = vec![a.to_string(), b.to_string()];
+    expected.sort();
+    assert_eq!(watched, expected);
+
+    assert!(controller.unwatch("dev_wallets", [a]));
+    assert_eq!(controller.watched("dev_wallets"), vec![b]);
+    assert!(controller.unwatch("dev_wallets", [b]));
+    assert!(!controller.request().accounts.contains_key("dev_wallets"));
+    assert!(!controller.unwatch("dev_wallets", [b]));
+}
+
+#[tokio::test]
+async fn watched_accounts_stream_without_reconnecting() {
+    let owner = Pubkey::new_unique();
+    let controller = SubscriptionController::new(owner_request(&owner));
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let mut source = GrpcSource::connect(server.endpoint(), None, controller.request())
+        .await
+        .unwrap()
+        .with_subscription(controller.subscribe());
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    let wallet = Pubkey::new_unique();
+    controller.watch("dev_wallets", [wallet]);
+    // connect request, then the merged one (the change coalesces with the resend on attach)
+    let pending = tokio::spawn(async move {
+        let update = tokio::time::timeout(TIMEOUT, source.next_update()).await;
+        (source, update)
+    });
+    server.wait_for_requests(2, TIMEOUT).await.unwrap();
+    server.push(account_update(&wallet, &Pubkey::new_unique(), 5, 1, vec![]));
+    let (mut source, update) = pending.await.unwrap();
+    match update.unwrap().unwrap() {
+        Some(SourceUpdate::Account(account)) => assert_eq!(account.pubkey, wallet),
+        other => panic!("expected the wallet, got {:?}", other),
+    }
+
+    controller.unwatch("dev_wallets", [wallet]);
+    let pending = tokio::spawn(async move { tokio::time::timeout(Duration::from_millis(300), source.next_update()).await });
+    server.wait_for_requests(3, TIMEOUT).await.unwrap();
+    server.push(account_update(&wallet, &Pubkey::new_unique(), 6, 1, vec![]));
+    assert!(pending.await.unwrap().is_err(), "unwatched account still streamed");
+}
+
+#[test]
+fn new_pools_get_their_vaults_watched() {
+    let controller = SubscriptionController::new(SubscribeRequest::default());
+    let mut pipeline = Pipeline::new()
+        .with_handler(AccountDecoder::default())
+        .with_handler(NewPoolFilter)
+        .with_handler(WatchPoolVaults::new(controller.clone()));
+
+    let (base_vault, quote_vault) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let state = LIQUIDITY_STATE_LAYOUT_V4 {
+        baseVault: base_vault,
+        quoteVault: quote_vault,
+        baseMint: Pubkey::new_unique(),
+        quoteMint: Pubkey::new_unique(),
+        ..Default::default()
+    };
+    pipeline
+        .process(AccountUpdate {
+            pubkey: Pubkey::new_unique(),
+            owner: mevbot_ws_rust::dex::Dex::RaydiumAmmV4.program_id(),
+            data: bytemuck::bytes_of(&state).to_vec(),
+            ..Default::default()
+        })
+        .unwrap();
+
+    let mut watched = controller.watched(POOL_VAULTS_FILTER);
+    watched.sort();
+    let mut expected = vec![base_vault, quote_vault];
+    expected.sort();
+    assert_eq!(watched, expected);
+}
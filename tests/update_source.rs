@@ -0,0 +1,172 @@
+use std::{collections::HashMap, time::{Duration, SystemTime}};
+
+use solana_client::rpc_response::{RpcLogsResponse, SlotTransactionStats, SlotUpdate as RpcSlotUpdate};
+use solana_program::{pubkey::Pubkey, vote};
+use solana_sdk::{signature::Signature, transaction::TransactionError};
+use yellowstone_grpc_proto::prelude::{
+    SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterTransactions,
+};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    server::BotEvent,
+    stream::{
+        FilterSet, GrpcSource, Recorder, Replay, ReplaySource, ReplaySpeed, SlotStatus, SlotUpdate, SourceUpdate,
+        TransactionUpdate, UpdateSource,
+    },
+    testing::{account_update, transaction_update, MockGeyserServer},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn next(source: &mut dyn UpdateSource) -> Option<SourceUpdate> {
+    tokio::time::timeout(TIMEOUT, source.next_update())
+        .await
+        .expect("timed out waiting for an update")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn grpc_source_normalizes_accounts_and_transactions() {
+    let mut server = MockGeyserServer::start().await.unwrap();
+    let (owner, watched) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            "owner".to_string(),
+            SubscribeRequestFilterAccounts {
+                owner: vec![owner.to_string()],
+                ..Default::default()
+            },
+        )]),
+        transactions: HashMap::from([(
+            "watched".to_string(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![watched.to_string()],
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let mut source = GrpcSource::connect(server.endpoint(), None, request).await.unwrap();
+    server.wait_for_subscribers(1, TIMEOUT).await.unwrap();
+
+    let pool = Pubkey::new_unique();
+    server.push(account_update(&pool, &owner, 5, 42, vec![1, 2, 3]));
+    server.push(transaction_update(6, [9; 64], &[watched], true));
+
+    match next(&mut source).await {
+        Some(SourceUpdate::Account(account)) => {
+            assert_eq!((account.pubkey, account.owner, account.slot), (pool, owner, 5));
+            assert_eq!((account.lamports, account.data), (42, vec![1, 2, 3]));
+        }
+        other => panic!("expected account, got {:?}", other),
+    }
+    match next(&mut source).await {
+        Some(SourceUpdate::Transaction(tx)) => {
+            assert_eq!(tx.slot, 6);
+            assert_eq!(tx.signature.as_ref(), [9; 64]);
+            assert_eq!(tx.account_keys, vec![watched]);
+            assert!(tx.failed);
+        }
+        other => panic!("expected transaction, got {:?}", other),
+    }
+
+    server.shutdown();
+    assert!(next(&mut source).await.is_none());
+}
+
+#[tokio::test]
+async fn replay_source_yields_recorded_updates_in_order() {
+    let path = std::env::temp_dir().join(format!("replay-source-{}.rec", std::process::id()));
+    let (owner, pubkey) = (Pubkey::new_unique(), Pubkey::new_unique());
+    {
+        let mut recorder = Recorder::create(&path, true).unwrap();
+        for slot in 1..=3 {
+            recorder
+                .record(&account_update(&pubkey, &owner, slot, 1, vec![slot as u8]), SystemTime::now())
+                .unwrap();
+        }
+    }
+
+    let mut source = ReplaySource::new(Replay::open(&path, ReplaySpeed::Max).unwrap());
+    let mut slots = Vec::new();
+    while let Some(update) = next(&mut source).await {
+        slots.push(update.slot());
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(slots, vec![1, 2, 3]);
+}
+
+#[test]
+fn websocket_slot_updates_map_to_yellowstone_statuses() {
+    let stats = SlotTransactionStats {
+        num_transaction_entries: 0,
+        num_successful_transactions: 0,
+        num_failed_transactions: 0,
+        max_transactions_per_entry: 0,
+    };
+    let cases = [
+        (RpcSlotUpdate::CreatedBank { slot: 5, parent: 3, timestamp: 0 }, Some(3), SlotStatus::CreatedBank),
+        (RpcSlotUpdate::Frozen { slot: 5, timestamp: 0, stats }, None, SlotStatus::Processed),
+        (RpcSlotUpdate::OptimisticConfirmation { slot: 5, timestamp: 0 }, None, SlotStatus::Confirmed),
+        (RpcSlotUpdate::Root { slot: 5, timestamp: 0 }, None, SlotStatus::Finalized),
+        (RpcSlotUpdate::Dead { slot: 5, timestamp: 0, err: "boom".to_string() }, None, SlotStatus::Dead),
+    ];
+    for (update, parent, status) in cases {
+        let description = format!("{:?}", update);
+        let update: SlotUpdate = update.into();
+        assert_eq!((update.slot, update.parent, update.status), (5, parent, status), "{}", description);
+    }
+}
+
+#[test]
+fn websocket_logs_name_the_mention_and_invoked_programs() {
+    let pool = Pubkey::new_unique();
+    let signature = Signature::from([7; 64]);
+    let cpmm = Dex::RaydiumCpmm.program_id();
+    let swap = |err| RpcLogsResponse {
+        signature: signature.to_string(),
+        err,
+        logs: vec![
+            format!("Program {} invoke [1]", cpmm),
+            "Program log: Instruction: SwapBaseInput".to_string(),
+            format!("Program {} invoke [2]", spl_token::ID),
+            format!("Program {} success", spl_token::ID),
+            format!("Program {} invoke [2]", spl_token::ID),
+            format!("Program {} success", spl_token::ID),
+            format!("Program {} consumed 30000 of 200000 compute units", cpmm),
+            format!("Program {} success", cpmm),
+        ],
+    };
+    let tx = TransactionUpdate::from_logs(9, pool, swap(None)).unwrap();
+    assert_eq!(tx.account_keys, vec![pool, cpmm, spl_token::ID]);
+    assert_eq!((tx.signature, tx.slot, tx.is_vote, tx.failed), (signature, 9, false, false));
+    assert!(matches!(BotEvent::swap(&tx), Some(BotEvent::Swaps { dex: Dex::RaydiumCpmm, .. })));
+
+    // the vote and failed flags are not part of `logsSubscribe`, the source checks them locally
+    let request = SubscribeRequest {
+        transactions: HashMap::from([(
+            "pool".to_string(),
+            SubscribeRequestFilterTransactions {
+                account_include: vec![pool.to_string()],
+                vote: Some(false),
+                failed: Some(false),
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+    let filters = FilterSet::try_from(&request).unwrap();
+    assert!(filters.matches(&SourceUpdate::Transaction(tx)));
+    let failed = TransactionUpdate::from_logs(9, pool, swap(Some(TransactionError::AccountInUse))).unwrap();
+    assert!(failed.failed);
+    assert!(!filters.matches(&SourceUpdate::Transaction(failed)));
+    let vote = RpcLogsResponse {
+        signature: signature.to_string(),
+        err: None,
+        logs: vec![format!("Program {} invoke [1]", vote::program::ID)],
+    };
+    let vote = TransactionUpdate::from_logs(9, pool, vote).unwrap();
+    assert!(vote.is_vote);
+    assert!(!filters.matches(&SourceUpdate::Transaction(vote)));
+}
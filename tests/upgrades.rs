@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+use mevbot_ws_rust::{
+    dex::Dex,
+    dex_processor::{
+        upgrades::{program_data_address, ProgramDataState},
+        AccountDecoder, AccountUpdate, Pipeline, PipelineEvent, ProcessOutcome, UpgradeGate, UpgradeMonitor,
+    },
+    metrics::Metrics,
+    testing::Snapshot,
+};
+
+fn program_data(slot: u64, authority: Option<Pubkey>, bytecode: &[u8]) -> Vec<u8> {
+    let mut data = 3u32.to_le_bytes().to_vec();
+    data.extend(slot.to_le_bytes());
+    match authority {
+        Some(authority) => {
+            data.push(1);
+            data.extend(authority.to_bytes());
+        }
+        None => data.extend([0; 33]),
+    }
+    data.extend(bytecode);
+    data
+}
+
+fn program_data_update(dex: Dex, slot: u64, data: Vec<u8>) -> AccountUpdate {
+    AccountUpdate {
+        pubkey: program_data_address(&dex.program_id()),
+        owner: bpf_loader_upgradeable::id(),
+        slot,
+        data,
+        ..Default::default()
+    }
+}
+
+fn cpmm_pool() -> AccountUpdate {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layouts/raydium_cpmm.json");
+    let (pubkey, account) = Snapshot::load(path).unwrap().accounts.into_iter().next().unwrap();
+    AccountUpdate {
+        pubkey,
+        owner: Dex::RaydiumCpmm.program_id(),
+        slot: 500,
+        data: account.data,
+        ..Default::default()
+    }
+}
+
+fn skip_reason(outcome: ProcessOutcome) -> Option<String> {
+    match outcome {
+        ProcessOutcome::Completed(_) => None,
+        ProcessOutcome::Skipped { reason, .. } => Some(reason),
+    }
+}
+
+#[test]
+fn parses_the_program_data_header() {
+    let authority = Pubkey::new_unique();
+    let state = ProgramDataState::parse(&program_data(42, Some(authority), b"\x7fELF")).unwrap();
+    assert_eq!(state.slot, 42);
+    assert_eq!(state.upgrade_authority, Some(authority));
+
+    let frozen = ProgramDataState::parse(&program_data(42, None, b"\x7fELF")).unwrap();
+    assert_eq!(frozen.upgrade_authority, None);
+    assert_eq!(frozen.bytecode_hash, state.bytecode_hash);
+
+    // extending the account only appends zeros
+    let extended = ProgramDataState::parse(&program_data(42, None, b"\x7fELF\0\0\0\0")).unwrap();
+    assert_eq!(extended.bytecode_hash, state.bytecode_hash);
+    let other = ProgramDataState::parse(&program_data(42, None, b"\x7fELG")).unwrap();
+    assert_ne!(other.bytecode_hash, state.bytecode_hash);
+
+    let mut program = program_data(42, None, &[]);
+    program[0] = 2;
+    assert_eq!(ProgramDataState::parse(&program), None, "Program, not ProgramData");
+    assert_eq!(ProgramDataState::parse(&program_data(42, None, &[])[..44]), None);
+    let mut bad_option = program_data(42, None, &[]);
+    bad_option[12] = 2;
+    assert_eq!(ProgramDataState::parse(&bad_option), None);
+}
+
+#[test]
+fn detects_slot_and_bytecode_changes() {
+    let mut monitor = UpgradeMonitor::new();
+    let state = |slot, bytecode: &[u8]| ProgramDataState::parse(&program_data(slot, None, bytecode)).unwrap();
+
+    // first write seen long after the deployment only sets the baseline
+    assert_eq!(monitor.observe(Dex::RaydiumClmm, 1_000, state(10, b"v1")), None);
+    assert_eq!(monitor.observe(Dex::RaydiumClmm, 1_001, state(10, b"v1")), None);
+
+    let upgrade = monitor.observe(Dex::RaydiumClmm, 2_000, state(2_000, b"v2")).unwrap();
+    assert_eq!(upgrade.dex, Dex::RaydiumClmm);
+    assert_eq!(upgrade.previous, Some(state(10, b"v1")));
+    assert_eq!(upgrade.current, state(2_000, b"v2"));
+    assert_eq!(upgrade.seen_at, 2_000);
+
+    // same slot, different bytecode
+    assert!(monitor.observe(Dex::RaydiumClmm, 2_001, state(2_000, b"v3")).is_some());
+
+    // without a baseline a deployment in the slot of the update is still an upgrade
+    let upgrade = monitor.observe(Dex::MeteoraDlmm, 3_000, state(3_000, b"v1")).unwrap();
+    assert_eq!(upgrade.previous, None);
+
+    let mut seeded = UpgradeMonitor::new().with_baseline(Dex::SolFi, state(10, b"v1"));
+    assert!(seeded.observe(Dex::SolFi, 4_000, state(3_999, b"v2")).is_some());
+}
+
+#[test]
+fn raises_an_event_and_counts_upgrades() {
+    let metrics = Metrics::new();
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(UpgradeMonitor::new())
+        .with_handler(AccountDecoder::default());
+    let baseline = pipeline
+        .process(program_data_update(Dex::RaydiumClmm, 100, program_data(10, None, b"v1")))
+        .unwrap();
+    assert_eq!(skip_reason(baseline).unwrap(), "raydium_clmm program unchanged since slot 10");
+
+    match pipeline
+        .process(program_data_update(Dex::RaydiumClmm, 200, program_data(200, None, b"v2")))
+        .unwrap()
+    {
+        ProcessOutcome::Skipped { handler, reason, account } => {
+            assert_eq!(handler, UpgradeMonitor::NAME);
+            assert_eq!(reason, "raydium_clmm upgraded at slot 200");
+            assert_eq!(
+                account.events,
+                vec![PipelineEvent::ProgramUpgraded {
+                    dex: Dex::RaydiumClmm,
+                    program: Dex::RaydiumClmm.program_id(),
+                    deployed_slot: 200,
+                }]
+            );
+        }
+        other => panic!("expected a skip, got {:?}", other),
+    }
+    assert_eq!(metrics.counter("program_upgrades_total", &["raydium_clmm"]), 1);
+    assert_eq!(metrics.counter("decode_failures_total", &["unknown", "unknown", "unknown"]), 0);
+
+    // without a gate nothing is paused
+    assert!(skip_reason(pipeline.process(cpmm_pool()).unwrap()).is_none());
+}
+
+#[test]
+fn pauses_the_upgraded_dex_until_confirmed() {
+    let gate = UpgradeGate::new();
+    let metrics = Metrics::new();
+    let mut pipeline = Pipeline::new()
+        .with_metrics(metrics.clone())
+        .with_handler(UpgradeMonitor::new().with_gate(gate.clone()))
+        .with_handler(AccountDecoder::default());
+
+    assert!(skip_reason(pipeline.process(cpmm_pool()).unwrap()).is_none());
+
+    pipeline
+        .process(program_data_update(Dex::RaydiumCpmm, 300, program_data(300, None, b"v2")))
+        .unwrap();
+    assert!(gate.is_paused(Dex::RaydiumCpmm));
+    assert!(!gate.is_paused(Dex::RaydiumClmm));
+    let pending = gate.pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].current.slot, 300);
+
+    let reason = skip_reason(pipeline.process(cpmm_pool()).unwrap()).unwrap();
+    assert_eq!(reason, "raydium_cpmm paused after a program upgrade, waiting for confirmation");
+    assert_eq!(metrics.counter("decode_failures_total", &["raydium_cpmm", "unknown", "unknown"]), 0);
+
+    assert!(!gate.confirm(Dex::RaydiumClmm));
+    assert!(gate.confirm(Dex::RaydiumCpmm));
+    assert!(gate.pending().is_empty());
+    assert!(skip_reason(pipeline.process(cpmm_pool()).unwrap()).is_none());
+
+    // the next upgrade pauses it again
+    pipeline
+        .process(program_data_update(Dex::RaydiumCpmm, 400, program_data(400, None, b"v3")))
+        .unwrap();
+    assert_eq!(gate.confirm_all(), vec![Dex::RaydiumCpmm]);
+    assert!(!gate.is_paused(Dex::RaydiumCpmm));
+}
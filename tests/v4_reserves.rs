@@ -0,0 +1,78 @@
+use mevbot_ws_rust::{
+    dex::raydium_lp_v4::{calc_total_without_take_pnl, OpenOrdersLayout, RaydiumV4SideReserve, OPEN_ORDERS_ACCOUNT_SIZE},
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+};
+
+/// OpenOrders account bytes: "serum" padding, account flags, market and owner, then the four balances
+fn open_orders(coin_free: u64, coin_total: u64, pc_free: u64, pc_total: u64) -> OpenOrdersLayout {
+    let mut data = vec![0u8; OPEN_ORDERS_ACCOUNT_SIZE];
+    for (i, amount) in [coin_free, coin_total, pc_free, pc_total].into_iter().enumerate() {
+        let offset = 5 + 8 + 32 + 32 + i * 8;
+        data[offset..offset + 8].copy_from_slice(&amount.to_le_bytes());
+    }
+    OpenOrdersLayout::try_from_slice_manual(&data).unwrap()
+}
+
+fn state(base_need_take_pnl: u64, quote_need_take_pnl: u64) -> LIQUIDITY_STATE_LAYOUT_V4 {
+    LIQUIDITY_STATE_LAYOUT_V4 {
+        baseNeedTakePnl: base_need_take_pnl,
+        quoteNeedTakePnl: quote_need_take_pnl,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn totals_add_open_orders_and_subtract_pnl() {
+    let reserves =
+        calc_total_without_take_pnl(1_000_000, 5_000_000, &open_orders(100, 250, 2_000, 3_500), &state(40, 700)).unwrap();
+    assert_eq!(
+        reserves.base,
+        RaydiumV4SideReserve {
+            vault: 1_000_000,
+            open_orders_free: 100,
+            open_orders_locked: 150,
+            need_take_pnl: 40,
+            total: 1_000_000 + 250 - 40,
+        }
+    );
+    assert_eq!(
+        reserves.quote,
+        RaydiumV4SideReserve {
+            vault: 5_000_000,
+            open_orders_free: 2_000,
+            open_orders_locked: 1_500,
+            need_take_pnl: 700,
+            total: 5_000_000 + 3_500 - 700,
+        }
+    );
+}
+
+#[test]
+fn pools_without_orders_are_their_vaults() {
+    let reserves = calc_total_without_take_pnl(42, 7, &open_orders(0, 0, 0, 0), &state(0, 0)).unwrap();
+    assert_eq!((reserves.base.total, reserves.quote.total), (42, 7));
+    assert_eq!(reserves.base.open_orders_locked, 0);
+
+    // the whole balance may be owed to the protocol
+    let reserves = calc_total_without_take_pnl(42, 7, &open_orders(0, 0, 0, 0), &state(42, 7)).unwrap();
+    assert_eq!((reserves.base.total, reserves.quote.total), (0, 0));
+}
+
+#[test]
+fn overflow_and_underflow_fail_like_the_program() {
+    // pnl larger than vault + open orders (CheckedSubOverflow)
+    assert_eq!(calc_total_without_take_pnl(10, 10, &open_orders(0, 5, 0, 5), &state(16, 0)), None);
+    assert_eq!(calc_total_without_take_pnl(10, 10, &open_orders(0, 5, 0, 5), &state(0, 16)), None);
+    // vault + open orders past u64 (CheckedAddOverflow)
+    assert_eq!(calc_total_without_take_pnl(u64::MAX, 0, &open_orders(0, 1, 0, 0), &state(0, 0)), None);
+    assert_eq!(calc_total_without_take_pnl(0, u64::MAX, &open_orders(0, 0, 0, 1), &state(0, 0)), None);
+    // an overflowing sum is not rescued by the pnl subtracted afterwards
+    assert_eq!(calc_total_without_take_pnl(u64::MAX, 0, &open_orders(0, 1, 0, 0), &state(1, 0)), None);
+}
+
+#[test]
+fn free_above_total_does_not_underflow_locked() {
+    let reserves = calc_total_without_take_pnl(0, 0, &open_orders(9, 4, 0, 0), &state(0, 0)).unwrap();
+    assert_eq!(reserves.base.open_orders_locked, 0);
+    assert_eq!(reserves.base.total, 4);
+}
@@ -0,0 +1,123 @@
+use solana_program::clock::Clock;
+
+use mevbot_ws_rust::instruction::decoder::{AmmState, AmmStatus, LIQUIDITY_STATE_LAYOUT_V4};
+
+const OPEN: u64 = 1_700_000_000;
+
+fn pool(status: u64, open_time: u64) -> LIQUIDITY_STATE_LAYOUT_V4 {
+    LIQUIDITY_STATE_LAYOUT_V4 {
+        status,
+        poolOpenTime: open_time,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn status_and_state_round_trip_their_raw_values() {
+    let statuses = [
+        AmmStatus::Uninitialized,
+        AmmStatus::Initialized,
+        AmmStatus::Disabled,
+        AmmStatus::WithdrawOnly,
+        AmmStatus::LiquidityOnly,
+        AmmStatus::OrderBookOnly,
+        AmmStatus::SwapOnly,
+        AmmStatus::WaitingTrade,
+    ];
+    for (raw, status) in statuses.into_iter().enumerate() {
+        assert_eq!(AmmStatus::try_from(raw as u64), Ok(status));
+        assert_eq!(pool(raw as u64, 0).amm_status(), Ok(status));
+    }
+    assert_eq!(AmmStatus::try_from(8), Err(8));
+
+    let states = [
+        AmmState::Invalid,
+        AmmState::Idle,
+        AmmState::CancelAllOrders,
+        AmmState::PlanOrders,
+        AmmState::CancelOrder,
+        AmmState::PlaceOrders,
+        AmmState::PurgeOrder,
+        AmmState::WithdrawTransfer,
+    ];
+    for (raw, state) in states.into_iter().enumerate() {
+        assert_eq!(AmmState::try_from(raw as u64), Ok(state));
+        let layout = LIQUIDITY_STATE_LAYOUT_V4 {
+            state: raw as u64,
+            ..Default::default()
+        };
+        assert_eq!(layout.amm_state(), Ok(state));
+    }
+    assert_eq!(AmmState::try_from(u64::MAX), Err(u64::MAX));
+}
+
+#[test]
+fn permissions_per_status() {
+    // (status, deposit, withdraw, swap, orderbook)
+    let table = [
+        (AmmStatus::Uninitialized, false, false, false, false),
+        (AmmStatus::Initialized, true, true, true, true),
+        (AmmStatus::Disabled, false, false, false, false),
+        (AmmStatus::WithdrawOnly, false, true, false, false),
+        (AmmStatus::LiquidityOnly, true, true, false, false),
+        (AmmStatus::OrderBookOnly, true, true, false, true),
+        (AmmStatus::SwapOnly, true, true, true, false),
+        (AmmStatus::WaitingTrade, true, true, true, false),
+    ];
+    for (status, deposit, withdraw, swap, orderbook) in table {
+        assert_eq!(status.deposit_permission(), deposit, "{:?} deposit", status);
+        assert_eq!(status.withdraw_permission(), withdraw, "{:?} withdraw", status);
+        assert_eq!(status.swap_permission(), swap, "{:?} swap", status);
+        assert_eq!(status.orderbook_permission(), orderbook, "{:?} orderbook", status);
+        assert_eq!(pool(status as u64, 0).orderbook_enabled(), orderbook, "{:?} orderbook_enabled", status);
+    }
+    assert!(!pool(99, 0).orderbook_enabled());
+}
+
+#[test]
+fn waiting_trade_opens_at_pool_open_time() {
+    let waiting = pool(AmmStatus::WaitingTrade as u64, OPEN);
+    let now = OPEN as i64;
+
+    assert!(!waiting.can_swap_at(now - 1));
+    assert_eq!(waiting.seconds_until_open(now - 1), Some(1));
+    assert_eq!(waiting.seconds_until_open(now - 3_600), Some(3_600));
+
+    assert!(waiting.can_swap_at(now));
+    assert_eq!(waiting.seconds_until_open(now), None);
+    assert!(waiting.can_swap_at(now + 1));
+    assert_eq!(waiting.seconds_until_open(now + 1), None);
+
+    // a clock before the epoch never reaches the open time
+    assert!(!waiting.can_swap_at(-1));
+    assert_eq!(waiting.seconds_until_open(-1), Some(OPEN));
+
+    let clock = Clock {
+        unix_timestamp: now,
+        ..Default::default()
+    };
+    assert!(waiting.can_swap(&clock));
+}
+
+#[test]
+fn other_statuses_ignore_pool_open_time() {
+    for status in [AmmStatus::Initialized, AmmStatus::SwapOnly] {
+        let open = pool(status as u64, OPEN);
+        assert!(open.can_swap_at(0), "{:?}", status);
+        assert_eq!(open.seconds_until_open(0), None);
+    }
+    for status in [
+        AmmStatus::Uninitialized,
+        AmmStatus::Disabled,
+        AmmStatus::WithdrawOnly,
+        AmmStatus::LiquidityOnly,
+        AmmStatus::OrderBookOnly,
+    ] {
+        let closed = pool(status as u64, OPEN);
+        assert!(!closed.can_swap_at(OPEN as i64 + 1), "{:?}", status);
+        assert_eq!(closed.seconds_until_open(0), None, "{:?} never opens", status);
+    }
+    let unknown = pool(42, 0);
+    assert!(!unknown.can_swap_at(OPEN as i64));
+    assert_eq!(unknown.seconds_until_open(0), None);
+}
@@ -0,0 +1,130 @@
+use std::{mem::offset_of, path::PathBuf};
+
+use mevbot_ws_rust::{
+    common::view::AccountView,
+    dex::{
+        meteora_dlmm::{MeteoraLbPairView, METEORA_DLMM_POOL_SIZE},
+        raydium_clmm::{RaydiumClmmPoolView, RAYDIUM_CLMM_POOL_SIZE},
+        raydium_cpmm::{RaydiumCpPoolView, RAYDIUM_CP_POOL_SIZE},
+        spl_token::{SplTokenAccountView, SPL_TOKEN_ACCOUNT_SIZE},
+    },
+    dex_processor::DecodedAccount,
+    instruction::decoder::LIQUIDITY_STATE_LAYOUT_V4,
+    testing::Snapshot,
+};
+
+fn fixture(layout: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/layouts/{}.json", layout));
+    Snapshot::load(path).unwrap().accounts.into_values().next().unwrap().data
+}
+
+#[test]
+fn sizes_and_alignment_match_the_accounts() {
+    assert_eq!(<LIQUIDITY_STATE_LAYOUT_V4 as AccountView>::SIZE, 752);
+    assert_eq!(RaydiumCpPoolView::SIZE, RAYDIUM_CP_POOL_SIZE);
+    assert_eq!(RaydiumClmmPoolView::SIZE, RAYDIUM_CLMM_POOL_SIZE);
+    assert_eq!(MeteoraLbPairView::SIZE, METEORA_DLMM_POOL_SIZE);
+    assert_eq!(SplTokenAccountView::SIZE, SPL_TOKEN_ACCOUNT_SIZE);
+
+    // packed, so a view can be borrowed at any address
+    assert_eq!(std::mem::align_of::<LIQUIDITY_STATE_LAYOUT_V4>(), 1);
+    assert_eq!(std::mem::align_of::<RaydiumCpPoolView>(), 1);
+    assert_eq!(std::mem::align_of::<RaydiumClmmPoolView>(), 1);
+    assert_eq!(std::mem::align_of::<MeteoraLbPairView>(), 1);
+    assert_eq!(std::mem::align_of::<SplTokenAccountView>(), 1);
+}
+
+#[test]
+fn field_offsets_match_the_on_chain_layouts() {
+    assert_eq!(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, swapQuoteInAmount), 296);
+    assert_eq!(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, baseVault), 336);
+    assert_eq!(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, quoteMint), 432);
+    assert_eq!(offset_of!(LIQUIDITY_STATE_LAYOUT_V4, marketProgramId), 560);
+
+    assert_eq!(offset_of!(RaydiumCpPoolView, amm_config), 8);
+    assert_eq!(offset_of!(RaydiumCpPoolView, token0_vault), 72);
+    assert_eq!(offset_of!(RaydiumCpPoolView, token0_mint), 168);
+    assert_eq!(offset_of!(RaydiumCpPoolView, observation_key), 296);
+    assert_eq!(offset_of!(RaydiumCpPoolView, auth_bump), 328);
+    assert_eq!(offset_of!(RaydiumCpPoolView, lp_supply), 333);
+    assert_eq!(offset_of!(RaydiumCpPoolView, open_time), 373);
+
+    assert_eq!(offset_of!(RaydiumClmmPoolView, amm_config), 9);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, token_mint0), 73);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, token_vault0), 137);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, tick_spacing), 235);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, liquidity), 237);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, sqrt_price_x64), 253);
+    assert_eq!(offset_of!(RaydiumClmmPoolView, tick_current), 269);
+
+    assert_eq!(offset_of!(MeteoraLbPairView, active_id), 76);
+    assert_eq!(offset_of!(MeteoraLbPairView, bin_step), 80);
+    assert_eq!(offset_of!(MeteoraLbPairView, token_x_mint), 88);
+    assert_eq!(offset_of!(MeteoraLbPairView, reserve_x), 152);
+    assert_eq!(offset_of!(MeteoraLbPairView, reserve_y), 184);
+    assert_eq!(offset_of!(MeteoraLbPairView, oracle), 552);
+
+    assert_eq!(offset_of!(SplTokenAccountView, owner), 32);
+    assert_eq!(offset_of!(SplTokenAccountView, amount), 64);
+    assert_eq!(offset_of!(SplTokenAccountView, state), 108);
+    assert_eq!(offset_of!(SplTokenAccountView, close_authority), 133);
+}
+
+#[test]
+fn views_read_the_same_values_as_the_decoders() {
+    let data = fixture("raydium_cpmm");
+    let view = RaydiumCpPoolView::view(&data).unwrap();
+    let Some(DecodedAccount::RaydiumCpmm(owned)) = DecodedAccount::decode_layout("raydium_cpmm", &data) else {
+        panic!("cpmm fixture does not decode");
+    };
+    assert_eq!({ view.token0_vault }, owned.token0Vault);
+    assert_eq!({ view.token1_mint }, owned.token1Mint);
+    assert_eq!({ view.lp_supply }, owned.lpSupply);
+    assert_eq!({ view.open_time }, owned.openTime);
+
+    let data = fixture("raydium_clmm");
+    let view = RaydiumClmmPoolView::view(&data).unwrap();
+    let Some(DecodedAccount::RaydiumClmm(owned)) = DecodedAccount::decode_layout("raydium_clmm", &data) else {
+        panic!("clmm fixture does not decode");
+    };
+    assert_eq!({ view.sqrt_price_x64 }, owned.sqrt_price_x64);
+    assert_eq!({ view.tick_current }, owned.tick_current);
+    assert_eq!({ view.liquidity }, owned.liquidity);
+
+    let data = fixture("meteora_dlmm_lb_pair");
+    let view = MeteoraLbPairView::view(&data).unwrap();
+    let Some(DecodedAccount::MeteoraDlmm(owned)) = DecodedAccount::decode_layout("meteora_dlmm_lb_pair", &data) else {
+        panic!("lb pair fixture does not decode");
+    };
+    assert_eq!({ view.active_id }, owned.active_id);
+    assert_eq!({ view.reserve_y }, owned.reserve_y);
+    assert_eq!({ view.oracle }, owned.oracle);
+
+    let data = fixture("spl_token_account");
+    let view = SplTokenAccountView::view(&data).unwrap();
+    let Some(DecodedAccount::SplToken(owned)) = DecodedAccount::decode_layout("spl_token_account", &data) else {
+        panic!("token fixture does not decode");
+    };
+    assert_eq!({ view.amount }, owned.amount);
+    assert_eq!({ view.mint }, owned.mint);
+}
+
+#[test]
+fn views_check_size_discriminator_and_offset() {
+    let data = fixture("raydium_cpmm");
+    assert!(RaydiumCpPoolView::view(&data[..RAYDIUM_CP_POOL_SIZE - 1]).is_none());
+    assert!(RaydiumCpPoolView::view(&[]).is_none());
+    let mut wrong = data.clone();
+    wrong[0] ^= 1;
+    assert!(RaydiumCpPoolView::view(&wrong).is_none());
+    // the Meteora discriminator differs from the Raydium one
+    assert!(MeteoraLbPairView::view(&data).is_none());
+
+    // unaligned bytes and trailing data (Token-2022 extensions) are fine
+    let token = fixture("spl_token_account");
+    let mut shifted = vec![0u8];
+    shifted.extend(&token);
+    shifted.extend([7; 10]);
+    let view = SplTokenAccountView::view(&shifted[1..]).unwrap();
+    assert_eq!({ view.amount }, { SplTokenAccountView::view(&token).unwrap().amount });
+}